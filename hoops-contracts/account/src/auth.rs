@@ -0,0 +1,353 @@
+//! Signers and `__check_auth` support for the `Account` custom account.
+//!
+//! Admin signers (the owner address and ed25519 keys or secp256r1 passkeys
+//! added as `SignerRole::Admin`) may authorize anything. Session signers are
+//! limited to the contracts/functions in their `Session` until it expires,
+//! with optional per-period spend limits read from a call argument. A session
+//! permission is matched against the root call it signs; the calls nested
+//! under that root inherit it. Whoever signs, token transfers and approvals
+//! from the account in the signed tree go through the spending policies.
+
+use soroban_sdk::{
+    auth::{Context, ContractContext},
+    contracttype,
+    crypto::Hash,
+    Address, Bytes, BytesN, Env, IntoVal, Map, Symbol, TryFromVal, Vec,
+};
+
+use crate::{bump, policy, AccountError, Key};
+
+/// Largest WebAuthn `clientDataJSON` accepted.
+const MAX_CLIENT_DATA: usize = 1024;
+/// User-present flag in the WebAuthn authenticator data.
+const FLAG_UP: u8 = 0x01;
+
+/// Storage key of a signer.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+pub enum SignerKey {
+    /// The account's `owner` address, checked with `require_auth_for_args`.
+    Owner,
+    Ed25519(BytesN<32>),
+    /// WebAuthn credential id.
+    Secp256r1(Bytes),
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Signer {
+    Ed25519(BytesN<32>),
+    /// Credential id and uncompressed SEC-1 public key.
+    Secp256r1(Bytes, BytesN<65>),
+}
+
+impl Signer {
+    pub fn key(&self) -> SignerKey {
+        match self {
+            Signer::Ed25519(pk) => SignerKey::Ed25519(pk.clone()),
+            Signer::Secp256r1(id, _) => SignerKey::Secp256r1(id.clone()),
+        }
+    }
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct WebAuthnSignature {
+    pub authenticator_data: Bytes,
+    pub client_data_json: Bytes,
+    pub signature: BytesN<64>,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Signature {
+    Owner,
+    Ed25519(BytesN<64>),
+    Secp256r1(WebAuthnSignature),
+}
+
+/// Signatures passed to `__check_auth`, one per signer.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Signatures(pub Map<SignerKey, Signature>);
+
+/// Caps the sum of argument `amount_arg` over every `period` seconds.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SpendLimit {
+    pub amount_arg: u32,
+    pub amount: i128,
+    pub period: u64,
+}
+
+/// A call a session signer may authorize. `fn_name: None` allows any
+/// function on `contract`; every entry in `limits` must hold.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Permission {
+    pub contract: Address,
+    pub fn_name: Option<Symbol>,
+    pub limits: Vec<SpendLimit>,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Session {
+    pub expires_at: u64,
+    pub permissions: Vec<Permission>,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum SignerRole {
+    Admin,
+    Session(Session),
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SignerEntry {
+    pub signer: Signer,
+    pub role: SignerRole,
+}
+
+pub fn get_signer(e: &Env, key: &SignerKey) -> Option<SignerEntry> {
+    let k = Key::Signer(key.clone());
+    let entry = e.storage().persistent().get(&k);
+    if entry.is_some() {
+        bump(e, &k);
+    }
+    entry
+}
+
+/// Keys of every registered signer.
+pub fn signer_keys(e: &Env) -> Vec<SignerKey> {
+    e.storage().persistent().get(&Key::Signers).unwrap_or(Vec::new(e))
+}
+
+pub fn set_signer(e: &Env, entry: &SignerEntry) {
+    let key = entry.signer.key();
+    let mut keys = signer_keys(e);
+    if !keys.contains(&key) {
+        keys.push_back(key.clone());
+        e.storage().persistent().set(&Key::Signers, &keys);
+    }
+    bump(e, &Key::Signers);
+    let k = Key::Signer(key);
+    e.storage().persistent().set(&k, entry);
+    bump(e, &k);
+}
+
+/// Drops a signer along with the spend windows of its session.
+pub fn remove_signer(e: &Env, key: &SignerKey) {
+    let Some(entry) = get_signer(e, key) else { return };
+    if let SignerRole::Session(session) = entry.role {
+        for (i, p) in session.permissions.iter().enumerate() {
+            for j in 0..p.limits.len() {
+                e.storage().persistent().remove(&Key::Spent(key.clone(), i as u32, j));
+            }
+        }
+    }
+    e.storage().persistent().remove(&Key::Signer(key.clone()));
+    let mut keys = signer_keys(e);
+    if let Some(i) = keys.first_index_of(key) {
+        keys.remove(i);
+        e.storage().persistent().set(&Key::Signers, &keys);
+    }
+}
+
+/// Sessions must be live and may not reach back into the account itself.
+pub fn check_session(e: &Env, session: &Session) -> Result<(), AccountError> {
+    if session.expires_at <= e.ledger().timestamp() {
+        return Err(AccountError::SessionExpired);
+    }
+    let me = e.current_contract_address();
+    for p in session.permissions.iter() {
+        if p.contract == me {
+            return Err(AccountError::NotPermitted);
+        }
+        if p.limits.iter().any(|l| l.amount < 0 || l.period == 0) {
+            return Err(AccountError::InvalidLimit);
+        }
+    }
+    Ok(())
+}
+
+pub fn check_auth(
+    e: &Env,
+    payload: &Hash<32>,
+    signatures: &Signatures,
+    contexts: &Vec<Context>,
+) -> Result<(), AccountError> {
+    if signatures.0.is_empty() {
+        return Err(AccountError::UnknownSigner);
+    }
+    let mut admin = false;
+    let mut sessions: Vec<(SignerKey, Session)> = Vec::new(e);
+    for (key, sig) in signatures.0.iter() {
+        match verify(e, payload, &key, &sig)? {
+            None => admin = true,
+            Some(session) => sessions.push_back((key, session)),
+        }
+    }
+    check_policies(e, contexts)?;
+    if admin {
+        return Ok(());
+    }
+
+    // Contexts list the signed tree depth first. Only the root call is
+    // matched and charged; the calls it makes with the account's
+    // authorization (the token transfer inside a swap) are covered by it
+    let Some(Context::Contract(root)) = contexts.first() else {
+        return Err(AccountError::NotPermitted);
+    };
+    if contexts.iter().any(|ctx| !matches!(ctx, Context::Contract(_))) {
+        return Err(AccountError::NotPermitted);
+    }
+    let now = e.ledger().timestamp();
+    for (key, session) in sessions.iter() {
+        if session.expires_at <= now {
+            return Err(AccountError::SessionExpired);
+        }
+        if let Some(i) = find_permission(&session, &root) {
+            return spend(e, &key, i, &session.permissions.get_unchecked(i), &root, now);
+        }
+    }
+    Err(AccountError::NotPermitted)
+}
+
+/// Runs `policy::check_call` on each token `transfer` / `approve` from the
+/// account in the signed tree. A nested one is made by the root call on the
+/// account's behalf, so the root must be an allowed router.
+fn check_policies(e: &Env, contexts: &Vec<Context>) -> Result<(), AccountError> {
+    let me = e.current_contract_address();
+    for (i, ctx) in contexts.iter().enumerate() {
+        let Context::Contract(call) = ctx else { continue };
+        if call.contract == me || !policy::is_token_op(e, &call.fn_name, &call.args) {
+            continue;
+        }
+        let nested = i > 0;
+        if let (true, Some(Context::Contract(root))) = (nested, contexts.first()) {
+            policy::check_router(e, &root.contract)?;
+        }
+        policy::check_call(e, &call.contract, &call.fn_name, &call.args, nested)?;
+    }
+    Ok(())
+}
+
+/// Checks one signature, returning the signer's session (`None` for admins).
+fn verify(
+    e: &Env,
+    payload: &Hash<32>,
+    key: &SignerKey,
+    sig: &Signature,
+) -> Result<Option<Session>, AccountError> {
+    if *key == SignerKey::Owner {
+        if *sig != Signature::Owner {
+            return Err(AccountError::InvalidSignature);
+        }
+        let owner: Address = e.storage().instance().get(&Key::Owner).ok_or(AccountError::NotInitialized)?;
+        let payload: BytesN<32> = payload.to_bytes();
+        owner.require_auth_for_args((payload,).into_val(e));
+        return Ok(None);
+    }
+    let entry = get_signer(e, key).ok_or(AccountError::UnknownSigner)?;
+    match (&entry.signer, sig) {
+        (Signer::Ed25519(pk), Signature::Ed25519(s)) => {
+            e.crypto().ed25519_verify(pk, &payload.to_bytes().into(), s);
+        }
+        (Signer::Secp256r1(_, pk), Signature::Secp256r1(s)) => verify_webauthn(e, payload, pk, s)?,
+        _ => return Err(AccountError::InvalidSignature),
+    }
+    match entry.role {
+        SignerRole::Admin => Ok(None),
+        SignerRole::Session(session) => Ok(Some(session)),
+    }
+}
+
+/// WebAuthn assertion: the client data must carry the payload as its
+/// challenge, the user must be present, and the signature covers
+/// `authenticator_data || sha256(client_data_json)`.
+fn verify_webauthn(
+    e: &Env,
+    payload: &Hash<32>,
+    pk: &BytesN<65>,
+    s: &WebAuthnSignature,
+) -> Result<(), AccountError> {
+    let len = s.client_data_json.len() as usize;
+    if len > MAX_CLIENT_DATA || s.authenticator_data.len() < 37 {
+        return Err(AccountError::InvalidSignature);
+    }
+    if s.authenticator_data.get_unchecked(32) & FLAG_UP == 0 {
+        return Err(AccountError::InvalidSignature);
+    }
+    let mut json = [0u8; MAX_CLIENT_DATA];
+    s.client_data_json.copy_into_slice(&mut json[..len]);
+
+    let mut needle = [0u8; 57];
+    needle[..13].copy_from_slice(b"\"challenge\":\"");
+    base64url(&payload.to_array(), &mut needle[13..56]);
+    needle[56] = b'"';
+    if !json[..len].windows(needle.len()).any(|w| w == needle) {
+        return Err(AccountError::InvalidSignature);
+    }
+
+    let mut msg = s.authenticator_data.clone();
+    msg.append(&e.crypto().sha256(&s.client_data_json).to_bytes().into());
+    e.crypto().secp256r1_verify(pk, &e.crypto().sha256(&msg), &s.signature);
+    Ok(())
+}
+
+/// Unpadded base64url of 32 bytes (43 characters).
+fn base64url(input: &[u8; 32], out: &mut [u8]) {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+    let mut o = 0;
+    for chunk in input.chunks(3) {
+        let b = [chunk[0], *chunk.get(1).unwrap_or(&0), *chunk.get(2).unwrap_or(&0)];
+        let n = (b[0] as u32) << 16 | (b[1] as u32) << 8 | b[2] as u32;
+        for i in 0..=chunk.len() {
+            out[o] = ALPHABET[(n >> (18 - 6 * i) & 63) as usize];
+            o += 1;
+        }
+    }
+}
+
+fn find_permission(session: &Session, call: &ContractContext) -> Option<u32> {
+    session.permissions.iter().position(|p| {
+        p.contract == call.contract && p.fn_name.as_ref().is_none_or(|f| *f == call.fn_name)
+    }).map(|i| i as u32)
+}
+
+/// Charges the call's amount against each of a permission's spend limits,
+/// starting a new window once the previous one has run for `period` seconds.
+fn spend(
+    e: &Env,
+    key: &SignerKey,
+    index: u32,
+    permission: &Permission,
+    call: &ContractContext,
+    now: u64,
+) -> Result<(), AccountError> {
+    for (j, limit) in permission.limits.iter().enumerate() {
+        let amount = call
+            .args
+            .get(limit.amount_arg)
+            .and_then(|v| i128::try_from_val(e, &v).ok())
+            .ok_or(AccountError::NotPermitted)?;
+        if amount < 0 {
+            return Err(AccountError::NotPermitted);
+        }
+        let k = Key::Spent(key.clone(), index, j as u32);
+        let (mut start, mut spent): (u64, i128) = e.storage().persistent().get(&k).unwrap_or((now, 0));
+        if now >= start + limit.period {
+            (start, spent) = (now, 0);
+        }
+        spent += amount;
+        if spent > limit.amount {
+            return Err(AccountError::LimitExceeded);
+        }
+        e.storage().persistent().set(&k, &(start, spent));
+        bump(e, &k);
+    }
+    Ok(())
+}
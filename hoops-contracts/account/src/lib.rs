@@ -1,10 +1,26 @@
 #![no_std]
 
 use soroban_sdk::{
+    auth::{Context, ContractContext, CustomAccountInterface, InvokerContractAuthEntry, SubContractInvocation},
+    crypto::Hash,
     contract, contractimpl, contracttype, symbol_short, contracterror,
-    token::Client as TokenClient, Address, BytesN, Env, Vec,
+    token::Client as TokenClient, Address, BytesN, Env, IntoVal, Map, Symbol, Val, Vec, vec,
 };
 
+mod auth;
+pub use auth::{
+    Permission, Session, Signature, Signatures, Signer, SignerEntry, SignerKey, SignerRole,
+    SpendLimit, WebAuthnSignature,
+};
+mod policy;
+pub use policy::{Outflow, PendingChange, PendingTransfer, Policy, PolicyChange, TokenPolicy};
+mod positions;
+pub use positions::Position;
+mod strategy;
+pub use strategy::{Strategy, StrategyAction, RATE_SCALE};
+mod orders;
+pub use orders::{Direction, Order, SwapOrder, UnwindOrder};
+
 pub mod hoops_router {
     soroban_sdk::contractimport!(
         file = "../bytecodes/hoops_router.wasm"
@@ -20,40 +36,199 @@ use hoops_router::{LpPlan, RouterClient};
 pub enum AccountError {
     AlreadyInitialized = 1,
     NotAuthorized = 2,
+    NotInitialized = 3,
+    UnknownSigner = 4,
+    InvalidSignature = 5,
+    SessionExpired = 6,
+    NotPermitted = 7,
+    LimitExceeded = 8,
+    InvalidLimit = 9,
+    RecipientNotAllowed = 10,
+    RouterNotAllowed = 11,
+    TimelockRequired = 12,
+    TimelockActive = 13,
+    UnknownTransfer = 14,
+    InvalidAmount = 15,
+    EmptyBatch = 16,
+    NotGuardian = 17,
+    InvalidThreshold = 18,
+    RecoveryPending = 19,
+    NoRecovery = 20,
+    AlreadyApproved = 21,
+    RecoveryNotReady = 22,
+    InvalidStrategy = 23,
+    UnknownStrategy = 24,
+    NotDue = 25,
+    NoQuote = 26,
+    SlippageExceeded = 27,
+    InvalidOrder = 28,
+    UnknownOrder = 29,
+    ConditionNotMet = 30,
+    NoPosition = 31,
+    NoDeployer = 32,
+    NoVersion = 33,
+    UnknownChange = 34,
+    DuplicateGuardian = 35,
+    InvalidDelay = 36,
+    UnapprovedWasm = 37,
 }
 
 #[contracttype]
 #[derive(Clone)]
-enum Key { Owner, Router }
+enum Key {
+    Owner,
+    Router,
+    Signer(SignerKey),
+    /// Keys of all registered signers, so recovery can revoke them.
+    Signers,
+    /// Spend window `(start, spent)` of a session permission's limit.
+    Spent(SignerKey, u32, u32),
+    Policy,
+    TokenPolicy(Address),
+    Outflow(Address),
+    Pending(u32),
+    NextTransfer,
+    PendingChange(u32),
+    NextChange,
+    /// USDC token used for cost basis and portfolio value.
+    Usdc,
+    Position(Address),
+    /// LP tokens with an open position.
+    Positions,
+    Guardians,
+    Recovery,
+    Strategy(u32),
+    NextStrategy,
+    Order(u32),
+    NextOrder,
+    /// Deployer whose approved WASM versions `upgrade_to_latest` follows.
+    Deployer,
+}
+
+const DAY_IN_LEDGERS: u32 = 17_280;
+const TTL_THRESHOLD: u32 = 30 * DAY_IN_LEDGERS;
+const TTL_BUMP: u32 = 120 * DAY_IN_LEDGERS;
+/// Shortest recovery delay, so the owner always has a day to cancel.
+pub const MIN_RECOVERY_DELAY: u64 = 86_400;
+
+/// Keeps a persistent entry alive; called whenever one is read or written.
+fn bump(e: &Env, key: &Key) {
+    e.storage().persistent().extend_ttl(key, TTL_THRESHOLD, TTL_BUMP);
+}
 
 #[contracttype]
 #[derive(Clone)]
 pub struct TokenEvent { pub token: Address, pub amount: i128 }
 
+#[contracttype]
+#[derive(Clone)]
+pub struct SignerEvent { pub key: SignerKey, pub role: SignerRole }
+
+#[contracttype]
+#[derive(Clone)]
+pub struct PendingEvent { pub id: u32, pub transfer: PendingTransfer }
+
+#[contracttype]
+#[derive(Clone)]
+pub struct ChangeEvent { pub id: u32, pub change: PendingChange }
+
+#[contracttype]
+#[derive(Clone)]
+pub struct OwnerEvent { pub old: Address, pub new: Address }
+
+#[contracttype]
+#[derive(Clone)]
+pub struct StrategyEvent { pub id: u32, pub strategy: Strategy }
+
+#[contracttype]
+#[derive(Clone)]
+pub struct RunEvent { pub id: u32, pub keeper: Address, pub fee: i128 }
+
+#[contracttype]
+#[derive(Clone)]
+pub struct OrderEvent { pub id: u32, pub order: Order }
+
+#[contracttype]
+#[derive(Clone)]
+pub struct FillEvent { pub id: u32, pub amount: i128 }
+
+/// Addresses that can together hand the account to a new owner.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Guardians {
+    pub members: Vec<Address>,
+    /// Approvals needed to execute a recovery.
+    pub threshold: u32,
+    /// Seconds between starting a recovery and executing it.
+    pub delay: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Recovery {
+    pub new_owner: Address,
+    pub approvals: Vec<Address>,
+    pub unlock_at: u64,
+}
+
 #[contract]
 pub struct Account;
 
 #[contractimpl]
 impl Account {
     /* ---- lifecycle ---- */
-    pub fn initialize(e: Env, owner: Address, router: Address) -> Result<(), AccountError> {
+    /// `deployer` is set when deployed through `hoops-account-deployer`,
+    /// whose registry of approved WASM versions bounds every upgrade. It
+    /// cannot be changed later.
+    pub fn initialize(e: Env, owner: Address, router: Address, deployer: Option<Address>) -> Result<(), AccountError> {
         if e.storage().instance().has(&Key::Owner) {
             return Err(AccountError::AlreadyInitialized)
         }
         owner.require_auth();
         e.storage().instance().set(&Key::Owner, &owner);
         e.storage().instance().set(&Key::Router, &router);
+        if let Some(deployer) = deployer {
+            e.storage().instance().set(&Key::Deployer, &deployer);
+        }
         Ok(())
     }
+    /// Moves to `wasm`, which must be a version approved in the deployer's
+    /// registry, e.g. to roll back to an earlier one.
     pub fn upgrade(e: Env, wasm: BytesN<32>) -> Result<(), AccountError> {
-        Self::owner(&e).require_auth();
-        e.deployer().update_current_contract_wasm(wasm);
+        Self::require_admin(&e);
+        let deployer = Self::deployer(&e).ok_or(AccountError::NoDeployer)?;
+        let version: Option<u32> =
+            e.invoke_contract(&deployer, &Symbol::new(&e, "version_of"), (wasm.clone(),).into_val(&e));
+        if version.is_none() {
+            return Err(AccountError::UnapprovedWasm)
+        }
+        e.deployer().update_current_contract_wasm(wasm.clone());
+        e.events().publish(("acct", symbol_short!("upgrade")), wasm);
         Ok(())
     }
 
+    /// Moves to the latest account WASM approved in the deployer's registry.
+    pub fn upgrade_to_latest(e: Env) -> Result<BytesN<32>, AccountError> {
+        Self::require_admin(&e);
+        let deployer = Self::deployer(&e).ok_or(AccountError::NoDeployer)?;
+        let wasm: Option<BytesN<32>> =
+            e.invoke_contract(&deployer, &Symbol::new(&e, "latest_wasm"), Vec::new(&e));
+        let wasm = wasm.ok_or(AccountError::NoVersion)?;
+        e.deployer().update_current_contract_wasm(wasm.clone());
+        e.events().publish(("acct", symbol_short!("upgrade")), wasm.clone());
+        Ok(wasm)
+    }
+
     /* ---- token passthrough ---- */
+    /// Sends `amount` of `token` to an allowed recipient, within the token's
+    /// limits. Amounts above the token's timelock threshold must be queued.
     pub fn transfer(e: Env, token: Address, to: Address, amount: i128) -> Result<(), AccountError> {
-        Self::owner(&e).require_auth();
+        Self::require_admin(&e);
+        policy::check_recipient(&e, &to)?;
+        if policy::needs_timelock(&e, &token, amount) {
+            return Err(AccountError::TimelockRequired)
+        }
+        policy::charge(&e, &token, amount)?;
         TokenClient::new(&e,&token)
             .transfer(&e.current_contract_address(), &to, &amount);
         e.events().publish(("acct", symbol_short!("xfer")),
@@ -62,51 +237,627 @@ impl Account {
     }
 
     /* ---- liquidity flow  (one token in, LP out) ---- */
+    /// Pulls `amount` of `usdc` from the owner and provides it through the
+    /// router. The router's allowance expires at `expiration_ledger`; the
+    /// router call itself must land before `deadline_ts` (ledger timestamp).
     pub fn deposit(
         e: Env,
         usdc: Address,
         amount: i128,
         lp_plans: Vec<LpPlan>,
-        deadline: u32,
+        expiration_ledger: u32,
+        deadline_ts: u64,
     ) -> Result<(), AccountError> {
-        let owner = Self::owner(&e);
-        owner.require_auth();
-        let tk = TokenClient::new(&e,&usdc);
-        tk.transfer(&owner, &e.current_contract_address(), &amount);
-        tk.approve(&e.current_contract_address(), &Self::router(&e), &amount, &deadline);
-        
-        let router_client = RouterClient::new(&e, &Self::router(&e));
-        router_client.provide_liquidity(&amount, &lp_plans, &e.current_contract_address(), &(deadline as u64));
-        
-        e.events().publish(("acct", symbol_short!("dep")),
-            TokenEvent{ token: usdc, amount });
-        Ok(())
+        Self::require_admin(&e);
+        TokenClient::new(&e,&usdc)
+            .transfer(&Self::owner(&e), &e.current_contract_address(), &amount);
+        Self::provide(&e, usdc, amount, lp_plans, expiration_ledger, deadline_ts)
     }
 
+    /// `deposit` using `usdc` the account already holds.
+    pub fn deposit_from_balance(
+        e: Env,
+        usdc: Address,
+        amount: i128,
+        lp_plans: Vec<LpPlan>,
+        expiration_ledger: u32,
+        deadline_ts: u64,
+    ) -> Result<(), AccountError> {
+        Self::require_admin(&e);
+        Self::provide(&e, usdc, amount, lp_plans, expiration_ledger, deadline_ts)
+    }
+
+    /// Redeems `lp_amount` of `lp_token` through the router and sends the
+    /// `usdc` it paid out to the owner. Returns that amount; USDC the
+    /// account held before is left in place.
     pub fn redeem(
         e: Env,
         lp_token: Address,
         lp_amount: i128,
         usdc: Address,
-        deadline: u32,
-    ) -> Result<(), AccountError> {
-        Self::owner(&e).require_auth();
-        TokenClient::new(&e,&lp_token)
-            .approve(&e.current_contract_address(), &Self::router(&e), &lp_amount, &deadline);
-        
-        let router_client = RouterClient::new(&e, &Self::router(&e));
-        router_client.redeem_liquidity(&lp_token, &lp_amount, &e.current_contract_address(), &(deadline as u64));
-        
-        // sweep USDC to owner
-        let bal = TokenClient::new(&e,&usdc).balance(&e.current_contract_address());
-        TokenClient::new(&e,&usdc)
-            .transfer(&e.current_contract_address(), &Self::owner(&e), &bal);
-        e.events().publish(("acct", symbol_short!("wd")),
-            TokenEvent{ token: usdc, amount: bal });
+        deadline_ts: u64,
+    ) -> Result<i128, AccountError> {
+        Self::require_admin(&e);
+        Self::withdraw(&e, lp_token, lp_amount, usdc, deadline_ts)
+    }
+
+    /* ---- generic calls ---- */
+    /// Runs `calls` in order as the account and returns their results. The
+    /// batch is atomic: a failing call reverts all of them. The account is
+    /// the direct invoker of each call, so `require_auth` on it inside the
+    /// called function is satisfied. Token `transfer` / `approve` calls from
+    /// the account go through the spending policies.
+    pub fn execute(e: Env, calls: Vec<(Address, Symbol, Vec<Val>)>) -> Result<Vec<Val>, AccountError> {
+        Self::execute_with_auth(e.clone(), calls, Vec::new(&e))
+    }
+
+    /// `execute`, also pre-authorizing `sub_auth`: calls deeper in the tree
+    /// that need the account's auth, such as the token transfers an adapter
+    /// makes on the account's behalf during a router swap. Nested transfers
+    /// and approvals are charged against the token limits, and with
+    /// `sub_auth` set every other call must target an allowed router.
+    pub fn execute_with_auth(
+        e: Env,
+        calls: Vec<(Address, Symbol, Vec<Val>)>,
+        sub_auth: Vec<(Address, Symbol, Vec<Val>)>,
+    ) -> Result<Vec<Val>, AccountError> {
+        Self::require_admin(&e);
+        if calls.is_empty() {
+            return Err(AccountError::EmptyBatch)
+        }
+        for (contract, fn_name, args) in calls.iter() {
+            policy::check_call(&e, &contract, &fn_name, &args, false)?;
+            if !sub_auth.is_empty() && !policy::is_token_op(&e, &fn_name, &args) {
+                policy::check_router(&e, &contract)?;
+            }
+        }
+        let mut entries = Vec::new(&e);
+        for (contract, fn_name, args) in sub_auth.iter() {
+            policy::check_call(&e, &contract, &fn_name, &args, true)?;
+            entries.push_back(InvokerContractAuthEntry::Contract(SubContractInvocation {
+                context: ContractContext { contract, fn_name, args },
+                sub_invocations: Vec::new(&e),
+            }));
+        }
+        if !entries.is_empty() {
+            e.authorize_as_current_contract(entries);
+        }
+
+        let mut results = Vec::new(&e);
+        for (contract, fn_name, args) in calls.iter() {
+            results.push_back(e.invoke_contract::<Val>(&contract, &fn_name, args));
+        }
+        e.events().publish(("acct", symbol_short!("exec")), calls.len());
+        Ok(results)
+    }
+
+    /* ---- timelocked transfers ---- */
+    /// Queues a transfer that can be executed once the policy's timelock
+    /// has passed. Returns its id.
+    pub fn queue_transfer(e: Env, token: Address, to: Address, amount: i128) -> Result<u32, AccountError> {
+        Self::require_admin(&e);
+        if amount <= 0 {
+            return Err(AccountError::InvalidAmount)
+        }
+        policy::check_recipient(&e, &to)?;
+        let (id, transfer) = policy::queue_transfer(&e, token, to, amount);
+        e.events().publish(("acct", symbol_short!("xfer_q")),
+            PendingEvent{ id, transfer });
+        Ok(id)
+    }
+
+    /// Runs a queued transfer after its unlock time. Recipient and limits
+    /// are checked again at this point.
+    pub fn execute_transfer(e: Env, id: u32) -> Result<(), AccountError> {
+        Self::require_admin(&e);
+        let pending = policy::get_pending(&e, id).ok_or(AccountError::UnknownTransfer)?;
+        if e.ledger().timestamp() < pending.unlock_at {
+            return Err(AccountError::TimelockActive)
+        }
+        policy::check_recipient(&e, &pending.to)?;
+        policy::charge(&e, &pending.token, pending.amount)?;
+        policy::remove_pending(&e, id);
+        TokenClient::new(&e,&pending.token)
+            .transfer(&e.current_contract_address(), &pending.to, &pending.amount);
+        e.events().publish(("acct", symbol_short!("xfer")),
+            TokenEvent{ token: pending.token, amount: pending.amount });
         Ok(())
     }
 
+    pub fn cancel_transfer(e: Env, id: u32) -> Result<(), AccountError> {
+        Self::require_admin(&e);
+        let transfer = policy::get_pending(&e, id).ok_or(AccountError::UnknownTransfer)?;
+        policy::remove_pending(&e, id);
+        e.events().publish(("acct", symbol_short!("xfer_c")),
+            PendingEvent{ id, transfer });
+        Ok(())
+    }
+
+    /* ---- policies ---- */
+    /// Replaces the account policy. A change that loosens it (see
+    /// `queue_policy_change`) needs the timelock while one is set.
+    pub fn set_policy(e: Env, policy: Policy) -> Result<(), AccountError> {
+        Self::require_admin(&e);
+        Self::change_policy(&e, PolicyChange::Policy(policy))
+    }
+
+    /// Replaces `token`'s limits, with the same timelock rule as `set_policy`.
+    pub fn set_token_policy(e: Env, token: Address, policy: TokenPolicy) -> Result<(), AccountError> {
+        Self::require_admin(&e);
+        Self::change_policy(&e, PolicyChange::Token(token, policy))
+    }
+
+    /// Queues a policy change that can be executed once the current
+    /// timelock has passed. Loosening changes (a wider allowlist, a shorter
+    /// timelock, higher or removed limits) must go through here. Returns its
+    /// id.
+    pub fn queue_policy_change(e: Env, change: PolicyChange) -> Result<u32, AccountError> {
+        Self::require_admin(&e);
+        if let PolicyChange::Token(_, policy) = &change {
+            policy::check_token_policy(policy)?;
+        }
+        let (id, change) = policy::queue_change(&e, change);
+        e.events().publish(("acct", symbol_short!("pol_q")), ChangeEvent{ id, change });
+        Ok(id)
+    }
+
+    pub fn execute_policy_change(e: Env, id: u32) -> Result<(), AccountError> {
+        Self::require_admin(&e);
+        let pending = policy::get_pending_change(&e, id).ok_or(AccountError::UnknownChange)?;
+        if e.ledger().timestamp() < pending.unlock_at {
+            return Err(AccountError::TimelockActive)
+        }
+        policy::remove_pending_change(&e, id);
+        Self::apply_policy(&e, pending.change)
+    }
+
+    pub fn cancel_policy_change(e: Env, id: u32) -> Result<(), AccountError> {
+        Self::require_admin(&e);
+        let change = policy::get_pending_change(&e, id).ok_or(AccountError::UnknownChange)?;
+        policy::remove_pending_change(&e, id);
+        e.events().publish(("acct", symbol_short!("pol_c")), ChangeEvent{ id, change });
+        Ok(())
+    }
+
+    /* ---- signers ---- */
+    /// Registers an ed25519 or secp256r1 (passkey) signer. Admin signers
+    /// act as the owner; session signers may only authorize the session's
+    /// permissions until `expires_at`. Re-adding a signer replaces its role
+    /// and resets its spend windows.
+    pub fn add_signer(e: Env, signer: Signer, role: SignerRole) -> Result<(), AccountError> {
+        Self::require_admin(&e);
+        if let SignerRole::Session(session) = &role {
+            auth::check_session(&e, session)?;
+        }
+        let key = signer.key();
+        auth::remove_signer(&e, &key);
+        auth::set_signer(&e, &SignerEntry { signer, role: role.clone() });
+        e.events().publish(("acct", symbol_short!("sgn_add")),
+            SignerEvent{ key, role });
+        Ok(())
+    }
+
+    pub fn remove_signer(e: Env, key: SignerKey) -> Result<(), AccountError> {
+        Self::require_admin(&e);
+        if auth::get_signer(&e, &key).is_none() {
+            return Err(AccountError::UnknownSigner)
+        }
+        auth::remove_signer(&e, &key);
+        e.events().publish(("acct", symbol_short!("sgn_rm")), key);
+        Ok(())
+    }
+
+    /* ---- owner rotation & recovery ---- */
+    /// Hands the account to `new_owner`, who must authorize taking it.
+    pub fn set_owner(e: Env, new_owner: Address) -> Result<(), AccountError> {
+        Self::require_admin(&e);
+        new_owner.require_auth();
+        Self::rotate_owner(&e, new_owner);
+        Ok(())
+    }
+
+    /// Sets the guardians able to recover the account, `threshold` of
+    /// which must approve, and the delay before a recovery can run (at
+    /// least `MIN_RECOVERY_DELAY`). An empty list disables recovery. Drops
+    /// any pending recovery.
+    pub fn set_guardians(e: Env, members: Vec<Address>, threshold: u32, delay: u64) -> Result<(), AccountError> {
+        Self::require_admin(&e);
+        for (i, m) in members.iter().enumerate() {
+            if members.first_index_of(&m) != Some(i as u32) {
+                return Err(AccountError::DuplicateGuardian)
+            }
+        }
+        if threshold > members.len() || (threshold == 0) != members.is_empty() {
+            return Err(AccountError::InvalidThreshold)
+        }
+        if !members.is_empty() && delay < MIN_RECOVERY_DELAY {
+            return Err(AccountError::InvalidDelay)
+        }
+        let guardians = Guardians { members, threshold, delay };
+        e.storage().persistent().set(&Key::Guardians, &guardians);
+        bump(&e, &Key::Guardians);
+        e.storage().persistent().remove(&Key::Recovery);
+        e.events().publish(("acct", symbol_short!("guardians")), guardians);
+        Ok(())
+    }
+
+    /// Proposes `new_owner` on behalf of `guardian`, counting as its
+    /// approval. Only one recovery can be pending at a time.
+    pub fn start_recovery(e: Env, guardian: Address, new_owner: Address) -> Result<(), AccountError> {
+        Self::require_guardian(&e, &guardian)?;
+        if e.storage().persistent().has(&Key::Recovery) {
+            return Err(AccountError::RecoveryPending)
+        }
+        let recovery = Recovery {
+            new_owner,
+            approvals: Vec::from_array(&e, [guardian]),
+            unlock_at: e.ledger().timestamp() + Self::get_guardians(e.clone()).delay,
+        };
+        e.storage().persistent().set(&Key::Recovery, &recovery);
+        bump(&e, &Key::Recovery);
+        e.events().publish(("acct", symbol_short!("rec_start")), recovery);
+        Ok(())
+    }
+
+    pub fn approve_recovery(e: Env, guardian: Address) -> Result<(), AccountError> {
+        Self::require_guardian(&e, &guardian)?;
+        let mut recovery = Self::get_recovery(e.clone()).ok_or(AccountError::NoRecovery)?;
+        if recovery.approvals.contains(&guardian) {
+            return Err(AccountError::AlreadyApproved)
+        }
+        recovery.approvals.push_back(guardian.clone());
+        e.storage().persistent().set(&Key::Recovery, &recovery);
+        bump(&e, &Key::Recovery);
+        e.events().publish(("acct", symbol_short!("rec_appr")), guardian);
+        Ok(())
+    }
+
+    /// Lets the owner (or an admin signer) stop a recovery during its delay.
+    pub fn cancel_recovery(e: Env) -> Result<(), AccountError> {
+        Self::require_admin(&e);
+        let recovery = Self::get_recovery(e.clone()).ok_or(AccountError::NoRecovery)?;
+        e.storage().persistent().remove(&Key::Recovery);
+        e.events().publish(("acct", symbol_short!("rec_cncl")), recovery);
+        Ok(())
+    }
+
+    /// Rotates the owner to the recovery's `new_owner` once enough current
+    /// guardians approved and the delay has passed, and removes every admin
+    /// and session signer, since any of them may be what was lost. Callable
+    /// by anyone.
+    pub fn execute_recovery(e: Env) -> Result<(), AccountError> {
+        let recovery = Self::get_recovery(e.clone()).ok_or(AccountError::NoRecovery)?;
+        let guardians = Self::get_guardians(e.clone());
+        let approvals = recovery.approvals.iter().filter(|g| guardians.members.contains(g)).count() as u32;
+        if approvals < guardians.threshold || e.ledger().timestamp() < recovery.unlock_at {
+            return Err(AccountError::RecoveryNotReady)
+        }
+        e.storage().persistent().remove(&Key::Recovery);
+        for key in auth::signer_keys(&e).iter() {
+            auth::remove_signer(&e, &key);
+            e.events().publish(("acct", symbol_short!("sgn_rm")), key);
+        }
+        Self::rotate_owner(&e, recovery.new_owner);
+        Ok(())
+    }
+
+    /* ---- recurring strategies ---- */
+    /// Stores a strategy that keepers can run from `next_run` on, every
+    /// `interval` seconds. Returns its id.
+    pub fn add_strategy(e: Env, strategy: Strategy) -> Result<u32, AccountError> {
+        Self::require_admin(&e);
+        strategy::check(&strategy)?;
+        let id = strategy::add(&e, &strategy);
+        e.events().publish(("acct", symbol_short!("strat_add")),
+            StrategyEvent{ id, strategy });
+        Ok(id)
+    }
+
+    pub fn remove_strategy(e: Env, id: u32) -> Result<(), AccountError> {
+        Self::require_admin(&e);
+        strategy::get(&e, id).ok_or(AccountError::UnknownStrategy)?;
+        strategy::remove(&e, id);
+        e.events().publish(("acct", symbol_short!("strat_rm")), id);
+        Ok(())
+    }
+
+    /// Runs a due strategy from the account's balance and pays `keeper`
+    /// its fee. Callable by anyone and needs no signature: the schedule,
+    /// router allowlist, token limits, `min_rate` and slippage bound are
+    /// all enforced here.
+    pub fn execute_due_strategy(e: Env, id: u32, keeper: Address) -> Result<(), AccountError> {
+        let mut s = strategy::get(&e, id).ok_or(AccountError::UnknownStrategy)?;
+        if e.ledger().timestamp() < s.next_run {
+            return Err(AccountError::NotDue)
+        }
+        strategy::advance(&e, &mut s);
+        strategy::set(&e, id, &s);
+
+        let StrategyAction::Swap(token_out) = s.action.clone();
+        let quote = RouterClient::new(&e, &Self::router(&e))
+            .get_best_quote(&s.amount, &s.token, &token_out)
+            .ok_or(AccountError::NoQuote)?;
+        let min_out = strategy::min_out(quote.amount_out, s.max_slippage_bps)
+            .max(strategy::floor(s.amount, s.min_rate));
+        Self::swap(&e, &s.token, s.amount, &token_out, min_out)?;
+        if s.keeper_fee > 0 {
+            policy::charge(&e, &s.token, s.keeper_fee)?;
+            TokenClient::new(&e,&s.token)
+                .transfer(&e.current_contract_address(), &keeper, &s.keeper_fee);
+        }
+        e.events().publish(("acct", symbol_short!("strat_run")),
+            RunEvent{ id, keeper, fee: s.keeper_fee });
+        Ok(())
+    }
+
+    /* ---- conditional orders ---- */
+    /// Stores an order that keepers can trigger once its condition holds.
+    /// Returns its id.
+    pub fn place_order(e: Env, order: Order) -> Result<u32, AccountError> {
+        Self::require_admin(&e);
+        orders::check(&order)?;
+        let id = orders::add(&e, &order);
+        e.events().publish(("acct", symbol_short!("ord_add")),
+            OrderEvent{ id, order });
+        Ok(id)
+    }
+
+    pub fn cancel_order(e: Env, id: u32) -> Result<(), AccountError> {
+        Self::require_admin(&e);
+        let order = orders::get(&e, id).ok_or(AccountError::UnknownOrder)?;
+        orders::remove(&e, id);
+        e.events().publish(("acct", symbol_short!("ord_cncl")),
+            OrderEvent{ id, order });
+        Ok(())
+    }
+
+    /// Fills an order if its condition holds at the router's current best
+    /// quote. Callable by anyone; the order is removed once filled. The
+    /// fill must meet the order's own minimums. Swap orders return the
+    /// amount received, unwinds the USDC sent to the owner.
+    pub fn trigger_order(e: Env, id: u32) -> Result<i128, AccountError> {
+        let order = orders::get(&e, id).ok_or(AccountError::UnknownOrder)?;
+        let router_client = RouterClient::new(&e, &Self::router(&e));
+        orders::remove(&e, id);
+        let amount = match order {
+            Order::Swap(o) => {
+                let quote = router_client
+                    .get_best_quote(&o.amount, &o.token_in, &o.token_out)
+                    .ok_or(AccountError::NoQuote)?;
+                if !orders::crossed(&o, quote.amount_out) {
+                    return Err(AccountError::ConditionNotMet)
+                }
+                Self::swap(&e, &o.token_in, o.amount, &o.token_out, o.min_out)?
+            }
+            Order::Unwind(o) => {
+                let p = positions::get_position(&e, &o.lp_token).ok_or(AccountError::NoPosition)?;
+                if positions::position_value(&e, &router_client, &o.usdc, &p) >= o.below {
+                    return Err(AccountError::ConditionNotMet)
+                }
+                let me = e.current_contract_address();
+                let held = TokenClient::new(&e,&o.lp_token).balance(&me).min(p.amount);
+                let mut before = Map::new(&e);
+                for token in o.min_amounts.keys().iter() {
+                    before.set(token.clone(), TokenClient::new(&e, &token).balance(&me));
+                }
+                let paid = Self::withdraw(&e, o.lp_token, held, o.usdc.clone(), e.ledger().timestamp())?;
+                for (token, min) in o.min_amounts.iter() {
+                    // The USDC has already gone on to the owner
+                    let received = if token == o.usdc {
+                        paid
+                    } else {
+                        TokenClient::new(&e, &token).balance(&me) - before.get(token.clone()).unwrap_or(0)
+                    };
+                    if received < min {
+                        return Err(AccountError::SlippageExceeded)
+                    }
+                }
+                paid
+            }
+        };
+        e.events().publish(("acct", symbol_short!("ord_fill")),
+            FillEvent{ id, amount });
+        Ok(amount)
+    }
+
     /* ---- views ---- */
     pub fn owner(e: &Env)  -> Address { e.storage().instance().get(&Key::Owner).unwrap() }
     pub fn router(e: &Env) -> Address { e.storage().instance().get(&Key::Router).unwrap() }
+    pub fn deployer(e: &Env) -> Option<Address> { e.storage().instance().get(&Key::Deployer) }
+    pub fn get_signer(e: Env, key: SignerKey) -> Option<SignerEntry> { auth::get_signer(&e, &key) }
+    pub fn get_signers(e: Env) -> Vec<SignerKey> { auth::signer_keys(&e) }
+    pub fn get_policy(e: Env) -> Policy { policy::get_policy(&e) }
+    pub fn get_token_policy(e: Env, token: Address) -> TokenPolicy { policy::get_token_policy(&e, &token) }
+    /// Amounts of `token` sent in the current day and week windows.
+    pub fn get_outflow(e: Env, token: Address) -> Outflow { policy::get_outflow(&e, &token) }
+    pub fn get_pending_transfer(e: Env, id: u32) -> Option<PendingTransfer> { policy::get_pending(&e, id) }
+    pub fn get_pending_policy_change(e: Env, id: u32) -> Option<PendingChange> { policy::get_pending_change(&e, id) }
+    pub fn get_guardians(e: Env) -> Guardians {
+        e.storage().persistent().get(&Key::Guardians)
+            .unwrap_or(Guardians { members: Vec::new(&e), threshold: 0, delay: 0 })
+    }
+    pub fn get_recovery(e: Env) -> Option<Recovery> { e.storage().persistent().get(&Key::Recovery) }
+    pub fn get_strategy(e: Env, id: u32) -> Option<Strategy> { strategy::get(&e, id) }
+    pub fn get_order(e: Env, id: u32) -> Option<Order> { orders::get(&e, id) }
+    /// Open LP positions recorded by `deposit` / `redeem`.
+    pub fn get_positions(e: Env) -> Vec<Position> { positions::get_positions(&e) }
+
+    /// USDC value of all open positions at the router's current best
+    /// quotes. 0 before the first deposit.
+    pub fn get_portfolio_value(e: Env) -> i128 {
+        let Some(usdc) = e.storage().instance().get::<_, Address>(&Key::Usdc) else { return 0 };
+        let router_client = RouterClient::new(&e, &Self::router(&e));
+        positions::get_positions(&e)
+            .iter()
+            .map(|p| positions::position_value(&e, &router_client, &usdc, &p))
+            .sum()
+    }
+}
+
+impl Account {
+    /// Account-level auth: satisfied by the owner or any admin signer
+    /// through `__check_auth`.
+    fn require_admin(e: &Env) {
+        e.current_contract_address().require_auth();
+    }
+
+    fn require_guardian(e: &Env, guardian: &Address) -> Result<(), AccountError> {
+        guardian.require_auth();
+        if !Self::get_guardians(e.clone()).members.contains(guardian) {
+            return Err(AccountError::NotGuardian)
+        }
+        Ok(())
+    }
+
+    fn change_policy(e: &Env, change: PolicyChange) -> Result<(), AccountError> {
+        if policy::get_policy(e).timelock > 0 && policy::loosens(e, &change) {
+            return Err(AccountError::TimelockRequired)
+        }
+        Self::apply_policy(e, change)
+    }
+
+    fn apply_policy(e: &Env, change: PolicyChange) -> Result<(), AccountError> {
+        policy::apply(e, &change)?;
+        match change {
+            PolicyChange::Policy(policy) =>
+                e.events().publish(("acct", symbol_short!("policy")), policy),
+            PolicyChange::Token(token, policy) =>
+                e.events().publish(("acct", symbol_short!("tk_policy"), token), policy),
+        }
+        Ok(())
+    }
+
+    fn rotate_owner(e: &Env, new: Address) {
+        let old = Self::owner(e);
+        e.storage().instance().set(&Key::Owner, &new);
+        e.events().publish(("acct", symbol_short!("owner")), OwnerEvent{ old, new });
+    }
+
+    /// Hands `amount` of `usdc` held by the account to the router's
+    /// `provide_liquidity` and records the resulting positions.
+    fn provide(
+        e: &Env,
+        usdc: Address,
+        amount: i128,
+        lp_plans: Vec<LpPlan>,
+        expiration_ledger: u32,
+        deadline_ts: u64,
+    ) -> Result<(), AccountError> {
+        policy::check_router(e, &Self::router(e))?;
+        policy::charge(e, &usdc, amount)?;
+        let me = e.current_contract_address();
+        TokenClient::new(e,&usdc).approve(&me, &Self::router(e), &amount, &expiration_ledger);
+        e.storage().instance().set(&Key::Usdc, &usdc);
+
+        let router_client = RouterClient::new(e, &Self::router(e));
+        let legs = positions::plan_legs(e, &router_client, &lp_plans);
+        let before = positions::balances(e, &lp_plans);
+        router_client.provide_liquidity(&amount, &lp_plans, &me, &deadline_ts);
+        for p in positions::record_deposit(e, &router_client, &usdc, legs, before).iter() {
+            e.events().publish(("acct", symbol_short!("pos"), p.lp_token.clone()), p);
+        }
+
+        e.events().publish(("acct", symbol_short!("dep")),
+            TokenEvent{ token: usdc, amount });
+        Ok(())
+    }
+
+    /// `redeem` without the auth check.
+    fn withdraw(
+        e: &Env,
+        lp_token: Address,
+        lp_amount: i128,
+        usdc: Address,
+        deadline_ts: u64,
+    ) -> Result<i128, AccountError> {
+        let router = Self::router(e);
+        policy::check_router(e, &router)?;
+        let me = e.current_contract_address();
+
+        let tk = TokenClient::new(e,&usdc);
+        let before = tk.balance(&me);
+        let router_client = RouterClient::new(e, &router);
+        // The router only needs the LP transfer in, as for `swap`; this
+        // covers the next call alone
+        e.authorize_as_current_contract(vec![e, InvokerContractAuthEntry::Contract(SubContractInvocation {
+            context: ContractContext {
+                contract: lp_token.clone(),
+                fn_name: symbol_short!("transfer"),
+                args: (me.clone(), router.clone(), lp_amount).into_val(e),
+            },
+            sub_invocations: Vec::new(e),
+        })]);
+        router_client.redeem_liquidity(&lp_token, &lp_amount, &me, &deadline_ts);
+        let amount = tk.balance(&me) - before;
+        if positions::get_position(e, &lp_token).is_some() {
+            match positions::record_redeem(e, &lp_token, lp_amount) {
+                Some(p) => e.events().publish(("acct", symbol_short!("pos"), lp_token), p),
+                None => e.events().publish(("acct", symbol_short!("pos_cls")), lp_token),
+            }
+        }
+
+        if amount > 0 {
+            tk.transfer(&me, &Self::owner(e), &amount);
+        }
+        e.events().publish(("acct", symbol_short!("wd")),
+            TokenEvent{ token: usdc, amount });
+        Ok(amount)
+    }
+
+    /// Swaps `amount` of the account's `token_in` through the router's
+    /// `swap_exact_in`, accepting no less than `min_out`. The account
+    /// authorizes the router's pull of the input itself, so no signature is
+    /// needed. Returns the amount received.
+    fn swap(
+        e: &Env,
+        token_in: &Address,
+        amount: i128,
+        token_out: &Address,
+        min_out: i128,
+    ) -> Result<i128, AccountError> {
+        let router = Self::router(e);
+        policy::check_router(e, &router)?;
+        policy::charge(e, token_in, amount)?;
+
+        let me = e.current_contract_address();
+        let out = TokenClient::new(e, token_out);
+        let before = out.balance(&me);
+        // Covers only the next call, so it goes right before the swap
+        e.authorize_as_current_contract(vec![e, InvokerContractAuthEntry::Contract(SubContractInvocation {
+            context: ContractContext {
+                contract: token_in.clone(),
+                fn_name: symbol_short!("transfer"),
+                args: (me.clone(), router.clone(), amount).into_val(e),
+            },
+            sub_invocations: Vec::new(e),
+        })]);
+        e.invoke_contract::<Val>(
+            &router,
+            &Symbol::new(e, "swap_exact_in"),
+            (amount, min_out, token_in.clone(), token_out.clone(), me.clone(), e.ledger().timestamp()).into_val(e),
+        );
+        let received = out.balance(&me) - before;
+        if received < min_out {
+            return Err(AccountError::SlippageExceeded)
+        }
+        Ok(received)
+    }
+}
+
+#[contractimpl]
+impl CustomAccountInterface for Account {
+    type Signature = Signatures;
+    type Error = AccountError;
+
+    fn __check_auth(
+        e: Env,
+        signature_payload: Hash<32>,
+        signatures: Signatures,
+        auth_contexts: Vec<Context>,
+    ) -> Result<(), AccountError> {
+        auth::check_auth(&e, &signature_payload, &signatures, &auth_contexts)
+    }
 }
+
+#[cfg(test)]
+mod test;
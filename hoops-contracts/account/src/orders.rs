@@ -0,0 +1,85 @@
+//! Conditional orders the owner places and keepers trigger: swaps that fire
+//! when the router's best quote crosses a price, and LP unwinds that fire
+//! when a position's USDC value drops below a threshold. Fills are bound by
+//! minimums stored on the order, never by the quote that triggered it.
+
+use soroban_sdk::{contracttype, Address, Env, Map};
+
+use crate::{bump, AccountError, Key};
+
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Direction {
+    /// Fires once the quote is at or above the price (take profit, limit).
+    Above,
+    /// Fires once the quote is at or below the price (stop loss).
+    Below,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SwapOrder {
+    pub token_in: Address,
+    pub token_out: Address,
+    pub amount: i128,
+    /// `token_out` the router's best quote for `amount` must cross.
+    pub price: i128,
+    pub direction: Direction,
+    /// Least `token_out` the fill may return for `amount`.
+    pub min_out: i128,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct UnwindOrder {
+    pub lp_token: Address,
+    /// Token the position is valued and redeemed in.
+    pub usdc: Address,
+    /// Redeem the whole position once it is worth less than this.
+    pub below: i128,
+    /// Least the redeem must pay out of each listed pool token.
+    pub min_amounts: Map<Address, i128>,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Order {
+    Swap(SwapOrder),
+    Unwind(UnwindOrder),
+}
+
+pub fn check(order: &Order) -> Result<(), AccountError> {
+    let valid = match order {
+        Order::Swap(o) => o.amount > 0 && o.price > 0 && o.min_out > 0,
+        Order::Unwind(o) => o.below > 0 && !o.min_amounts.is_empty() && o.min_amounts.values().iter().all(|m| m > 0),
+    };
+    if !valid {
+        return Err(AccountError::InvalidOrder);
+    }
+    Ok(())
+}
+
+/// Whether a quote of `quoted` crosses the order's price.
+pub fn crossed(order: &SwapOrder, quoted: i128) -> bool {
+    match order.direction {
+        Direction::Above => quoted >= order.price,
+        Direction::Below => quoted <= order.price,
+    }
+}
+
+pub fn add(e: &Env, order: &Order) -> u32 {
+    let id: u32 = e.storage().instance().get(&Key::NextOrder).unwrap_or(0);
+    e.storage().instance().set(&Key::NextOrder, &(id + 1));
+    let k = Key::Order(id);
+    e.storage().persistent().set(&k, order);
+    bump(e, &k);
+    id
+}
+
+pub fn get(e: &Env, id: u32) -> Option<Order> {
+    e.storage().persistent().get(&Key::Order(id))
+}
+
+pub fn remove(e: &Env, id: u32) {
+    e.storage().persistent().remove(&Key::Order(id));
+}
@@ -0,0 +1,280 @@
+//! Spending policies the account enforces on funds leaving it: per-token
+//! daily/weekly limits, recipient and router allowlists, and a timelock for
+//! transfers above a per-token threshold. Changes that loosen a policy wait
+//! out the same timelock.
+
+use soroban_sdk::{contracttype, symbol_short, Address, Env, Symbol, TryFromVal, Val, Vec};
+
+use crate::{bump, AccountError, Key};
+
+const DAY: u64 = 86_400;
+const WEEK: u64 = 7 * DAY;
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Policy {
+    /// Allowed `transfer` recipients; empty allows anyone.
+    pub recipients: Vec<Address>,
+    /// Routers the account may hand funds to; empty allows any.
+    pub routers: Vec<Address>,
+    /// Seconds a queued transfer waits before it can be executed.
+    pub timelock: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TokenPolicy {
+    pub daily_limit: Option<i128>,
+    pub weekly_limit: Option<i128>,
+    /// Transfers above this amount must go through `queue_transfer`.
+    pub timelock_above: Option<i128>,
+}
+
+/// Outflow of a token in the current day and week windows.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Outflow {
+    pub day_start: u64,
+    pub day: i128,
+    pub week_start: u64,
+    pub week: i128,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PendingTransfer {
+    pub token: Address,
+    pub to: Address,
+    pub amount: i128,
+    pub unlock_at: u64,
+}
+
+/// A policy update; loosening ones are queued behind the timelock.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum PolicyChange {
+    Policy(Policy),
+    Token(Address, TokenPolicy),
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PendingChange {
+    pub change: PolicyChange,
+    pub unlock_at: u64,
+}
+
+pub fn get_policy(e: &Env) -> Policy {
+    let policy = e.storage().persistent().get(&Key::Policy);
+    if policy.is_some() {
+        bump(e, &Key::Policy);
+    }
+    policy.unwrap_or(Policy { recipients: Vec::new(e), routers: Vec::new(e), timelock: 0 })
+}
+
+pub fn set_policy(e: &Env, policy: &Policy) {
+    e.storage().persistent().set(&Key::Policy, policy);
+    bump(e, &Key::Policy);
+}
+
+pub fn get_token_policy(e: &Env, token: &Address) -> TokenPolicy {
+    let k = Key::TokenPolicy(token.clone());
+    let policy = e.storage().persistent().get(&k);
+    if policy.is_some() {
+        bump(e, &k);
+    }
+    policy.unwrap_or(TokenPolicy { daily_limit: None, weekly_limit: None, timelock_above: None })
+}
+
+pub fn check_token_policy(policy: &TokenPolicy) -> Result<(), AccountError> {
+    let negative = |v: Option<i128>| v.is_some_and(|v| v < 0);
+    if negative(policy.daily_limit) || negative(policy.weekly_limit) || negative(policy.timelock_above) {
+        return Err(AccountError::InvalidLimit);
+    }
+    Ok(())
+}
+
+pub fn set_token_policy(e: &Env, token: &Address, policy: &TokenPolicy) -> Result<(), AccountError> {
+    check_token_policy(policy)?;
+    let k = Key::TokenPolicy(token.clone());
+    e.storage().persistent().set(&k, policy);
+    bump(e, &k);
+    Ok(())
+}
+
+/// Whether `change` allows anything the current policy does not: a
+/// recipient or router left off a non-empty list, a shorter timelock, or a
+/// higher or removed limit or timelock threshold.
+pub fn loosens(e: &Env, change: &PolicyChange) -> bool {
+    let widened = |old: Vec<Address>, new: &Vec<Address>| {
+        !old.is_empty() && (new.is_empty() || new.iter().any(|a| !old.contains(&a)))
+    };
+    let raised = |old: Option<i128>, new: Option<i128>| old.is_some_and(|o| new.is_none_or(|n| n > o));
+    match change {
+        PolicyChange::Policy(new) => {
+            let old = get_policy(e);
+            widened(old.recipients, &new.recipients) || widened(old.routers, &new.routers) || new.timelock < old.timelock
+        }
+        PolicyChange::Token(token, new) => {
+            let old = get_token_policy(e, token);
+            raised(old.daily_limit, new.daily_limit)
+                || raised(old.weekly_limit, new.weekly_limit)
+                || raised(old.timelock_above, new.timelock_above)
+        }
+    }
+}
+
+pub fn apply(e: &Env, change: &PolicyChange) -> Result<(), AccountError> {
+    match change {
+        PolicyChange::Policy(policy) => {
+            set_policy(e, policy);
+            Ok(())
+        }
+        PolicyChange::Token(token, policy) => set_token_policy(e, token, policy),
+    }
+}
+
+pub fn queue_change(e: &Env, change: PolicyChange) -> (u32, PendingChange) {
+    let id: u32 = e.storage().instance().get(&Key::NextChange).unwrap_or(0);
+    e.storage().instance().set(&Key::NextChange, &(id + 1));
+    let pending = PendingChange { change, unlock_at: e.ledger().timestamp() + get_policy(e).timelock };
+    let k = Key::PendingChange(id);
+    e.storage().persistent().set(&k, &pending);
+    bump(e, &k);
+    (id, pending)
+}
+
+pub fn get_pending_change(e: &Env, id: u32) -> Option<PendingChange> {
+    e.storage().persistent().get(&Key::PendingChange(id))
+}
+
+pub fn remove_pending_change(e: &Env, id: u32) {
+    e.storage().persistent().remove(&Key::PendingChange(id));
+}
+
+pub fn get_outflow(e: &Env, token: &Address) -> Outflow {
+    let now = e.ledger().timestamp();
+    let mut flow: Outflow = e
+        .storage()
+        .persistent()
+        .get(&Key::Outflow(token.clone()))
+        .unwrap_or(Outflow { day_start: now, day: 0, week_start: now, week: 0 });
+    if now >= flow.day_start + DAY {
+        (flow.day_start, flow.day) = (now, 0);
+    }
+    if now >= flow.week_start + WEEK {
+        (flow.week_start, flow.week) = (now, 0);
+    }
+    flow
+}
+
+pub fn check_recipient(e: &Env, to: &Address) -> Result<(), AccountError> {
+    let recipients = get_policy(e).recipients;
+    if !recipients.is_empty() && !recipients.contains(to) {
+        return Err(AccountError::RecipientNotAllowed);
+    }
+    Ok(())
+}
+
+pub fn check_router(e: &Env, router: &Address) -> Result<(), AccountError> {
+    let routers = get_policy(e).routers;
+    if !routers.is_empty() && !routers.contains(router) {
+        return Err(AccountError::RouterNotAllowed);
+    }
+    Ok(())
+}
+
+/// Whether a transfer of `amount` must be queued behind the timelock.
+pub fn needs_timelock(e: &Env, token: &Address, amount: i128) -> bool {
+    get_token_policy(e, token).timelock_above.is_some_and(|t| amount > t)
+}
+
+/// Counts `amount` of `token` leaving the account against its limits.
+pub fn charge(e: &Env, token: &Address, amount: i128) -> Result<(), AccountError> {
+    if amount < 0 {
+        return Err(AccountError::InvalidAmount);
+    }
+    let policy = get_token_policy(e, token);
+    let mut flow = get_outflow(e, token);
+    flow.day += amount;
+    flow.week += amount;
+    if policy.daily_limit.is_some_and(|l| flow.day > l) || policy.weekly_limit.is_some_and(|l| flow.week > l) {
+        return Err(AccountError::LimitExceeded);
+    }
+    let k = Key::Outflow(token.clone());
+    e.storage().persistent().set(&k, &flow);
+    bump(e, &k);
+    Ok(())
+}
+
+pub fn queue_transfer(e: &Env, token: Address, to: Address, amount: i128) -> (u32, PendingTransfer) {
+    let id: u32 = e.storage().instance().get(&Key::NextTransfer).unwrap_or(0);
+    e.storage().instance().set(&Key::NextTransfer, &(id + 1));
+    let pending = PendingTransfer {
+        token,
+        to,
+        amount,
+        unlock_at: e.ledger().timestamp() + get_policy(e).timelock,
+    };
+    let k = Key::Pending(id);
+    e.storage().persistent().set(&k, &pending);
+    bump(e, &k);
+    (id, pending)
+}
+
+pub fn get_pending(e: &Env, id: u32) -> Option<PendingTransfer> {
+    e.storage().persistent().get(&Key::Pending(id))
+}
+
+pub fn remove_pending(e: &Env, id: u32) {
+    e.storage().persistent().remove(&Key::Pending(id));
+}
+
+/// Policy checks for a call made (`nested: false`) or pre-authorized
+/// (`nested: true`) by `execute`, or signed for in `__check_auth`. Token `transfer`s and `approve`s from the
+/// account are charged against the token's limits; direct transfers also
+/// need an allowed recipient, approvals an allowed router. Recipients of
+/// nested transfers are chosen by the router, so they are not checked.
+pub fn check_call(
+    e: &Env,
+    contract: &Address,
+    fn_name: &Symbol,
+    args: &Vec<Val>,
+    nested: bool,
+) -> Result<(), AccountError> {
+    let me = e.current_contract_address();
+    if *contract == me {
+        return Err(AccountError::NotPermitted);
+    }
+    let arg = |i: u32| args.get(i).ok_or(AccountError::NotPermitted);
+    let from = args.get(0).and_then(|v| Address::try_from_val(e, &v).ok());
+    if from.as_ref() != Some(&me) {
+        return Ok(());
+    }
+    if *fn_name == symbol_short!("transfer") {
+        let to = Address::try_from_val(e, &arg(1)?).map_err(|_| AccountError::NotPermitted)?;
+        let amount = i128::try_from_val(e, &arg(2)?).map_err(|_| AccountError::NotPermitted)?;
+        if !nested {
+            check_recipient(e, &to)?;
+        }
+        if needs_timelock(e, contract, amount) {
+            return Err(AccountError::TimelockRequired);
+        }
+        charge(e, contract, amount)
+    } else if *fn_name == symbol_short!("approve") {
+        let spender = Address::try_from_val(e, &arg(1)?).map_err(|_| AccountError::NotPermitted)?;
+        let amount = i128::try_from_val(e, &arg(2)?).map_err(|_| AccountError::NotPermitted)?;
+        check_router(e, &spender)?;
+        charge(e, contract, amount)
+    } else {
+        Ok(())
+    }
+}
+
+/// Whether a call moves the account's tokens by itself (`transfer` /
+/// `approve` from the account).
+pub fn is_token_op(e: &Env, fn_name: &Symbol, args: &Vec<Val>) -> bool {
+    let from = args.get(0).and_then(|v| Address::try_from_val(e, &v).ok());
+    from == Some(e.current_contract_address())
+        && (*fn_name == symbol_short!("transfer") || *fn_name == symbol_short!("approve"))
+}
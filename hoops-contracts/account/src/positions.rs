@@ -0,0 +1,227 @@
+//! LP positions opened through `deposit`, with their USDC cost basis, and
+//! their current value from pool reserves and router quotes.
+
+use soroban_sdk::{contracttype, token::Client as TokenClient, Address, Env, Map, Vec};
+
+use crate::hoops_router::{LpPlan, RouterClient, SwapQuote};
+use crate::{bump, Key};
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Position {
+    pub lp_token: Address,
+    pub adapter_id: i128,
+    pub pool: Address,
+    /// LP tokens held for this position.
+    pub amount: i128,
+    /// USDC value of the tokens put in, at deposit-time quotes.
+    pub cost_basis: i128,
+    pub token_a: Address,
+    pub token_b: Address,
+    /// Underlying amounts put in, reduced pro rata on redeem.
+    pub amount_a: i128,
+    pub amount_b: i128,
+    /// Ledger of the last change.
+    pub ledger: u32,
+}
+
+/// A plan matched to its market, with the LP balance before the deposit.
+#[contracttype(export = false)]
+#[derive(Clone)]
+pub struct Leg {
+    pub plan: LpPlan,
+    pub market: SwapQuote,
+    pub lp_before: i128,
+}
+
+pub fn get_position(e: &Env, lp_token: &Address) -> Option<Position> {
+    e.storage().persistent().get(&Key::Position(lp_token.clone()))
+}
+
+pub fn get_positions(e: &Env) -> Vec<Position> {
+    let mut positions = Vec::new(e);
+    for lp in lp_tokens(e).iter() {
+        if let Some(p) = get_position(e, &lp) {
+            positions.push_back(p);
+        }
+    }
+    positions
+}
+
+fn lp_tokens(e: &Env) -> Vec<Address> {
+    e.storage().persistent().get(&Key::Positions).unwrap_or(Vec::new(e))
+}
+
+fn set_position(e: &Env, p: &Position) {
+    let k = Key::Position(p.lp_token.clone());
+    let mut lps = lp_tokens(e);
+    if !lps.contains(&p.lp_token) {
+        lps.push_back(p.lp_token.clone());
+        e.storage().persistent().set(&Key::Positions, &lps);
+    }
+    bump(e, &Key::Positions);
+    e.storage().persistent().set(&k, p);
+    bump(e, &k);
+}
+
+fn remove_position(e: &Env, lp_token: &Address) {
+    let mut lps = lp_tokens(e);
+    if let Some(i) = lps.first_index_of(lp_token) {
+        lps.remove(i);
+        e.storage().persistent().set(&Key::Positions, &lps);
+    }
+    e.storage().persistent().remove(&Key::Position(lp_token.clone()));
+}
+
+/// Matches each plan to the router market it will be deposited in. The
+/// router sends a plan to adapter `proportion`; the market's LP token is
+/// taken from that adapter's quote for the pair. Plans without a quote are
+/// not tracked.
+pub fn plan_legs(e: &Env, router: &RouterClient, plans: &Vec<LpPlan>) -> Vec<Leg> {
+    let me = e.current_contract_address();
+    let mut legs = Vec::new(e);
+    for plan in plans.iter() {
+        let (amount, token_in, token_out) = if plan.amount_a > 0 {
+            (plan.amount_a, &plan.token_a, &plan.token_b)
+        } else {
+            (plan.amount_b, &plan.token_b, &plan.token_a)
+        };
+        let Ok(Ok(quotes)) = router.try_get_all_quotes(&amount, token_in, token_out) else { continue };
+        let Some(market) = quotes.iter().find(|q| q.adapter_id == plan.proportion as i128) else { continue };
+        let lp_before = TokenClient::new(e, &market.lp_token).balance(&me);
+        legs.push_back(Leg { plan, market, lp_before });
+    }
+    legs
+}
+
+/// Balances of every token the plans spend.
+pub fn balances(e: &Env, plans: &Vec<LpPlan>) -> Map<Address, i128> {
+    let me = e.current_contract_address();
+    let mut out = Map::new(e);
+    for plan in plans.iter() {
+        for token in [plan.token_a, plan.token_b] {
+            if !out.contains_key(token.clone()) {
+                out.set(token.clone(), TokenClient::new(e, &token).balance(&me));
+            }
+        }
+    }
+    out
+}
+
+/// Records the LP minted for each leg. What the router actually took of a
+/// token is split across the plans using it in proportion to their
+/// requested amounts, and valued in `usdc` for the cost basis.
+pub fn record_deposit(
+    e: &Env,
+    router: &RouterClient,
+    usdc: &Address,
+    legs: Vec<Leg>,
+    before: Map<Address, i128>,
+) -> Vec<Position> {
+    let me = e.current_contract_address();
+    let mut requested: Map<Address, i128> = Map::new(e);
+    for leg in legs.iter() {
+        for (token, amt) in [(&leg.plan.token_a, leg.plan.amount_a), (&leg.plan.token_b, leg.plan.amount_b)] {
+            requested.set(token.clone(), requested.get(token.clone()).unwrap_or(0) + amt);
+        }
+    }
+    let used = |token: &Address, amt: i128| -> i128 {
+        let total = requested.get(token.clone()).unwrap_or(0);
+        let spent = before.get(token.clone()).unwrap_or(0) - TokenClient::new(e, token).balance(&me);
+        if total <= 0 || spent <= 0 { 0 } else { spent * amt / total }
+    };
+
+    let mut changed = Vec::new(e);
+    for leg in legs.iter() {
+        let minted = TokenClient::new(e, &leg.market.lp_token).balance(&me) - leg.lp_before;
+        if minted <= 0 {
+            continue;
+        }
+        let amount_a = used(&leg.plan.token_a, leg.plan.amount_a);
+        let amount_b = used(&leg.plan.token_b, leg.plan.amount_b);
+        let cost = value(router, usdc, &leg.plan.token_a, amount_a) + value(router, usdc, &leg.plan.token_b, amount_b);
+        let p = match get_position(e, &leg.market.lp_token) {
+            Some(mut p) => {
+                p.amount += minted;
+                p.cost_basis += cost;
+                p.amount_a += amount_a;
+                p.amount_b += amount_b;
+                p.ledger = e.ledger().sequence();
+                p
+            }
+            None => Position {
+                lp_token: leg.market.lp_token.clone(),
+                adapter_id: leg.market.adapter_id,
+                pool: leg.market.pool_address.clone(),
+                amount: minted,
+                cost_basis: cost,
+                token_a: leg.plan.token_a.clone(),
+                token_b: leg.plan.token_b.clone(),
+                amount_a,
+                amount_b,
+                ledger: e.ledger().sequence(),
+            },
+        };
+        set_position(e, &p);
+        changed.push_back(p);
+    }
+    changed
+}
+
+/// Reduces a position pro rata for `lp_amount` redeemed. Returns the
+/// updated position, or `None` if it was closed or never tracked.
+pub fn record_redeem(e: &Env, lp_token: &Address, lp_amount: i128) -> Option<Position> {
+    let mut p = get_position(e, lp_token)?;
+    if lp_amount >= p.amount {
+        remove_position(e, lp_token);
+        return None;
+    }
+    let keep = |x: i128| x - x * lp_amount / p.amount;
+    p.cost_basis = keep(p.cost_basis);
+    p.amount_a = keep(p.amount_a);
+    p.amount_b = keep(p.amount_b);
+    p.amount -= lp_amount;
+    p.ledger = e.ledger().sequence();
+    set_position(e, &p);
+    Some(p)
+}
+
+/// `amount` of `token` in USDC at the router's best quote; 0 if unquoted.
+pub fn value(router: &RouterClient, usdc: &Address, token: &Address, amount: i128) -> i128 {
+    if amount <= 0 {
+        return 0;
+    }
+    if token == usdc {
+        return amount;
+    }
+    match router.try_get_best_quote(&amount, token, usdc) {
+        Ok(Ok(Some(q))) => q.amount_out,
+        _ => 0,
+    }
+}
+
+/// USDC value of what the position's LP tokens redeem for now. LP the
+/// account holds beyond the recorded amount is not counted.
+pub fn position_value(e: &Env, router: &RouterClient, usdc: &Address, p: &Position) -> i128 {
+    let held = TokenClient::new(e, &p.lp_token)
+        .balance(&e.current_contract_address())
+        .min(p.amount);
+    lp_value(router, usdc, p.adapter_id, &p.pool, held)
+}
+
+/// USDC value of `lp_amount` of `pool`'s LP: its share (`lp_amount /
+/// lp_supply`) of each pool reserve, at current quotes.
+pub fn lp_value(router: &RouterClient, usdc: &Address, adapter_id: i128, pool: &Address, lp_amount: i128) -> i128 {
+    if lp_amount <= 0 {
+        return 0;
+    }
+    let Ok(Ok(Some(info))) = router.try_get_pool_info(&adapter_id, pool) else { return 0 };
+    if info.lp_supply <= 0 {
+        return 0;
+    }
+    let mut total = 0;
+    for (token, reserve) in info.tokens.iter().zip(info.reserves.iter()) {
+        total += value(router, usdc, &token, reserve * lp_amount / info.lp_supply);
+    }
+    total
+}
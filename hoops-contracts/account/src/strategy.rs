@@ -0,0 +1,92 @@
+//! Recurring strategies the owner defines and keepers run when due:
+//! scheduled swaps into a target token (DCA). Keepers run them without a
+//! signature, so only swaps are offered: the account can pre-authorize the
+//! router's pull of a swap's input, but not the adapter and pool calls a
+//! liquidity deposit makes on its behalf. Deposits stay owner calls.
+
+use soroban_sdk::{contracttype, Address, Env};
+
+use crate::{bump, AccountError, Key};
+
+const BPS: i128 = 10_000;
+/// Scale of `Strategy::min_rate`.
+pub const RATE_SCALE: i128 = 10_000_000;
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum StrategyAction {
+    /// Swap `amount` into this token.
+    Swap(Address),
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Strategy {
+    /// Token spent on each run, from the account's balance.
+    pub token: Address,
+    pub amount: i128,
+    pub action: StrategyAction,
+    /// Ledger timestamp from which the next run is allowed.
+    pub next_run: u64,
+    /// Seconds between runs.
+    pub interval: u64,
+    /// Largest shortfall of a swap's output against the router's best
+    /// quote, in basis points.
+    pub max_slippage_bps: u32,
+    /// Least `token_out` per unit of `token`, scaled by `RATE_SCALE`.
+    /// Unlike the quotes, a keeper cannot move it.
+    pub min_rate: i128,
+    /// Paid in `token` to whoever runs the strategy.
+    pub keeper_fee: i128,
+}
+
+pub fn check(strategy: &Strategy) -> Result<(), AccountError> {
+    if strategy.amount <= 0
+        || strategy.interval == 0
+        || strategy.keeper_fee < 0
+        || strategy.max_slippage_bps as i128 > BPS
+        || strategy.min_rate <= 0
+    {
+        return Err(AccountError::InvalidStrategy);
+    }
+    Ok(())
+}
+
+pub fn add(e: &Env, strategy: &Strategy) -> u32 {
+    let id: u32 = e.storage().instance().get(&Key::NextStrategy).unwrap_or(0);
+    e.storage().instance().set(&Key::NextStrategy, &(id + 1));
+    set(e, id, strategy);
+    id
+}
+
+pub fn get(e: &Env, id: u32) -> Option<Strategy> {
+    e.storage().persistent().get(&Key::Strategy(id))
+}
+
+pub fn set(e: &Env, id: u32, strategy: &Strategy) {
+    let k = Key::Strategy(id);
+    e.storage().persistent().set(&k, strategy);
+    bump(e, &k);
+}
+
+pub fn remove(e: &Env, id: u32) {
+    e.storage().persistent().remove(&Key::Strategy(id));
+}
+
+/// Moves `next_run` to the first slot after now, so missed runs are
+/// skipped rather than executed back to back.
+pub fn advance(e: &Env, strategy: &mut Strategy) {
+    let now = e.ledger().timestamp();
+    let missed = (now - strategy.next_run) / strategy.interval;
+    strategy.next_run += (missed + 1) * strategy.interval;
+}
+
+/// Least output of a run spending `amount` at `min_rate`.
+pub fn floor(amount: i128, min_rate: i128) -> i128 {
+    amount * min_rate / RATE_SCALE
+}
+
+/// Least output accepted for a swap quoted at `quoted`.
+pub fn min_out(quoted: i128, max_slippage_bps: u32) -> i128 {
+    quoted * (BPS - max_slippage_bps as i128) / BPS
+}
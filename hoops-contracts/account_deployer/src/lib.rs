@@ -1,23 +1,139 @@
 #![no_std]
 
-use soroban_sdk::{contract, contractimpl, Address, BytesN, Env};
+use soroban_sdk::{
+    contract, contracterror, contractimpl, contracttype, symbol_short, Address, BytesN, Env, IntoVal,
+    Symbol, Vec,
+};
+
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum DeployerError {
+    /// No account WASM has been approved yet.
+    NoVersion = 1,
+}
+
+#[contracttype]
+#[derive(Clone)]
+enum Key {
+    Admin,
+    /// Highest approved version; versions start at 1.
+    LatestVersion,
+    /// Approved account WASM hash of a version.
+    Version(u32),
+    /// Version under which a WASM hash was approved.
+    Approved(BytesN<32>),
+    /// Accounts deployed for an owner, oldest first.
+    Accounts(Address),
+}
+
+const DAY_IN_LEDGERS: u32 = 17_280;
+const TTL_THRESHOLD: u32 = 30 * DAY_IN_LEDGERS;
+const TTL_BUMP: u32 = 120 * DAY_IN_LEDGERS;
+
+fn bump(env: &Env, key: &Key) {
+    env.storage().persistent().extend_ttl(key, TTL_THRESHOLD, TTL_BUMP);
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub struct DeployEvent { pub owner: Address, pub account: Address, pub router: Address }
+
+#[contracttype]
+#[derive(Clone)]
+pub struct VersionEvent { pub version: u32, pub wasm_hash: BytesN<32> }
 
 #[contract]
 pub struct Deployer;
 
 #[contractimpl]
 impl Deployer {
+    /// Sets the admin that approves account WASM versions. It is fixed at
+    /// deployment, so nobody can claim the registry before its owner does.
+    pub fn __constructor(env: Env, admin: Address) {
+        env.storage().instance().set(&Key::Admin, &admin);
+    }
+
+    /// Approves an account WASM (already uploaded) as the next version.
+    /// New accounts use it, and existing ones can move to it with
+    /// `upgrade_to_latest`. Returns the version number.
+    pub fn add_version(env: Env, wasm_hash: BytesN<32>) -> Result<u32, DeployerError> {
+        Self::admin(env.clone()).require_auth();
+        let version = Self::latest_version(env.clone()) + 1;
+        let k = Key::Version(version);
+        env.storage().persistent().set(&k, &wasm_hash);
+        bump(&env, &k);
+        let k = Key::Approved(wasm_hash.clone());
+        env.storage().persistent().set(&k, &version);
+        bump(&env, &k);
+        env.storage().instance().set(&Key::LatestVersion, &version);
+        env.events().publish(("deployer", symbol_short!("version")),
+            VersionEvent{ version, wasm_hash });
+        Ok(version)
+    }
+
+    /// Deploys the latest approved `hoops-account` at the address derived
+    /// from `owner` and `salt`, and initializes it with `owner`, `router`
+    /// and this deployer in the same call so nobody else can claim it in
+    /// between.
     pub fn deploy_account(
         env: Env,
         owner: Address,
-        router: Address, // keep for future use
-        wasm_hash: BytesN<32>,
+        router: Address,
         salt: BytesN<32>,
-    ) -> Address {
+    ) -> Result<Address, DeployerError> {
         owner.require_auth();
-        let _ = router; // suppress unused variable warning for now
-        let acct_id = env.deployer().with_address(owner.clone(), salt).deploy_v2(wasm_hash, ());
-        // router is currently unused, but kept for interface compatibility
-        acct_id
+        let wasm_hash = Self::latest_wasm(env.clone()).ok_or(DeployerError::NoVersion)?;
+        let account = env.deployer().with_address(owner.clone(), salt).deploy_v2(wasm_hash, ());
+        env.invoke_contract::<()>(
+            &account,
+            &Symbol::new(&env, "initialize"),
+            (owner.clone(), router.clone(), Some(env.current_contract_address())).into_val(&env),
+        );
+
+        let k = Key::Accounts(owner.clone());
+        let mut accounts = Self::get_accounts(env.clone(), owner.clone());
+        accounts.push_back(account.clone());
+        env.storage().persistent().set(&k, &accounts);
+        bump(&env, &k);
+
+        env.events().publish(("deployer", symbol_short!("deploy")),
+            DeployEvent{ owner, account: account.clone(), router });
+        Ok(account)
+    }
+
+    /* ---- views ---- */
+    pub fn admin(env: Env) -> Address { env.storage().instance().get(&Key::Admin).unwrap() }
+
+    /// Highest approved version, 0 if none.
+    pub fn latest_version(env: Env) -> u32 { env.storage().instance().get(&Key::LatestVersion).unwrap_or(0) }
+
+    pub fn get_version(env: Env, version: u32) -> Option<BytesN<32>> {
+        env.storage().persistent().get(&Key::Version(version))
+    }
+
+    /// Version `wasm_hash` was approved as, if any. Accounts only
+    /// `upgrade` to approved hashes.
+    pub fn version_of(env: Env, wasm_hash: BytesN<32>) -> Option<u32> {
+        env.storage().persistent().get(&Key::Approved(wasm_hash))
+    }
+
+    /// WASM hash of the latest approved version.
+    pub fn latest_wasm(env: Env) -> Option<BytesN<32>> {
+        let version = Self::latest_version(env.clone());
+        Self::get_version(env, version)
+    }
+
+    /// Accounts deployed for `owner` through this deployer.
+    pub fn get_accounts(env: Env, owner: Address) -> Vec<Address> {
+        env.storage().persistent().get(&Key::Accounts(owner)).unwrap_or(Vec::new(&env))
+    }
+
+    /// Address `deploy_account` will use for `owner` and `salt`.
+    pub fn predict_address(env: Env, owner: Address, salt: BytesN<32>) -> Address {
+        env.deployer().with_address(owner, salt).deployed_address()
     }
 }
+
+#[cfg(test)]
+mod test;
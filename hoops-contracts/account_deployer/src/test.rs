@@ -0,0 +1,97 @@
+extern crate std;
+
+use soroban_sdk::{
+    testutils::{Address as _, BytesN as _},
+    vec, Address, BytesN, Env, IntoVal, Symbol, Val, Vec,
+};
+
+use crate::{Deployer, DeployerClient, DeployerError};
+
+// Built from `account/`. Rebuild it into `bytecodes/` whenever the account
+// interface changes (see the workspace README).
+const ACCOUNT_WASM: &[u8] = include_bytes!("../../bytecodes/hoops_account.wasm");
+
+struct TestEnv {
+    env: Env,
+    deployer: DeployerClient<'static>,
+    owner: Address,
+    router: Address,
+}
+
+impl TestEnv {
+    fn setup() -> Self {
+        let env = Env::default();
+        env.mock_all_auths();
+        let deployer = DeployerClient::new(&env, &env.register(Deployer, (Address::generate(&env),)));
+        let wasm_hash = env.deployer().upload_contract_wasm(ACCOUNT_WASM);
+        deployer.add_version(&wasm_hash);
+        TestEnv {
+            owner: Address::generate(&env),
+            router: Address::generate(&env),
+            env,
+            deployer,
+        }
+    }
+}
+
+#[test]
+fn test_deploy_account() {
+    let t = TestEnv::setup();
+    let salt = BytesN::from_array(&t.env, &[1; 32]);
+    let predicted = t.deployer.predict_address(&t.owner, &salt);
+
+    let acct = t.deployer.deploy_account(&t.owner, &t.router, &salt);
+    assert_eq!(acct, predicted);
+
+    // Initialized in the same call
+    let view = |name: &str| t.env.invoke_contract::<Address>(&acct, &Symbol::new(&t.env, name), Vec::new(&t.env));
+    assert_eq!(view("owner"), t.owner);
+    assert_eq!(view("router"), t.router);
+    let deployer: Option<Address> = t.env.invoke_contract(&acct, &Symbol::new(&t.env, "deployer"), Vec::new(&t.env));
+    assert_eq!(deployer, Some(t.deployer.address.clone()));
+    let args: Vec<Val> = (Address::generate(&t.env), t.router.clone()).into_val(&t.env);
+    assert!(t.env.try_invoke_contract::<(), soroban_sdk::Error>(&acct, &Symbol::new(&t.env, "initialize"), args).is_err());
+}
+
+#[test]
+fn test_get_accounts() {
+    let t = TestEnv::setup();
+    let other = Address::generate(&t.env);
+    assert_eq!(t.deployer.get_accounts(&t.owner), vec![&t.env]);
+
+    let first = t.deployer.deploy_account(&t.owner, &t.router, &BytesN::from_array(&t.env, &[1; 32]));
+    let second = t.deployer.deploy_account(&t.owner, &t.router, &BytesN::from_array(&t.env, &[2; 32]));
+    let theirs = t.deployer.deploy_account(&other, &t.router, &BytesN::from_array(&t.env, &[1; 32]));
+
+    assert_eq!(t.deployer.get_accounts(&t.owner), vec![&t.env, first.clone(), second]);
+    assert_eq!(t.deployer.get_accounts(&other), vec![&t.env, theirs.clone()]);
+    // Same salt, different owner, different address
+    assert_ne!(first, theirs);
+}
+
+#[test]
+fn test_versions() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let deployer = DeployerClient::new(&env, &env.register(Deployer, (admin.clone(),)));
+    let salt = BytesN::from_array(&env, &[1; 32]);
+    let (v1, v2) = (BytesN::random(&env), BytesN::random(&env));
+
+    assert_eq!(deployer.admin(), admin);
+    assert_eq!(deployer.latest_wasm(), None);
+    assert_eq!(
+        deployer.try_deploy_account(&admin, &admin, &salt),
+        Err(Ok(DeployerError::NoVersion))
+    );
+
+    assert_eq!(deployer.add_version(&v1), 1);
+    assert_eq!(env.auths()[0].0, admin);
+    assert_eq!(deployer.add_version(&v2), 2);
+    assert_eq!(deployer.latest_version(), 2);
+    assert_eq!(deployer.version_of(&v1), Some(1));
+    assert_eq!(deployer.version_of(&BytesN::random(&env)), None);
+    assert_eq!(deployer.latest_wasm(), Some(v2));
+    assert_eq!(deployer.get_version(&1), Some(v1));
+    assert_eq!(deployer.get_version(&3), None);
+}
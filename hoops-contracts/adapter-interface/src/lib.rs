@@ -1,6 +1,6 @@
 #![no_std]
 
-use soroban_sdk::{contractclient, contractspecfn, contracterror, Address, Env, Vec, BytesN};
+use soroban_sdk::{contractclient, contractspecfn, contracterror, contracttype, Address, Env, Vec, BytesN};
 
 #[contracterror]
 #[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
@@ -25,6 +25,52 @@ pub enum AdapterError {
     InsufficientBalance = 211,
     InsufficientLiquidity = 212,
     PairNotFound = 213,
+    NotAuthorized = 214,
+    /// An exact-out trade needed more input than the caller's maximum.
+    MaxInExceeded = 215,
+}
+
+/// Fixed-point scale of spot prices (7 decimals, as Stellar amounts).
+pub const PRICE_SCALE: i128 = 10_000_000;
+
+/// Optional features an adapter may implement beyond plain exact-in swaps
+/// and two-token liquidity.
+#[contracttype]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[repr(u32)]
+pub enum Capability {
+    ExactOut = 0,
+    MultiHop = 1,
+    SingleSidedLp = 2,
+    Rewards = 3,
+    Staking = 4,
+}
+
+/// Pricing curve of a pool. Matches `MarketData::pool_type` in the router.
+#[contracttype]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[repr(u32)]
+pub enum PoolKind {
+    ConstantProduct = 0,
+    Stable = 1,
+    Weighted = 2,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PoolInfo {
+    pub pool: Address,
+    pub lp_token: Address,
+    /// Pool tokens, in the pool's own order.
+    pub tokens: Vec<Address>,
+    /// Reserves, same order as `tokens`.
+    pub reserves: Vec<i128>,
+    /// Normalized weights for weighted pools, empty otherwise.
+    pub weights: Vec<i128>,
+    pub fee_bps: u32,
+    pub kind: PoolKind,
+    /// LP tokens issued, the claim on `reserves` they share.
+    pub lp_supply: i128,
 }
 
 pub struct Spec;
@@ -33,9 +79,20 @@ pub struct Spec;
 #[contractclient(name = "AdapterClient")]
 pub trait AdapterTrait {
     /* -------- lifecycle ------------------------------------------------ */
-    fn initialize(e: Env, amm_id: i128, amm_address: Address) -> Result<(), AdapterError>;
+    fn initialize(
+        e: Env,
+        amm_id: i128,
+        amm_address: Address,
+        admin: Address,
+        usdc: Address,
+    ) -> Result<(), AdapterError>;
     fn upgrade (e: Env, new_wasm: BytesN<32>) -> Result<(), AdapterError>;
     fn version() -> u32;
+
+    /* -------- admin ---------------------------------------------------- */
+    // set_admin, set_usdc, set_ttl, set_router, get_router, get_admin and
+    // get_config come from `hoops_common::adapter_admin!`.
+
     /* -------- swaps ---------------------------------------------------- */
     fn swap_exact_in(
         e: Env,
@@ -55,12 +112,39 @@ pub trait AdapterTrait {
         deadline: u64,
     ) -> Result<i128, AdapterError>;
 
+    /// `swap_exact_in` of `path`'s two tokens on `pool`, one of the pools
+    /// from `list_pools`, instead of the pool the adapter would pick for the
+    /// pair. A pool the adapter does not trade the pair on is rejected.
+    fn swap_exact_in_on(
+        e: Env,
+        pool: Address,
+        amount_in: i128,
+        min_out: i128,
+        path: Vec<Address>,
+        to: Address,
+        deadline: u64,
+    ) -> Result<i128, AdapterError>;
+
+    /// `swap_exact_out` on `pool`, like `swap_exact_in_on`.
+    fn swap_exact_out_on(
+        e: Env,
+        pool: Address,
+        amount_out: i128,
+        max_in: i128,
+        path: Vec<Address>,
+        to: Address,
+        deadline: u64,
+    ) -> Result<i128, AdapterError>;
 
     /* ---------- quotes ---------- */
     fn quote_in(e: Env, pool_address: Address, amount_in: i128, token_in: Address, token_out: Address) -> Result<i128, AdapterError>;
     fn quote_out(e: Env, pool_address: Address, amount_out: i128, token_in: Address, token_out: Address) -> Result<i128, AdapterError>;
+    /// Marginal price of `token_in` in `token_out` on `pool`, before fees:
+    /// `token_out` per `token_in`, scaled by `PRICE_SCALE`.
+    fn get_spot_price(e: Env, pool: Address, token_in: Address, token_out: Address) -> Result<i128, AdapterError>;
 
     /* -------- liquidity ------------------------------------------------ */
+    #[allow(clippy::too_many_arguments)]
     fn add_liquidity(
         e: Env,
         token_a: Address,
@@ -73,6 +157,9 @@ pub trait AdapterTrait {
         deadline: u64,
     ) -> Result<(i128, i128, i128), AdapterError>;
 
+    /// Takes `lp_amt` of `lp` from `to`, redeems it from the adapter's own
+    /// balance and pays the pool's two tokens to `to`. Like the swaps, the
+    /// only auth needed from `to` is the LP transfer to the adapter.
     fn remove_liquidity(
         e: Env,
         lp_token: Address,
@@ -82,4 +169,12 @@ pub trait AdapterTrait {
         to: Address,
         deadline: u64,
     ) -> Result<(i128,i128), AdapterError>;
+
+    /* -------- discovery ------------------------------------------------ */
+    fn get_pool_info(e: Env, pool: Address) -> Result<PoolInfo, AdapterError>;
+    fn supports(e: Env, capability: Capability) -> bool;
+    /// Pools `start..start + limit` of those this adapter can trade: every
+    /// factory pool, or the registered ones for adapters that keep their own
+    /// pool mapping.
+    fn list_pools(e: Env, start: u32, limit: u32) -> Vec<Address>;
 }
@@ -1,4 +1,4 @@
-use soroban_sdk::{contracttype, symbol_short, Address, Env, Vec};
+use soroban_sdk::{contracttype, symbol_short, Address, BytesN, Env, Vec};
 
 #[contracttype]
 #[derive(Clone)]
@@ -16,9 +16,24 @@ pub struct AddLpEvent { pub token_a: Address, pub token_b: Address, pub lp: Addr
 #[derive(Clone)]
 pub struct RemLpEvent { pub lp: Address, pub to: Address }
 
+#[contracttype]
+#[derive(Clone)]
+pub struct PoolSetEvent { pub tokens: Vec<Address>, pub pool: Address, pub lp: Address, pub pool_index: BytesN<32>, pub by: Address }
+
+#[contracttype]
+#[derive(Clone)]
+pub struct PoolRemEvent { pub tokens: Vec<Address>, pub pool: Address, pub pool_index: BytesN<32>, pub by: Address }
+
+#[contracttype]
+#[derive(Clone)]
+pub struct ClaimEvent { pub user: Address, pub lp: Address, pub amount: i128, pub to: Address }
+
 pub(crate) fn init(e: &Env, amm: Address) { e.events().publish(("aqua",symbol_short!("init")), InitEvent{amm}); }
 pub(crate) fn swap(e:&Env, ev:SwapEvent){ e.events().publish(("aqua",symbol_short!("swap")), ev); }
 #[allow(dead_code)]
 pub(crate) fn add_lp(e:&Env, ev:AddLpEvent){ e.events().publish(("aqua",symbol_short!("addlp")), ev); }
 #[allow(dead_code)]
 pub(crate) fn rem_lp(e:&Env, ev:RemLpEvent){ e.events().publish(("aqua",symbol_short!("remlp")), ev); }
+pub(crate) fn pool_set(e:&Env, ev:PoolSetEvent){ e.events().publish(("aqua",symbol_short!("pool_set")), ev); }
+pub(crate) fn pool_rem(e:&Env, ev:PoolRemEvent){ e.events().publish(("aqua",symbol_short!("pool_rem")), ev); }
+pub(crate) fn claim(e:&Env, ev:ClaimEvent){ e.events().publish(("aqua",symbol_short!("claim")), ev); }
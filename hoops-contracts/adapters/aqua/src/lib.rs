@@ -5,77 +5,905 @@ mod protocol;
 mod storage;
 
 use event::*;
-use hoops_adapter_interface::{AdapterError, AdapterTrait};
+use hoops_adapter_interface::{AdapterError, AdapterTrait, Capability, PoolInfo, PoolKind, PRICE_SCALE};
+use hoops_common::adapter_base;
 use soroban_fixed_point_math::SorobanFixedPoint;
-use soroban_sdk::{contract, contractimpl, log, panic_with_error, token::Client as TokenClient, Address, BytesN, Env, Vec};
+use soroban_sdk::{
+    contract, contractimpl, log, symbol_short, token::Client as TokenClient, Address, BytesN, Env, IntoVal, InvokeError,
+    Map, Symbol, Vec,
+};
 use storage::{
-    bump, get_amm, get_pool_for_tokens, is_init, mark_init, set_amm, set_pool_for_tokens,
-    AquaPoolInfo,
+    bump, get_all_pools, get_amm, get_pool, get_pools_for_tokens, is_init, mark_init, remove_pool,
+    set_amm, set_pool, AquaPoolInfo,
 };
 
-use crate::storage::get_core_config;
-
 const PROTOCOL_ID: i128 = 0;
-pub fn get_deposit_amounts(
+/// In/out token indices of `path` within the pool's `get_tokens()`.
+fn token_indices(
+    pool: &protocol::AquaPoolClient,
+    path: &Vec<Address>,
+) -> Result<(u32, u32), AdapterError> {
+    let tokens = pool.get_tokens();
+    let in_idx = tokens
+        .first_index_of(path.get_unchecked(0))
+        .ok_or(AdapterError::UnsupportedPair)?;
+    let out_idx = tokens
+        .first_index_of(path.get_unchecked(1))
+        .ok_or(AdapterError::UnsupportedPair)?;
+    Ok((in_idx, out_idx))
+}
+
+/// `PoolNotFound` unless `pool` is registered with the adapter.
+fn check_registered(e: &Env, pool: &Address) -> Result<(), AdapterError> {
+    if !get_all_pools(e).iter().any(|p| p.pool_address == *pool) {
+        return Err(AdapterError::PoolNotFound);
+    }
+    Ok(())
+}
+
+fn check_live(e: &Env, deadline: u64) -> Result<(), AdapterError> {
+    if !is_init(e) {
+        return Err(AdapterError::NotInitialized);
+    }
+    if e.ledger().timestamp() > deadline {
+        return Err(AdapterError::DeadlinePassed);
+    }
+    Ok(())
+}
+
+fn check_swap(e: &Env, path: &Vec<Address>, deadline: u64) -> Result<(), AdapterError> {
+    check_live(e, deadline)?;
+    if path.len() < 2 {
+        return Err(AdapterError::InvalidPath);
+    }
+    Ok(())
+}
+
+/// Registered pool for `path` giving the most output for `amt_in`, with that
+/// output. Pools whose estimate fails (killed, empty, ...) are skipped.
+fn best_pool_in(e: &Env, path: &Vec<Address>, amt_in: u128) -> Result<(AquaPoolInfo, u128), AdapterError> {
+    let pools = get_pools_for_tokens(e, path);
+    if pools.is_empty() {
+        return Err(AdapterError::PoolNotFound);
+    }
+    let mut best: Option<(AquaPoolInfo, u128)> = None;
+    for info in pools.iter() {
+        let pool = protocol::AquaPoolClient::new(e, &info.pool_address);
+        let Ok((in_idx, out_idx)) = token_indices(&pool, path) else { continue };
+        if let Ok(Ok(out)) = pool.try_estimate_swap(&in_idx, &out_idx, &amt_in) {
+            if best.as_ref().is_none_or(|(_, b)| out > *b) {
+                best = Some((info, out));
+            }
+        }
+    }
+    best.ok_or(AdapterError::InsufficientLiquidity)
+}
+
+/// Registered pool for `path` requiring the least input to receive `out`,
+/// with that input.
+fn best_pool_out(e: &Env, path: &Vec<Address>, out: u128) -> Result<(AquaPoolInfo, u128), AdapterError> {
+    let pools = get_pools_for_tokens(e, path);
+    if pools.is_empty() {
+        return Err(AdapterError::PoolNotFound);
+    }
+    let mut best: Option<(AquaPoolInfo, u128)> = None;
+    for info in pools.iter() {
+        let pool = protocol::AquaPoolClient::new(e, &info.pool_address);
+        let Ok((in_idx, out_idx)) = token_indices(&pool, path) else { continue };
+        if let Ok(Ok(amt_in)) = pool.try_estimate_swap_strict_receive(&in_idx, &out_idx, &out) {
+            if best.as_ref().is_none_or(|(_, b)| amt_in < *b) {
+                best = Some((info, amt_in));
+            }
+        }
+    }
+    best.ok_or(AdapterError::InsufficientLiquidity)
+}
+
+/// Registered pool at address `pool` holding both tokens of `path`.
+fn pool_at(e: &Env, path: &Vec<Address>, pool: &Address) -> Result<AquaPoolInfo, AdapterError> {
+    get_pools_for_tokens(e, path)
+        .iter()
+        .find(|info| info.pool_address == *pool)
+        .ok_or(AdapterError::PoolNotFound)
+}
+
+type SwapsChain = Vec<(Vec<Address>, BytesN<32>, Address)>;
+
+fn hop(e: &Env, path: &Vec<Address>, i: u32) -> Vec<Address> {
+    Vec::from_array(e, [path.get_unchecked(i), path.get_unchecked(i + 1)])
+}
+
+fn chain_entry(e: &Env, info: &AquaPoolInfo, token_out: Address) -> (Vec<Address>, BytesN<32>, Address) {
+    let pool_tokens = protocol::AquaPoolClient::new(e, &info.pool_address).get_tokens();
+    (pool_tokens, info.pool_index.clone(), token_out)
+}
+
+/// Aqua `swaps_chain` for `path`, picking the best registered pool per hop by
+/// walking `estimate_swap` forward from `amt_in`. Also returns the estimated
+/// final output.
+fn chain_in(e: &Env, path: &Vec<Address>, amt_in: u128) -> Result<(SwapsChain, u128), AdapterError> {
+    let mut chain = Vec::new(e);
+    let mut amt = amt_in;
+    for i in 0..path.len() - 1 {
+        let (info, out) = best_pool_in(e, &hop(e, path, i), amt)?;
+        chain.push_back(chain_entry(e, &info, path.get_unchecked(i + 1)));
+        amt = out;
+    }
+    Ok((chain, amt))
+}
+
+/// Aqua `swaps_chain` for `path`, walking `estimate_swap_strict_receive`
+/// backwards from `out`. Also returns the estimated input required.
+fn chain_out(e: &Env, path: &Vec<Address>, out: u128) -> Result<(SwapsChain, u128), AdapterError> {
+    let mut chain = Vec::new(e);
+    let mut amt = out;
+    for i in (0..path.len() - 1).rev() {
+        let (info, amt_in) = best_pool_out(e, &hop(e, path, i), amt)?;
+        chain.push_front(chain_entry(e, &info, path.get_unchecked(i + 1)));
+        amt = amt_in;
+    }
+    Ok((chain, amt))
+}
+
+/// Translate an Aqua pool error into an `AdapterError`. Unknown errors stay
+/// `ExternalFailure`.
+fn pool_err(err: Result<soroban_sdk::Error, InvokeError>) -> AdapterError {
+    use protocol::aqua_liquidity_pool::{LiquidityPoolError as PoolError, LiquidityPoolValidationError as Invalid};
+    const MAP: [(Invalid, AdapterError); 7] = [
+        (Invalid::InMinNotSatisfied, AdapterError::MinAmountNotMet),
+        (Invalid::OutMinNotSatisfied, AdapterError::MinAmountNotMet),
+        (Invalid::InMaxNotSatisfied, AdapterError::MaxInExceeded),
+        (Invalid::EmptyPool, AdapterError::InsufficientLiquidity),
+        (Invalid::InsufficientBalance, AdapterError::InsufficientBalance),
+        (Invalid::ZeroAmount, AdapterError::InvalidAmount),
+        (Invalid::InvalidDepositAmount, AdapterError::InvalidAmount),
+    ];
+    let Ok(code) = err else { return AdapterError::ExternalFailure };
+    if code == PoolError::PoolSwapKilled.into() || code == PoolError::PoolDepositKilled.into() {
+        return AdapterError::PoolNotFound;
+    }
+    MAP.into_iter()
+        .find(|(c, _)| code == (*c).into())
+        .map_or(AdapterError::ExternalFailure, |(_, a)| a)
+}
+
+fn swap_in_pool(
     e: &Env,
-    desired_a: u128,
-    min_a: u128,
-    desired_b: u128,
-    min_b: u128,
-    reserve_a: u128,
-    reserve_b: u128,
-) -> (u128, u128) {
-    if reserve_a == 0 && reserve_b == 0 {
-        return (desired_a, desired_b);
-    }
-
-    let amount_b = desired_a.fixed_mul_floor(e, &reserve_b, &reserve_a);
-    if amount_b <= desired_b {
-        if amount_b < min_b {
-            panic_with_error!(e, AdapterError::InvalidAmount);
-        }
-        (desired_a, amount_b)
-    } else {
-        let amount_a = desired_b.fixed_mul_floor(&e, &reserve_a, &reserve_b);
-        if amount_a > desired_a || desired_a < min_a {
-            panic_with_error!(e, AdapterError::InvalidAmount);
+    pool_info: &AquaPoolInfo,
+    amt_in: u128,
+    min_out: u128,
+    path: Vec<Address>,
+    to: Address,
+) -> Result<i128, AdapterError> {
+    let pool = protocol::AquaPoolClient::new(e, &pool_info.pool_address);
+    let (in_idx, out_idx) = token_indices(&pool, &path)?;
+    let (token_in, token_out) = (path.get_unchecked(0), path.get_unchecked(1));
+    // Trade from the adapter's balance; the pool pulls the input from here
+    adapter_base::take(e, &token_in, &to, amt_in as i128).ok_or(AdapterError::InsufficientBalance)?;
+    adapter_base::authorize_transfer(e, &token_in, &pool_info.pool_address, amt_in as i128);
+    let amt_out = pool.try_swap(
+        &e.current_contract_address(), // user
+        &in_idx,
+        &out_idx,
+        //todo: convert all our usage of i128 as amounts to u128 for safety.
+        &amt_in,
+        &min_out,
+    )
+        .map_err(pool_err)?
+        .map_err(|_| AdapterError::ExternalFailure)?;
+    let amt_out_i128 = amt_out as i128;
+    adapter_base::pay(e, &token_out, &to, amt_out_i128);
+    event::swap(
+        e,
+        event::SwapEvent {
+            amt_in: amt_in as i128,
+            amt_out: amt_out_i128,
+            path,
+            to,
+        },
+    );
+    bump(e);
+    Ok(amt_out_i128)
+}
+
+fn swap_out_pool(
+    e: &Env,
+    pool_info: &AquaPoolInfo,
+    out: u128,
+    max_in: u128,
+    path: Vec<Address>,
+    to: Address,
+) -> Result<i128, AdapterError> {
+    let pool = protocol::AquaPoolClient::new(e, &pool_info.pool_address);
+    let (in_idx, out_idx) = token_indices(&pool, &path)?;
+    let (token_in, token_out) = (path.get_unchecked(0), path.get_unchecked(1));
+    // The pool pulls `max_in` from the adapter and refunds what it did not use
+    adapter_base::take(e, &token_in, &to, max_in as i128).ok_or(AdapterError::InsufficientBalance)?;
+    adapter_base::authorize_transfer(e, &token_in, &pool_info.pool_address, max_in as i128);
+    let amt_in = pool.try_swap_strict_receive(
+        &e.current_contract_address(), // user
+        //todo: convert all our usage of i128 as amounts to u128 for safety.
+        &in_idx,
+        &out_idx,
+        &out,
+        &max_in,
+    )
+        .map_err(pool_err)?
+        .map_err(|_| AdapterError::ExternalFailure)?;
+
+    let amt_in_i128 = amt_in as i128;
+    adapter_base::pay(e, &token_out, &to, out as i128);
+    adapter_base::pay(e, &token_in, &to, max_in as i128 - amt_in_i128);
+    event::swap(
+        e,
+        event::SwapEvent {
+            amt_in: amt_in_i128,
+            amt_out: out as i128,
+            path,
+            to,
+        },
+    );
+    bump(e);
+    Ok(amt_in_i128)
+}
+
+fn is_stable(pool: &protocol::AquaPoolClient) -> bool {
+    pool.pool_type() == symbol_short!("stable")
+}
+
+fn to_u128s(e: &Env, amounts: &Vec<i128>) -> Result<Vec<u128>, AdapterError> {
+    let mut out = Vec::new(e);
+    for amt in amounts.iter() {
+        if amt < 0 {
+            return Err(AdapterError::InvalidAmount);
         }
-        (amount_a, desired_b)
+        out.push_back(amt as u128);
     }
+    Ok(out)
 }
 
-pub fn get_shares(
+/// Reorders `amounts` (aligned with `tokens`) into the pool's token order,
+/// with zero for pool tokens the caller did not list.
+fn to_pool_order(
     e: &Env,
+    tokens: &Vec<Address>,
+    amounts: &Vec<u128>,
+    pool_tokens: &Vec<Address>,
+) -> Result<Vec<u128>, AdapterError> {
+    if tokens.len() != amounts.len() {
+        return Err(AdapterError::InvalidArgument);
+    }
+    if !tokens.iter().all(|t| pool_tokens.contains(&t)) {
+        return Err(AdapterError::UnsupportedPair);
+    }
+    let mut out = Vec::new(e);
+    for t in pool_tokens.iter() {
+        out.push_back(tokens.first_index_of(&t).map_or(0, |i| amounts.get_unchecked(i)));
+    }
+    Ok(out)
+}
+
+/// Inverse of [`to_pool_order`].
+fn from_pool_order(
+    e: &Env,
+    tokens: &Vec<Address>,
+    amounts: &Vec<u128>,
+    pool_tokens: &Vec<Address>,
+) -> Vec<i128> {
+    let mut out = Vec::new(e);
+    for t in tokens.iter() {
+        let i = pool_tokens.first_index_of(&t).unwrap();
+        out.push_back(amounts.get_unchecked(i) as i128);
+    }
+    out
+}
+
+/// Shares the pool would mint for `amounts` (pool token order). Stableswap
+/// pools answer through their own `calc_token_amount`; constant-product pools
+/// mint in proportion to the scarcest non-zero side.
+fn estimate_shares(e: &Env, pool_address: &Address, amounts: &Vec<u128>) -> Result<u128, AdapterError> {
+    let pool = protocol::AquaPoolClient::new(e, pool_address);
+    if is_stable(&pool) {
+        let stable = protocol::AquaStableSwapPoolClient::new(e, pool_address);
+        return match stable.try_calc_token_amount(amounts, &true) {
+            Ok(Ok(shares)) => Ok(shares),
+            _ => Err(AdapterError::InsufficientLiquidity),
+        };
+    }
+    let reserves = pool.get_reserves();
+    let total_shares = pool.get_total_shares();
+    if total_shares == 0 {
+        return Err(AdapterError::InsufficientLiquidity);
+    }
+    let mut shares: Option<u128> = None;
+    for (i, amt) in amounts.iter().enumerate() {
+        if amt == 0 {
+            continue;
+        }
+        let reserve = reserves.get_unchecked(i as u32);
+        if reserve == 0 {
+            return Err(AdapterError::InsufficientLiquidity);
+        }
+        let s = amt.fixed_mul_floor(e, &total_shares, &reserve);
+        shares = Some(shares.map_or(s, |m| m.min(s)));
+    }
+    Ok(shares.unwrap_or(0))
+}
+
+/// Deposits `amounts` (aligned with `tokens`) into the pool. Returns the
+/// deposited amounts, aligned with `tokens`, and the minted shares.
+fn deposit_to_pool(
+    e: &Env,
+    pool_info: &AquaPoolInfo,
+    tokens: &Vec<Address>,
+    amounts: &Vec<u128>,
+    min_shares: u128,
+    to: &Address,
+    seed_empty: bool,
+) -> Result<(Vec<i128>, i128), AdapterError> {
+    let pool = protocol::AquaPoolClient::new(e, &pool_info.pool_address);
+    let pool_tokens = pool.get_tokens();
+    let desired = to_pool_order(e, tokens, amounts, &pool_tokens)?;
+    if desired.iter().all(|a| a == 0) {
+        return Err(AdapterError::InvalidAmount);
+    }
+    let has_zero = desired.iter().any(|a| a == 0);
+    if pool.get_total_shares() == 0 {
+        if !seed_empty {
+            return Err(AdapterError::InsufficientLiquidity);
+        }
+        // The first deposit sets the price, so every token is required
+        if has_zero {
+            return Err(AdapterError::InvalidAmount);
+        }
+    } else if has_zero && !is_stable(&pool) {
+        // Single-sided deposits are a stableswap feature
+        return Err(AdapterError::InvalidAmount);
+    }
+    log!(e, "Depositing {:?} into Aqua pool {:?}", desired, pool_info.pool_address);
+    let (deposited, shares) = pool.try_deposit(to, &desired, &min_shares)
+        .map_err(pool_err)?
+        .map_err(|_| AdapterError::ExternalFailure)?;
+    bump(e);
+    Ok((from_pool_order(e, tokens, &deposited, &pool_tokens), shares as i128))
+}
+
+#[allow(clippy::too_many_arguments)]
+fn add_liquidity_to_pool(
+    e: &Env,
+    pool_info: &AquaPoolInfo,
+    token_a: Address,
+    token_b: Address,
     amt_a: u128,
     amt_b: u128,
-    reserve_a: u128,
-    reserve_b: u128,
-    shares: u128,
-) -> u128 {
-    if reserve_a == 0 || reserve_b == 0 || shares == 0 || amt_a == 0 || amt_b == 0 {
-        return 0; // No shares can be created in an empty pool
-    }
-    let new_reserve_a = reserve_a + amt_a;
-    let new_reserve_b = reserve_b + amt_b;
-    let shares_a = amt_a.fixed_mul_floor(e, &shares, &new_reserve_a);
-    let shares_b = amt_b.fixed_mul_floor(e, &shares, &new_reserve_b);
-    shares_a.min(shares_b)
+    amt_a_min: u128,
+    amt_b_min: u128,
+    to: &Address,
+) -> Result<(i128, i128, i128), AdapterError> {
+    let tokens = Vec::from_array(e, [token_a, token_b]);
+    let pool = protocol::AquaPoolClient::new(e, &pool_info.pool_address);
+    // The caller's minimums translate into a share floor: what depositing
+    // just the minimums would mint now. Constant-product pools take amounts
+    // in proportion to the reserves; stableswap pools take everything that
+    // is offered, so the floor is their only slippage bound.
+    let min_shares = if amt_a_min == 0 && amt_b_min == 0 {
+        0
+    } else {
+        let mins = Vec::from_array(e, [amt_a_min, amt_b_min]);
+        estimate_shares(e, &pool_info.pool_address, &to_pool_order(e, &tokens, &mins, &pool.get_tokens())?)?
+    };
+    let amounts = Vec::from_array(e, [amt_a, amt_b]);
+    let (deposited, shares) = deposit_to_pool(e, pool_info, &tokens, &amounts, min_shares, to, false)?;
+    let amount_a = deposited.get_unchecked(0);
+    let amount_b = deposited.get_unchecked(1);
+    if (amount_a as u128) < amt_a_min || (amount_b as u128) < amt_b_min {
+        return Err(AdapterError::MinAmountNotMet);
+    }
+    Ok((amount_a, amount_b, shares))
 }
+
 #[contract]
 pub struct AquaAdapter;
 
+hoops_common::adapter_admin!(AquaAdapter, AdapterError);
+
 pub trait AquaAdapterTrait {
-    fn set_pool_for_tokens(e: Env, tokens: Vec<Address>, info: AquaPoolInfo);
+    /// Map a token pair (or the full token set of a 3-4 token stableswap
+    /// pool) to an Aqua pool under `info.pool_index`. `caller` must be the
+    /// adapter admin or the registered router, the pool must hold every
+    /// token, and the Aqua router must list the pool under that index.
+    fn set_pool_for_tokens(
+        e: Env,
+        caller: Address,
+        tokens: Vec<Address>,
+        info: AquaPoolInfo,
+    ) -> Result<(), AdapterError>;
+    /// Drop one pool mapping for a token pair or set (admin or router only).
+    fn remove_pool_for_tokens(
+        e: Env,
+        caller: Address,
+        tokens: Vec<Address>,
+        pool_index: BytesN<32>,
+    ) -> Result<(), AdapterError>;
+    /// First registered pool for the pair, if any.
     fn get_pool_for_tokens(e: Env, tokens: Vec<Address>) -> Option<AquaPoolInfo>;
+    fn get_pool(e: Env, tokens: Vec<Address>, pool_index: BytesN<32>) -> Option<AquaPoolInfo>;
+    /// Every pool registered for the pair, in registration order.
+    fn get_pools_for_tokens(e: Env, tokens: Vec<Address>) -> Vec<AquaPoolInfo>;
+    /// Pool the default `swap_exact_in` would route `amt_in` through.
+    fn get_best_pool_in(e: Env, path: Vec<Address>, amt_in: i128) -> Result<AquaPoolInfo, AdapterError>;
+    /// Pool the default `swap_exact_out` would route `out` through.
+    fn get_best_pool_out(e: Env, path: Vec<Address>, out: i128) -> Result<AquaPoolInfo, AdapterError>;
+    /// Output of `swap_exact_in` along `path`, chaining `estimate_swap` over
+    /// the best pool of each hop.
+    fn quote_path_in(e: Env, path: Vec<Address>, amt_in: i128) -> Result<i128, AdapterError>;
+    /// Input `swap_exact_out` needs along `path` to receive `out`.
+    fn quote_path_out(e: Env, path: Vec<Address>, out: i128) -> Result<i128, AdapterError>;
+
+    /* ---------- explicit pool ---------- */
+    fn swap_exact_in_pool(
+        e: Env,
+        pool_index: BytesN<32>,
+        amt_in: i128,
+        min_out: i128,
+        path: Vec<Address>,
+        to: Address,
+        deadline: u64,
+    ) -> Result<i128, AdapterError>;
+    fn swap_exact_out_pool(
+        e: Env,
+        pool_index: BytesN<32>,
+        out: i128,
+        max_in: i128,
+        path: Vec<Address>,
+        to: Address,
+        deadline: u64,
+    ) -> Result<i128, AdapterError>;
+    #[allow(clippy::too_many_arguments)]
+    fn add_liquidity_pool(
+        e: Env,
+        pool_index: BytesN<32>,
+        token_a: Address,
+        token_b: Address,
+        amt_a: i128,
+        amt_b: i128,
+        amt_a_min: i128,
+        amt_b_min: i128,
+        to: Address,
+        deadline: u64,
+    ) -> Result<(i128, i128, i128), AdapterError>;
+
+    /* ---------- multi-token liquidity ---------- */
+    /// Shares `deposit` would mint for `amounts` (aligned with `tokens`),
+    /// as estimated by the pool.
+    fn estimate_deposit(
+        e: Env,
+        tokens: Vec<Address>,
+        pool_index: BytesN<32>,
+        amounts: Vec<i128>,
+    ) -> Result<i128, AdapterError>;
+    /// Deposit into the pool registered under `(tokens, pool_index)`.
+    /// `amounts` is aligned with `tokens`; stableswap pools accept zeros
+    /// (single-sided deposits). Empty pools are only seeded when
+    /// `seed_empty` is set. Returns the deposited amounts (aligned with
+    /// `tokens`) and the minted shares.
+    #[allow(clippy::too_many_arguments)]
+    fn deposit(
+        e: Env,
+        tokens: Vec<Address>,
+        pool_index: BytesN<32>,
+        amounts: Vec<i128>,
+        min_shares: i128,
+        to: Address,
+        deadline: u64,
+        seed_empty: bool,
+    ) -> Result<(Vec<i128>, i128), AdapterError>;
+    /// Burn `share_amount` of `lp` for all pool tokens. `min_amounts` and the
+    /// result follow the pool's `get_tokens()` order.
+    fn withdraw(
+        e: Env,
+        lp: Address,
+        share_amount: i128,
+        min_amounts: Vec<i128>,
+        to: Address,
+        deadline: u64,
+    ) -> Result<Vec<i128>, AdapterError>;
+    /// Amount of `token_out` that `withdraw_one` would return (stableswap).
+    fn estimate_withdraw_one(
+        e: Env,
+        lp: Address,
+        share_amount: i128,
+        token_out: Address,
+    ) -> Result<i128, AdapterError>;
+    /// Burn `share_amount` of a stableswap `lp` for a single token.
+    fn withdraw_one(
+        e: Env,
+        lp: Address,
+        share_amount: i128,
+        token_out: Address,
+        min_amount: i128,
+        to: Address,
+        deadline: u64,
+    ) -> Result<i128, AdapterError>;
+
+    /* ---------- rewards ---------- */
+    /// AQUA reward token paid by the pools (admin only). Needed to forward
+    /// claims to a recipient other than the LP holder.
+    fn set_reward_token(e: Env, reward_token: Address) -> Result<(), AdapterError>;
+    fn get_reward_token(e: Env) -> Option<Address>;
+    /// Rewards `user` can currently claim for the pool behind `lp`.
+    fn get_user_reward(e: Env, user: Address, lp: Address) -> Result<i128, AdapterError>;
+    /// The pool's reward info for `user` (rates, working balance, ...).
+    fn get_rewards_info(e: Env, user: Address, lp: Address) -> Result<Map<Symbol, i128>, AdapterError>;
+    /// Claim `user`'s rewards for the pool behind `lp` and send them to `to`.
+    /// Returns the claimed amount.
+    fn claim(e: Env, user: Address, lp: Address, to: Address) -> Result<i128, AdapterError>;
 }
 
 #[contractimpl]
 impl AquaAdapterTrait for AquaAdapter {
-    fn set_pool_for_tokens(e: Env, tokens: Vec<Address>, info: AquaPoolInfo) {
-        set_pool_for_tokens(&e, &tokens, &info);
+    fn set_pool_for_tokens(
+        e: Env,
+        caller: Address,
+        tokens: Vec<Address>,
+        info: AquaPoolInfo,
+    ) -> Result<(), AdapterError> {
+        if !adapter_base::require_admin_or_router(&e, &caller) {
+            return Err(AdapterError::NotAuthorized);
+        }
+        let pool = protocol::AquaPoolClient::new(&e, &info.pool_address);
+        let pool_tokens = pool.get_tokens();
+        if !adapter_base::pool_has_token_set(&tokens, &pool_tokens) {
+            return Err(AdapterError::UnsupportedPair);
+        }
+        if pool.share_id() != info.lp_token_address {
+            return Err(AdapterError::InvalidArgument);
+        }
+        let router = protocol::AquaRouterClient::new(&e, &get_amm(&e)?);
+        if router.get_pools(&pool_tokens).get(info.pool_index.clone()) != Some(info.pool_address.clone()) {
+            return Err(AdapterError::InvalidArgument);
+        }
+        set_pool(&e, &tokens, &info);
+        pool_set(
+            &e,
+            PoolSetEvent {
+                tokens,
+                pool: info.pool_address,
+                lp: info.lp_token_address,
+                pool_index: info.pool_index,
+                by: caller,
+            },
+        );
+        bump(&e);
+        Ok(())
+    }
+    fn remove_pool_for_tokens(
+        e: Env,
+        caller: Address,
+        tokens: Vec<Address>,
+        pool_index: BytesN<32>,
+    ) -> Result<(), AdapterError> {
+        if !adapter_base::require_admin_or_router(&e, &caller) {
+            return Err(AdapterError::NotAuthorized);
+        }
+        if tokens.len() < 2 {
+            return Err(AdapterError::InvalidArgument);
+        }
+        let info = remove_pool(&e, &tokens, &pool_index).ok_or(AdapterError::PoolNotFound)?;
+        pool_rem(
+            &e,
+            PoolRemEvent {
+                tokens,
+                pool: info.pool_address,
+                pool_index,
+                by: caller,
+            },
+        );
+        bump(&e);
+        Ok(())
     }
     fn get_pool_for_tokens(e: Env, tokens: Vec<Address>) -> Option<AquaPoolInfo> {
-        get_pool_for_tokens(&e, &tokens)
+        get_pools_for_tokens(&e, &tokens).first()
+    }
+    fn get_pool(e: Env, tokens: Vec<Address>, pool_index: BytesN<32>) -> Option<AquaPoolInfo> {
+        get_pool(&e, &tokens, &pool_index)
+    }
+    fn get_pools_for_tokens(e: Env, tokens: Vec<Address>) -> Vec<AquaPoolInfo> {
+        get_pools_for_tokens(&e, &tokens)
+    }
+    fn get_best_pool_in(e: Env, path: Vec<Address>, amt_in: i128) -> Result<AquaPoolInfo, AdapterError> {
+        if amt_in <= 0 {
+            return Err(AdapterError::InvalidAmount);
+        }
+        if path.len() != 2 {
+            return Err(AdapterError::MultipathUnsupported);
+        }
+        best_pool_in(&e, &path, amt_in as u128).map(|(info, _)| info)
+    }
+    fn get_best_pool_out(e: Env, path: Vec<Address>, out: i128) -> Result<AquaPoolInfo, AdapterError> {
+        if out <= 0 {
+            return Err(AdapterError::InvalidAmount);
+        }
+        if path.len() != 2 {
+            return Err(AdapterError::MultipathUnsupported);
+        }
+        best_pool_out(&e, &path, out as u128).map(|(info, _)| info)
+    }
+    fn quote_path_in(e: Env, path: Vec<Address>, amt_in: i128) -> Result<i128, AdapterError> {
+        if !is_init(&e) {
+            return Err(AdapterError::NotInitialized);
+        }
+        if amt_in <= 0 {
+            return Err(AdapterError::InvalidAmount);
+        }
+        if path.len() < 2 {
+            return Err(AdapterError::InvalidPath);
+        }
+        let (_, out) = chain_in(&e, &path, amt_in as u128)?;
+        Ok(out as i128)
+    }
+    fn quote_path_out(e: Env, path: Vec<Address>, out: i128) -> Result<i128, AdapterError> {
+        if !is_init(&e) {
+            return Err(AdapterError::NotInitialized);
+        }
+        if out <= 0 {
+            return Err(AdapterError::InvalidAmount);
+        }
+        if path.len() < 2 {
+            return Err(AdapterError::InvalidPath);
+        }
+        let (_, amt_in) = chain_out(&e, &path, out as u128)?;
+        Ok(amt_in as i128)
+    }
+
+    fn swap_exact_in_pool(
+        e: Env,
+        pool_index: BytesN<32>,
+        amt_in: i128,
+        min_out: i128,
+        path: Vec<Address>,
+        to: Address,
+        deadline: u64,
+    ) -> Result<i128, AdapterError> {
+        if amt_in < 0 || min_out < 0 {
+            return Err(AdapterError::InvalidAmount);
+        };
+        to.require_auth();
+        check_swap(&e, &path, deadline)?;
+        if path.len() != 2 {
+            return Err(AdapterError::MultipathUnsupported);
+        }
+        let pool_info = get_pool(&e, &path, &pool_index).ok_or(AdapterError::PoolNotFound)?;
+        swap_in_pool(&e, &pool_info, amt_in as u128, min_out as u128, path, to)
+    }
+
+    fn swap_exact_out_pool(
+        e: Env,
+        pool_index: BytesN<32>,
+        out: i128,
+        max_in: i128,
+        path: Vec<Address>,
+        to: Address,
+        deadline: u64,
+    ) -> Result<i128, AdapterError> {
+        if out < 0 || max_in < 0 {
+            return Err(AdapterError::InvalidAmount);
+        };
+        to.require_auth();
+        check_swap(&e, &path, deadline)?;
+        if path.len() != 2 {
+            return Err(AdapterError::MultipathUnsupported);
+        }
+        let pool_info = get_pool(&e, &path, &pool_index).ok_or(AdapterError::PoolNotFound)?;
+        swap_out_pool(&e, &pool_info, out as u128, max_in as u128, path, to)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn add_liquidity_pool(
+        e: Env,
+        pool_index: BytesN<32>,
+        token_a: Address,
+        token_b: Address,
+        amt_a: i128,
+        amt_b: i128,
+        amt_a_min: i128,
+        amt_b_min: i128,
+        to: Address,
+        deadline: u64,
+    ) -> Result<(i128, i128, i128), AdapterError> {
+        to.require_auth();
+        if amt_a <= 0 || amt_b <= 0 || amt_a_min < 0 || amt_b_min < 0 {
+            return Err(AdapterError::InvalidAmount);
+        }
+        check_live(&e, deadline)?;
+        let tokens = Vec::from_array(&e, [token_a.clone(), token_b.clone()]);
+        let pool_info = get_pool(&e, &tokens, &pool_index).ok_or(AdapterError::PoolNotFound)?;
+        add_liquidity_to_pool(
+            &e,
+            &pool_info,
+            token_a,
+            token_b,
+            amt_a as u128,
+            amt_b as u128,
+            amt_a_min as u128,
+            amt_b_min as u128,
+            &to,
+        )
+    }
+
+    fn estimate_deposit(
+        e: Env,
+        tokens: Vec<Address>,
+        pool_index: BytesN<32>,
+        amounts: Vec<i128>,
+    ) -> Result<i128, AdapterError> {
+        let pool_info = get_pool(&e, &tokens, &pool_index).ok_or(AdapterError::PoolNotFound)?;
+        let pool = protocol::AquaPoolClient::new(&e, &pool_info.pool_address);
+        let amounts = to_pool_order(&e, &tokens, &to_u128s(&e, &amounts)?, &pool.get_tokens())?;
+        estimate_shares(&e, &pool_info.pool_address, &amounts).map(|s| s as i128)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn deposit(
+        e: Env,
+        tokens: Vec<Address>,
+        pool_index: BytesN<32>,
+        amounts: Vec<i128>,
+        min_shares: i128,
+        to: Address,
+        deadline: u64,
+        seed_empty: bool,
+    ) -> Result<(Vec<i128>, i128), AdapterError> {
+        to.require_auth();
+        if min_shares < 0 {
+            return Err(AdapterError::InvalidAmount);
+        }
+        let amounts = to_u128s(&e, &amounts)?;
+        check_live(&e, deadline)?;
+        let pool_info = get_pool(&e, &tokens, &pool_index).ok_or(AdapterError::PoolNotFound)?;
+        deposit_to_pool(&e, &pool_info, &tokens, &amounts, min_shares as u128, &to, seed_empty)
+    }
+
+    fn withdraw(
+        e: Env,
+        lp: Address,
+        share_amount: i128,
+        min_amounts: Vec<i128>,
+        to: Address,
+        deadline: u64,
+    ) -> Result<Vec<i128>, AdapterError> {
+        to.require_auth();
+        if share_amount <= 0 {
+            return Err(AdapterError::InvalidAmount);
+        }
+        let min_amounts = to_u128s(&e, &min_amounts)?;
+        check_live(&e, deadline)?;
+        let pool_info = storage::get_pool_by_lp_token(&e, &lp).ok_or(AdapterError::PoolNotFound)?;
+        let pool = protocol::AquaPoolClient::new(&e, &pool_info.pool_address);
+        if min_amounts.len() != pool.get_tokens().len() {
+            return Err(AdapterError::InvalidArgument);
+        }
+        if TokenClient::new(&e, &lp).balance(&to) < share_amount {
+            return Err(AdapterError::InsufficientBalance);
+        }
+        let amounts = pool.try_withdraw(&to, &(share_amount as u128), &min_amounts)
+            .map_err(pool_err)?
+            .map_err(|_| AdapterError::ExternalFailure)?;
+        let mut out = Vec::new(&e);
+        for amt in amounts.iter() {
+            out.push_back(amt as i128);
+        }
+        bump(&e);
+        Ok(out)
+    }
+
+    fn estimate_withdraw_one(
+        e: Env,
+        lp: Address,
+        share_amount: i128,
+        token_out: Address,
+    ) -> Result<i128, AdapterError> {
+        if share_amount <= 0 {
+            return Err(AdapterError::InvalidAmount);
+        }
+        let pool_info = storage::get_pool_by_lp_token(&e, &lp).ok_or(AdapterError::PoolNotFound)?;
+        let pool = protocol::AquaPoolClient::new(&e, &pool_info.pool_address);
+        if !is_stable(&pool) {
+            return Err(AdapterError::InvalidArgument);
+        }
+        let i = pool.get_tokens().first_index_of(&token_out).ok_or(AdapterError::UnsupportedPair)?;
+        let stable = protocol::AquaStableSwapPoolClient::new(&e, &pool_info.pool_address);
+        match stable.try_calc_withdraw_one_coin(&(share_amount as u128), &i) {
+            Ok(Ok(amount)) => Ok(amount as i128),
+            _ => Err(AdapterError::InsufficientLiquidity),
+        }
+    }
+
+    fn withdraw_one(
+        e: Env,
+        lp: Address,
+        share_amount: i128,
+        token_out: Address,
+        min_amount: i128,
+        to: Address,
+        deadline: u64,
+    ) -> Result<i128, AdapterError> {
+        to.require_auth();
+        if share_amount <= 0 || min_amount < 0 {
+            return Err(AdapterError::InvalidAmount);
+        }
+        check_live(&e, deadline)?;
+        let pool_info = storage::get_pool_by_lp_token(&e, &lp).ok_or(AdapterError::PoolNotFound)?;
+        let pool = protocol::AquaPoolClient::new(&e, &pool_info.pool_address);
+        if !is_stable(&pool) {
+            return Err(AdapterError::InvalidArgument);
+        }
+        let i = pool.get_tokens().first_index_of(&token_out).ok_or(AdapterError::UnsupportedPair)?;
+        if TokenClient::new(&e, &lp).balance(&to) < share_amount {
+            return Err(AdapterError::InsufficientBalance);
+        }
+        let stable = protocol::AquaStableSwapPoolClient::new(&e, &pool_info.pool_address);
+        let amounts = stable
+            .try_withdraw_one_coin(&to, &(share_amount as u128), &i, &(min_amount as u128))
+            .map_err(pool_err)?
+            .map_err(|_| AdapterError::ExternalFailure)?;
+        bump(&e);
+        Ok(amounts.get_unchecked(i) as i128)
+    }
+
+    fn set_reward_token(e: Env, reward_token: Address) -> Result<(), AdapterError> {
+        adapter_base::require_admin(&e).ok_or(AdapterError::NotInitialized)?;
+        storage::set_reward_token(&e, &reward_token);
+        bump(&e);
+        Ok(())
+    }
+
+    fn get_reward_token(e: Env) -> Option<Address> {
+        storage::get_reward_token(&e)
+    }
+
+    fn get_user_reward(e: Env, user: Address, lp: Address) -> Result<i128, AdapterError> {
+        let pool_info = storage::get_pool_by_lp_token(&e, &lp).ok_or(AdapterError::PoolNotFound)?;
+        let pool = protocol::AquaPoolClient::new(&e, &pool_info.pool_address);
+        let reward = pool.try_get_user_reward(&user)
+            .map_err(pool_err)?
+            .map_err(|_| AdapterError::ExternalFailure)?;
+        Ok(reward as i128)
+    }
+
+    fn get_rewards_info(e: Env, user: Address, lp: Address) -> Result<Map<Symbol, i128>, AdapterError> {
+        let pool_info = storage::get_pool_by_lp_token(&e, &lp).ok_or(AdapterError::PoolNotFound)?;
+        let pool = protocol::AquaPoolClient::new(&e, &pool_info.pool_address);
+        pool.try_get_rewards_info(&user)
+            .map_err(pool_err)?
+            .map_err(|_| AdapterError::ExternalFailure)
+    }
+
+    fn claim(e: Env, user: Address, lp: Address, to: Address) -> Result<i128, AdapterError> {
+        user.require_auth();
+        if !is_init(&e) {
+            return Err(AdapterError::NotInitialized);
+        }
+        let pool_info = storage::get_pool_by_lp_token(&e, &lp).ok_or(AdapterError::PoolNotFound)?;
+        // Resolve the reward token up front so a forward cannot fail after the claim
+        let reward_token = if to != user {
+            Some(storage::get_reward_token(&e).ok_or(AdapterError::NotInitialized)?)
+        } else {
+            None
+        };
+        let pool = protocol::AquaPoolClient::new(&e, &pool_info.pool_address);
+        // The pool always pays the LP holder; forward from there
+        let amount = pool.try_claim(&user)
+            .map_err(pool_err)?
+            .map_err(|_| AdapterError::ExternalFailure)? as i128;
+        if let Some(reward_token) = reward_token {
+            if amount > 0 {
+                TokenClient::new(&e, &reward_token).transfer(&user, &to, &amount);
+            }
+        }
+        event::claim(&e, ClaimEvent { user, lp, amount, to });
+        bump(&e);
+        Ok(amount)
     }
 }
 
@@ -84,7 +912,13 @@ impl AdapterTrait for AquaAdapter {
     fn version() -> u32 {
         1
     }
-    fn initialize(e: Env, amm_id: i128, amm_addr: Address) -> Result<(), AdapterError> {
+    fn initialize(
+        e: Env,
+        amm_id: i128,
+        amm_addr: Address,
+        admin: Address,
+        usdc: Address,
+    ) -> Result<(), AdapterError> {
         if is_init(&e) {
             return Err(AdapterError::AlreadyInitialized);
         }
@@ -92,6 +926,7 @@ impl AdapterTrait for AquaAdapter {
             return Err(AdapterError::InvalidID);
         }
 
+        adapter_base::init_core_config(&e, &admin, &usdc);
         set_amm(&e, amm_addr.clone());
         mark_init(&e);
         bump(&e);
@@ -100,10 +935,11 @@ impl AdapterTrait for AquaAdapter {
     }
 
     fn upgrade(e: Env, new_wasm_hash: BytesN<32>) -> Result<(), AdapterError> {
-        let config = get_core_config(&e);
-        config.admin.require_auth();
-        Ok(e.deployer().update_current_contract_wasm(new_wasm_hash))
+        adapter_base::require_admin(&e).ok_or(AdapterError::NotInitialized)?;
+        e.deployer().update_current_contract_wasm(new_wasm_hash);
+        Ok(())
     }
+
     fn swap_exact_in(
         e: Env,
         amt_in: i128,
@@ -118,34 +954,24 @@ impl AdapterTrait for AquaAdapter {
         let amt_in = amt_in as u128;
         let min_out = min_out as u128;
         to.require_auth();
-        if !is_init(&e) {
-            return Err(AdapterError::ExternalFailure);
-        }
-        if e.ledger().timestamp() > deadline {
-            return Err(AdapterError::ExternalFailure);
-        }
-        if path.len() != 2 {
-            return Err(AdapterError::MultipathUnsupported);
+        check_swap(&e, &path, deadline)?;
+        if path.len() == 2 {
+            // Default pool: whichever registered pool quotes the most output
+            let (pool_info, _) = best_pool_in(&e, &path, amt_in)?;
+            return swap_in_pool(&e, &pool_info, amt_in, min_out, path, to);
         }
-        let pool_info = get_pool_for_tokens(&e, &path).ok_or(AdapterError::UnsupportedPair)?;
-        let pool = protocol::AquaPoolClient::new(&e, &pool_info.pool_address);
-        let tokens = pool.get_tokens();
-        let in_token = path.get(0).unwrap();
-        let in_idx = if tokens.get(0).unwrap() == in_token {
-            0
-        } else {
-            1
-        };
-        let out_idx = if in_idx == 0 { 1 } else { 0 };
-        let amt_out = pool.swap(
-            &to, // user
-            &in_idx,
-            &out_idx,
-            //todo: convert all our usage of i128 as amounts to u128 for safety.
-            &amt_in, 
-            &min_out,
-        );
+        let (chain, _) = chain_in(&e, &path, amt_in)?;
+        let router_addr = get_amm(&e)?;
+        let router = protocol::AquaRouterClient::new(&e, &router_addr);
+        let (token_in, token_out) = (path.get_unchecked(0), path.get_unchecked(path.len() - 1));
+        adapter_base::take(&e, &token_in, &to, amt_in as i128).ok_or(AdapterError::InsufficientBalance)?;
+        adapter_base::authorize_transfer(&e, &token_in, &router_addr, amt_in as i128);
+        let amt_out = router
+            .try_swap_chained(&e.current_contract_address(), &chain, &token_in, &amt_in, &min_out)
+            .map_err(pool_err)?
+            .map_err(|_| AdapterError::ExternalFailure)?;
         let amt_out_i128 = amt_out as i128;
+        adapter_base::pay(&e, &token_out, &to, amt_out_i128);
         event::swap(
             &e,
             event::SwapEvent {
@@ -173,36 +999,25 @@ impl AdapterTrait for AquaAdapter {
         let out = out as u128;
         let max_in = max_in as u128;
         to.require_auth();
-        if !is_init(&e) {
-            return Err(AdapterError::ExternalFailure);
+        check_swap(&e, &path, deadline)?;
+        if path.len() == 2 {
+            // Default pool: whichever registered pool needs the least input
+            let (pool_info, _) = best_pool_out(&e, &path, out)?;
+            return swap_out_pool(&e, &pool_info, out, max_in, path, to);
         }
-        if e.ledger().timestamp() > deadline {
-            return Err(AdapterError::ExternalFailure);
-        }
-        if path.len() != 2 {
-            return Err(AdapterError::MultipathUnsupported);
-        }
-        let pool_info = get_pool_for_tokens(&e, &path).ok_or(AdapterError::UnsupportedPair)?;
-        let pool = protocol::AquaPoolClient::new(&e, &pool_info.pool_address);
-        let tokens = pool.get_tokens();
-        let in_token = path.get(0).unwrap();
-        let in_idx = if tokens.get(0).unwrap() == in_token {
-            0
-        } else {
-            1
-        };
-        let out_idx = if in_idx == 0 { 1 } else { 0 };
-
-        let amt_in = pool.swap_strict_receive(
-            &to,
-            //todo: convert all our usage of i128 as amounts to u128 for safety.
-            &in_idx,
-            &out_idx,
-            &out,
-            &max_in,
-        );
-
+        let (chain, _) = chain_out(&e, &path, out)?;
+        let router_addr = get_amm(&e)?;
+        let router = protocol::AquaRouterClient::new(&e, &router_addr);
+        let (token_in, token_out) = (path.get_unchecked(0), path.get_unchecked(path.len() - 1));
+        adapter_base::take(&e, &token_in, &to, max_in as i128).ok_or(AdapterError::InsufficientBalance)?;
+        adapter_base::authorize_transfer(&e, &token_in, &router_addr, max_in as i128);
+        let amt_in = router
+            .try_swap_chained_strict_receive(&e.current_contract_address(), &chain, &token_in, &out, &max_in)
+            .map_err(pool_err)?
+            .map_err(|_| AdapterError::ExternalFailure)?;
         let amt_in_i128 = amt_in as i128;
+        adapter_base::pay(&e, &token_out, &to, out as i128);
+        adapter_base::pay(&e, &token_in, &to, max_in as i128 - amt_in_i128);
         event::swap(
             &e,
             event::SwapEvent {
@@ -215,7 +1030,50 @@ impl AdapterTrait for AquaAdapter {
         bump(&e);
         Ok(amt_in_i128)
     }
- 
+
+    fn swap_exact_in_on(
+        e: Env,
+        pool: Address,
+        amt_in: i128,
+        min_out: i128,
+        path: Vec<Address>,
+        to: Address,
+        deadline: u64,
+    ) -> Result<i128, AdapterError> {
+        if amt_in < 0 || min_out < 0 {
+            return Err(AdapterError::InvalidAmount);
+        };
+        to.require_auth();
+        check_swap(&e, &path, deadline)?;
+        if path.len() != 2 {
+            return Err(AdapterError::MultipathUnsupported);
+        }
+        let pool_info = pool_at(&e, &path, &pool)?;
+        swap_in_pool(&e, &pool_info, amt_in as u128, min_out as u128, path, to)
+    }
+
+    fn swap_exact_out_on(
+        e: Env,
+        pool: Address,
+        out: i128,
+        max_in: i128,
+        path: Vec<Address>,
+        to: Address,
+        deadline: u64,
+    ) -> Result<i128, AdapterError> {
+        if out < 0 || max_in < 0 {
+            return Err(AdapterError::InvalidAmount);
+        };
+        to.require_auth();
+        check_swap(&e, &path, deadline)?;
+        if path.len() != 2 {
+            return Err(AdapterError::MultipathUnsupported);
+        }
+        let pool_info = pool_at(&e, &path, &pool)?;
+        swap_out_pool(&e, &pool_info, out as u128, max_in as u128, path, to)
+    }
+
+    #[allow(clippy::too_many_arguments)]
     fn add_liquidity(
         e: Env,
         token_a: Address,
@@ -231,7 +1089,6 @@ impl AdapterTrait for AquaAdapter {
         if amt_a < 0 || amt_b < 0 || amt_a_min < 0 || amt_b_min < 0 {
             return Err(AdapterError::InvalidAmount);
         }
-        log!(&e, "testing the logging");
         let amt_a = amt_a as u128;
         let amt_b = amt_b as u128;
         let amt_a_min = amt_a_min as u128;
@@ -239,56 +1096,13 @@ impl AdapterTrait for AquaAdapter {
         if amt_a == 0 || amt_b == 0 {
             return Err(AdapterError::InvalidAmount);
         }
-        
-        if !is_init(&e) {
-            return Err(AdapterError::ExternalFailure);
-        }
-        if e.ledger().timestamp() > deadline {
-            return Err(AdapterError::ExternalFailure);
-        }
+        check_live(&e, deadline)?;
         let tokens = Vec::from_array(&e, [token_a.clone(), token_b.clone()]);
-        let pool_info = get_pool_for_tokens(&e, &tokens).ok_or(AdapterError::UnsupportedPair)?;
-        let pool = protocol::AquaPoolClient::new(&e, &pool_info.pool_address);
-        log!(&e, "Found pool for tokens: {:?}", pool_info.pool_address);
-
-        let reserves = pool.get_reserves();
-        log!(&e, "Calling get_reserves: reserves={:?}", reserves);
-        let reserve_a = reserves.get(0).unwrap();
-        let reserve_b = reserves.get(1).unwrap();
-        
-        if reserve_a == 0 && reserve_b == 0 {
-            return Err(AdapterError::ExternalFailure); // Cannot add liquidity to an empty pool yet.
-        }
-       let shares = pool.get_total_shares();
-       log!(&e, "Calling get_total_shares: shares={}", shares);
-        let (amount_a, amount_b) =
-            get_deposit_amounts(&e, amt_a, amt_b, amt_a_min, amt_b_min, reserve_a, reserve_b);
-        log!(&e, "get_deposit_amounts returned: amount_a={:?}, amount_b={:?}", amount_a, amount_b);
-        let desired_amounts = Vec::from_array(&e, [amount_a, amount_b]);
-        let min_shares = get_shares(
-            &e,
-            amount_a,
-            amount_b,
-            reserve_a,
-            reserve_b,
-            shares,
-        );
-        log!(&e, "Calling get_deposit_amounts: amt_a={}, amt_b={}, amt_a_min={}, amt_b_min={}, reserve_a={}, reserve_b={}", amt_a, amt_b, amt_a_min, amt_b_min, reserve_a, reserve_b);
-        let (amount_a, amount_b) = get_deposit_amounts(&e, amt_a, amt_b, amt_a_min, amt_b_min, reserve_a, reserve_b);
-        log!(&e, "get_deposit_amounts returned: amount_a={}, amount_b={}", amount_a, amount_b);
-
-        log!(&e, "Calling get_shares: amount_a={}, amount_b={}, reserve_a={}, reserve_b={}, shares={}", amount_a, amount_b, reserve_a, reserve_b, shares);
-        
-        log!(&e, "get_shares returned: min_shares={}", min_shares);
-        if min_shares == 0 {
-            return Err(AdapterError::InvalidAmount);
-        }
-        let (amounts, shares) = pool.deposit(&to, &desired_amounts, &min_shares);
-        let amount_a_i128 = amounts.get(0).unwrap() as i128;
-        let amount_b_i128 = amounts.get(1).unwrap() as i128;
-        let shares_i128 = shares as i128;
-        bump(&e);
-        Ok((amount_a_i128, amount_b_i128, shares_i128))
+        // Default pool: the first one registered for the pair
+        let pool_info = get_pools_for_tokens(&e, &tokens)
+            .first()
+            .ok_or(AdapterError::PoolNotFound)?;
+        add_liquidity_to_pool(&e, &pool_info, token_a, token_b, amt_a, amt_b, amt_a_min, amt_b_min, &to)
     }
 
     fn remove_liquidity(
@@ -307,17 +1121,17 @@ impl AdapterTrait for AquaAdapter {
         let amt_a_min = amt_a_min as u128;
         let amt_b_min = amt_b_min as u128;
         to.require_auth();
-        if !is_init(&e) {
-            return Err(AdapterError::ExternalFailure);
-        }
-        if e.ledger().timestamp() > deadline {
-            return Err(AdapterError::ExternalFailure);
-        }
+        check_live(&e, deadline)?;
         log!(&e, "Attempting to remove liquidity from Aqua pool with lp token {:?}", lp);
         // Find pool by LP token address
-        let pool_info = storage::get_pool_by_lp_token(&e, &lp).ok_or(AdapterError::UnsupportedPair)?;
+        let pool_info = storage::get_pool_by_lp_token(&e, &lp).ok_or(AdapterError::PoolNotFound)?;
         log!(&e, "Found pool for LP token: {:?}", pool_info);
         let pool = protocol::AquaPoolClient::new(&e, &pool_info.pool_address);
+        let tokens = pool.get_tokens();
+        if tokens.len() != 2 {
+            // Multi-token pools go through `withdraw`
+            return Err(AdapterError::InvalidArgument);
+        }
         let lp_token_client = TokenClient::new(&e, &lp);
 
         let curr_lp = lp_token_client.balance(&to);
@@ -326,13 +1140,22 @@ impl AdapterTrait for AquaAdapter {
         }
         let minimums = Vec::from_array(&e, [amt_a_min, amt_b_min]);
 
-        let amounts = pool.withdraw(
-            &to,
+        // Withdraw from the adapter's balance, as for swaps, so `to` only
+        // authorizes the share transfer here; the pool burns the shares
+        let me = e.current_contract_address();
+        adapter_base::take(&e, &lp, &to, lp_amt as i128).ok_or(AdapterError::InsufficientBalance)?;
+        adapter_base::authorize_call(&e, &lp, "burn", (me.clone(), lp_amt as i128).into_val(&e));
+        let amounts = pool.try_withdraw(
+            &me,
             &lp_amt,
             &minimums,
-        );
+        )
+            .map_err(pool_err)?
+            .map_err(|_| AdapterError::ExternalFailure)?;
         let amt_a = amounts.get(0).unwrap() as i128;
         let amt_b = amounts.get(1).unwrap() as i128;
+        adapter_base::pay(&e, &tokens.get_unchecked(0), &to, amt_a);
+        adapter_base::pay(&e, &tokens.get_unchecked(1), &to, amt_b);
         bump(&e);
         Ok((amt_a, amt_b))
     }
@@ -344,20 +1167,15 @@ impl AdapterTrait for AquaAdapter {
         if amount_in <= 0 {
             return Err(AdapterError::InvalidAmount);
         }
+        check_registered(&e, &pool_address)?;
+        // Ask the pool itself so fees and the stableswap invariant are included
         let pool_client = protocol::AquaPoolClient::new(&e, &pool_address);
-        let reserves = pool_client.get_reserves();
-        let pool_tokens = pool_client.get_tokens();
-        let (reserve_in, reserve_out) = if pool_tokens.get(0).unwrap() == token_in {
-            (reserves.get(0).unwrap(), reserves.get(1).unwrap())
-        } else {
-            (reserves.get(1).unwrap(), reserves.get(0).unwrap())
-        };
-        if reserve_in == 0 || reserve_out == 0 {
-            return Ok(0); // No liquidity
+        let path = Vec::from_array(&e, [token_in, token_out]);
+        let (in_idx, out_idx) = token_indices(&pool_client, &path)?;
+        match pool_client.try_estimate_swap(&in_idx, &out_idx, &(amount_in as u128)) {
+            Ok(Ok(amount_out)) => Ok(amount_out as i128),
+            _ => Err(AdapterError::InsufficientLiquidity),
         }
-        // Standard constant product formula
-        let amount_out = (amount_in as u128 * reserve_out) / (reserve_in + amount_in as u128);
-        Ok(amount_out as i128)
     }
 
     fn quote_out(e: Env, pool_address: Address, amount_out: i128, token_in: Address, token_out: Address) -> Result<i128, AdapterError> {
@@ -367,22 +1185,89 @@ impl AdapterTrait for AquaAdapter {
         if amount_out <= 0 {
             return Err(AdapterError::InvalidAmount);
         }
+        check_registered(&e, &pool_address)?;
         let pool_client = protocol::AquaPoolClient::new(&e, &pool_address);
-        let reserves = pool_client.get_reserves();
-        let pool_tokens = pool_client.get_tokens();
-        let (reserve_in, reserve_out) = if pool_tokens.get(0).unwrap() == token_in {
-            (reserves.get(0).unwrap(), reserves.get(1).unwrap())
-        } else {
-            (reserves.get(1).unwrap(), reserves.get(0).unwrap())
-        };
+        let path = Vec::from_array(&e, [token_in, token_out]);
+        let (in_idx, out_idx) = token_indices(&pool_client, &path)?;
+        match pool_client.try_estimate_swap_strict_receive(&in_idx, &out_idx, &(amount_out as u128)) {
+            Ok(Ok(amount_in)) => Ok(amount_in as i128),
+            _ => Err(AdapterError::InsufficientLiquidity),
+        }
+    }
 
-        if reserve_in == 0 || reserve_out == 0 || reserve_out <= amount_out as u128 {
+    fn get_spot_price(e: Env, pool: Address, token_in: Address, token_out: Address) -> Result<i128, AdapterError> {
+        if !is_init(&e) {
+            return Err(AdapterError::NotInitialized);
+        }
+        check_registered(&e, &pool)?;
+        let pool_client = protocol::AquaPoolClient::new(&e, &pool);
+        let path = Vec::from_array(&e, [token_in, token_out]);
+        let (in_idx, out_idx) = token_indices(&pool_client, &path)?;
+        let reserves = match pool_client.try_get_reserves() {
+            Ok(Ok(reserves)) => reserves,
+            _ => return Err(AdapterError::ExternalFailure),
+        };
+        let reserve_in = reserves.get(in_idx).unwrap_or(0) as i128;
+        let reserve_out = reserves.get(out_idx).unwrap_or(0) as i128;
+        if reserve_in == 0 || reserve_out == 0 {
             return Err(AdapterError::InsufficientLiquidity);
         }
+        if !is_stable(&pool_client) {
+            return Ok(reserve_out * PRICE_SCALE / reserve_in);
+        }
+        // Stableswap has no closed form; simulate 0.01% of the input reserve
+        // and add the fee back
+        let probe = (reserve_in / 10_000).max(1);
+        let out = match pool_client.try_estimate_swap(&in_idx, &out_idx, &(probe as u128)) {
+            Ok(Ok(out)) => out as i128,
+            _ => return Err(AdapterError::InsufficientLiquidity),
+        };
+        let fee_bps = pool_client.get_fee_fraction() as i128;
+        Ok(out * PRICE_SCALE * 10_000 / (probe * (10_000 - fee_bps)))
+    }
+
+    /* ---------- discovery ---------- */
+    fn get_pool_info(e: Env, pool: Address) -> Result<PoolInfo, AdapterError> {
+        if !is_init(&e) {
+            return Err(AdapterError::NotInitialized);
+        }
+        let client = protocol::AquaPoolClient::new(&e, &pool);
+        let tokens = match client.try_get_tokens() {
+            Ok(Ok(tokens)) => tokens,
+            _ => return Err(AdapterError::PoolNotFound),
+        };
+        let mut reserves = Vec::new(&e);
+        for r in client.get_reserves().iter() {
+            reserves.push_back(r as i128);
+        }
+        let kind = if is_stable(&client) { PoolKind::Stable } else { PoolKind::ConstantProduct };
+        Ok(PoolInfo {
+            lp_token: client.share_id(),
+            pool,
+            tokens,
+            reserves,
+            weights: Vec::new(&e),
+            // Aqua fee fractions are already in basis points
+            fee_bps: client.get_fee_fraction(),
+            kind,
+            lp_supply: client.get_total_shares() as i128,
+        })
+    }
 
-        // Standard constant product formula for required input
-        let amount_in = (reserve_in * amount_out as u128) / (reserve_out - amount_out as u128) + 1;
+    fn supports(_e: Env, capability: Capability) -> bool {
+        matches!(
+            capability,
+            Capability::ExactOut | Capability::MultiHop | Capability::SingleSidedLp | Capability::Rewards
+        )
+    }
 
-        Ok(amount_in as i128)
+    fn list_pools(e: Env, start: u32, limit: u32) -> Vec<Address> {
+        let mut pools = Vec::new(&e);
+        for info in get_all_pools(&e).iter() {
+            if !pools.contains(&info.pool_address) {
+                pools.push_back(info.pool_address);
+            }
+        }
+        adapter_base::page(&pools, start, limit)
     }
 }
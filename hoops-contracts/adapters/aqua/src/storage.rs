@@ -1,29 +1,17 @@
-use soroban_sdk::{contracttype, Address, Env, Vec, Symbol, unwrap::UnwrapOptimized,symbol_short};
+use soroban_sdk::{contracttype, Address, BytesN, Env, Vec};
 use hoops_adapter_interface::AdapterError;
 
+pub use hoops_common::adapter_base::bump;
 
 #[derive(Clone)]
 #[contracttype]
-enum Key { Amm, Init, Pool(PoolKey), Lp(Address) }
-
-const DAY_LEDGER: u32 = 17_280;
-const BUMP: u32 = 60 * DAY_LEDGER;
-pub const KEY_CORE_CONFIG: Symbol = symbol_short!("CONFIG");
-
-#[contracttype]
-#[derive(Clone, Debug, Eq, PartialEq)]
-pub struct CoreConfig {
-    pub admin: Address,
-    pub usdc: Address,
-    pub next: u32,
-    pub ttl_thresh: u32,
-    pub ttl_bump: u32,
-}
+enum Key { Amm, Init, Pool(PoolKey), Pools(Vec<Address>), Lp(Address), RewardToken, Sets }
 
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct PoolKey {
     pub tokens: Vec<Address>,
+    pub pool_index: BytesN<32>,
 }
 
 #[contracttype]
@@ -31,59 +19,147 @@ pub struct PoolKey {
 pub struct AquaPoolInfo {
     pub pool_address: Address,
     pub lp_token_address: Address,
-}
-
-pub fn set_core_config(e: &Env, config: &CoreConfig) {
-    e.storage().instance().set(&KEY_CORE_CONFIG, config);
-}
-pub fn get_core_config(e: &Env) -> CoreConfig {
-    e.storage().instance().get(&KEY_CORE_CONFIG).unwrap_optimized()
-}
-#[allow(dead_code)]
-pub fn update_core_config<F: FnOnce(&mut CoreConfig)>(e: &Env, f: F) {
-    let mut config = get_core_config(e);
-    f(&mut config);
-    set_core_config(e, &config);
+    /// Aqua router pool index hash (fee tier / pool type).
+    pub pool_index: BytesN<32>,
 }
 
 pub fn set_amm(e:&Env, a:Address){ e.storage().instance().set(&Key::Amm,&a); }
 pub fn get_amm(e:&Env)->Result<Address,AdapterError>{
-    e.storage().instance().get(&Key::Amm).ok_or(AdapterError::ExternalFailure)
+    e.storage().instance().get(&Key::Amm).ok_or(AdapterError::NotInitialized)
 }
 pub fn mark_init(e:&Env){ e.storage().instance().set(&Key::Init,&true); }
+pub fn set_reward_token(e:&Env, t:&Address){ e.storage().instance().set(&Key::RewardToken,t); }
+pub fn get_reward_token(e:&Env)->Option<Address>{ e.storage().instance().get(&Key::RewardToken) }
 
 pub fn is_init(e:&Env)->bool{ e.storage().instance().has(&Key::Init) }
 
-pub fn bump(e:&Env){
-    e.storage().instance().extend_ttl(BUMP-DAY_LEDGER, BUMP);
+// Sorted copy of `tokens` (pairs, or the 3-4 token sets of stableswap pools)
+pub fn sort_addresses(e: &Env, tokens: &Vec<Address>) -> Vec<Address> {
+    let mut sorted: Vec<Address> = Vec::new(e);
+    for t in tokens.iter() {
+        let pos = sorted.iter().position(|s| s > t).unwrap_or(sorted.len() as usize);
+        sorted.insert(pos as u32, t);
+    }
+    sorted
 }
 
-fn sort_addresses(e: &Env, tokens: &Vec<Address>) -> Vec<Address> {
-    let mut arr: [Address; 2] = [tokens.get_unchecked(0), tokens.get_unchecked(1)];
-    if arr[0] > arr[1] {
-        arr.swap(0, 1);
-    }
-    Vec::from_array(e, arr)
+fn pool_key(e: &Env, tokens: &Vec<Address>, pool_index: &BytesN<32>) -> Key {
+    Key::Pool(PoolKey { tokens: sort_addresses(e, tokens), pool_index: pool_index.clone() })
+}
+
+// Pool indices registered for a pair, in registration order
+pub fn get_pool_indices(e: &Env, tokens: &Vec<Address>) -> Vec<BytesN<32>> {
+    e.storage()
+        .instance()
+        .get(&Key::Pools(sort_addresses(e, tokens)))
+        .unwrap_or(Vec::new(e))
 }
 
-// Store a pool info for a given set of tokens (sorted for canonicalization)
-pub fn set_pool_for_tokens(e: &Env, tokens: &Vec<Address>, info: &AquaPoolInfo) {
-    let tokens_sorted = sort_addresses(e, tokens);
-    let key = Key::Pool(PoolKey { tokens: tokens_sorted });
+// Token sets (sorted) with at least one registered pool
+fn get_token_sets(e: &Env) -> Vec<Vec<Address>> {
+    e.storage().instance().get(&Key::Sets).unwrap_or(Vec::new(e))
+}
+
+// Store a pool info for a given set of tokens and pool index
+pub fn set_pool(e: &Env, tokens: &Vec<Address>, info: &AquaPoolInfo) {
+    let key = pool_key(e, tokens, &info.pool_index);
+    // Drop the reverse mapping of the pool being replaced, if any
+    if let Some(prev) = e.storage().instance().get::<_, AquaPoolInfo>(&key) {
+        e.storage().instance().remove(&Key::Lp(prev.lp_token_address));
+    }
     e.storage().instance().set(&key, &info.clone());
+    let mut indices = get_pool_indices(e, tokens);
+    if !indices.contains(&info.pool_index) {
+        if indices.is_empty() {
+            let mut sets = get_token_sets(e);
+            sets.push_back(sort_addresses(e, tokens));
+            e.storage().instance().set(&Key::Sets, &sets);
+        }
+        indices.push_back(info.pool_index.clone());
+        e.storage().instance().set(&Key::Pools(sort_addresses(e, tokens)), &indices);
+    }
     // Also store reverse mapping for LP token
     e.storage().instance().set(&Key::Lp(info.lp_token_address.clone()), &info.clone());
 }
 
-// Get a pool info for a given set of tokens (sorted for canonicalization)
-pub fn get_pool_for_tokens(e: &Env, tokens: &Vec<Address>) -> Option<AquaPoolInfo> {
-    let tokens_sorted = sort_addresses(e, tokens);
-    let key = Key::Pool(PoolKey { tokens: tokens_sorted });
-    e.storage().instance().get(&key)
+// Registered sets strictly larger than `tokens` that hold all of them, e.g.
+// the 3-4 token stableswap pools a pair can also trade on
+fn supersets(e: &Env, tokens: &Vec<Address>) -> Vec<Vec<Address>> {
+    let mut sets = Vec::new(e);
+    for set in get_token_sets(e).iter() {
+        if set.len() > tokens.len() && tokens.iter().all(|t| set.contains(&t)) {
+            sets.push_back(set);
+        }
+    }
+    sets
+}
+
+// Pool info for `tokens` and a pool index, registered for that set or for a
+// larger set containing it
+pub fn get_pool(e: &Env, tokens: &Vec<Address>, pool_index: &BytesN<32>) -> Option<AquaPoolInfo> {
+    e.storage().instance().get(&pool_key(e, tokens, pool_index)).or_else(|| {
+        supersets(e, tokens)
+            .iter()
+            .find_map(|set| e.storage().instance().get(&pool_key(e, &set, pool_index)))
+    })
+}
+
+// Pools registered for exactly this set, in registration order
+fn get_set_pools(e: &Env, tokens: &Vec<Address>) -> Vec<AquaPoolInfo> {
+    let mut pools = Vec::new(e);
+    for pool_index in get_pool_indices(e, tokens).iter() {
+        if let Some(info) = e.storage().instance().get(&pool_key(e, tokens, &pool_index)) {
+            pools.push_back(info);
+        }
+    }
+    pools
+}
+
+// All pools holding `tokens`: those registered for the set itself in
+// registration order, then those of larger sets containing it
+pub fn get_pools_for_tokens(e: &Env, tokens: &Vec<Address>) -> Vec<AquaPoolInfo> {
+    let mut pools = get_set_pools(e, tokens);
+    for set in supersets(e, tokens).iter() {
+        for info in get_set_pools(e, &set).iter() {
+            pools.push_back(info);
+        }
+    }
+    pools
+}
+
+// Remove the pool info (and its LP reverse mapping) for a pair and pool index
+pub fn remove_pool(e: &Env, tokens: &Vec<Address>, pool_index: &BytesN<32>) -> Option<AquaPoolInfo> {
+    let key = pool_key(e, tokens, pool_index);
+    let info: AquaPoolInfo = e.storage().instance().get(&key)?;
+    e.storage().instance().remove(&key);
+    e.storage().instance().remove(&Key::Lp(info.lp_token_address.clone()));
+    let mut indices = get_pool_indices(e, tokens);
+    if let Some(i) = indices.first_index_of(pool_index) {
+        indices.remove(i);
+    }
+    e.storage().instance().set(&Key::Pools(sort_addresses(e, tokens)), &indices);
+    if indices.is_empty() {
+        let mut sets = get_token_sets(e);
+        if let Some(i) = sets.first_index_of(sort_addresses(e, tokens)) {
+            sets.remove(i);
+            e.storage().instance().set(&Key::Sets, &sets);
+        }
+    }
+    Some(info)
+}
+
+// Every registered pool, across all token sets
+pub fn get_all_pools(e: &Env) -> Vec<AquaPoolInfo> {
+    let mut pools = Vec::new(e);
+    for set in get_token_sets(e).iter() {
+        for info in get_set_pools(e, &set).iter() {
+            pools.push_back(info);
+        }
+    }
+    pools
 }
 
 // Get a pool info by LP token address
 pub fn get_pool_by_lp_token(e: &Env, lp_token: &Address) -> Option<AquaPoolInfo> {
     e.storage().instance().get(&Key::Lp(lp_token.clone()))
 }
-
@@ -19,8 +19,18 @@ pub struct AddLpEvent { pub token_a: Address, pub token_b: Address, pub lp: Addr
 
 pub struct RemLpEvent { pub lp: Address, pub to: Address }
 
+#[contracttype]
+#[derive(Clone)]
+pub struct PoolSetEvent { pub tokens: Vec<Address>, pub pool: Address, pub by: Address }
+
+#[contracttype]
+#[derive(Clone)]
+pub struct PoolRemEvent { pub tokens: Vec<Address>, pub pool: Address, pub by: Address }
+
 pub(crate) fn init(e: &Env, amm: Address) { e.events().publish(("comet",symbol_short!("init")), InitEvent{amm}); }
 
 pub(crate) fn swap(e:&Env, ev:SwapEvent){ e.events().publish(("comet",symbol_short!("swap")), ev); }
 pub(crate) fn add_lp(e:&Env, ev:AddLpEvent){ e.events().publish(("comet",symbol_short!("addlp")), ev); }
 pub(crate) fn rem_lp(e:&Env, ev:RemLpEvent){ e.events().publish(("comet",symbol_short!("remlp")), ev); }
+pub(crate) fn pool_set(e:&Env, ev:PoolSetEvent){ e.events().publish(("comet",symbol_short!("pool_set")), ev); }
+pub(crate) fn pool_rem(e:&Env, ev:PoolRemEvent){ e.events().publish(("comet",symbol_short!("pool_rem")), ev); }
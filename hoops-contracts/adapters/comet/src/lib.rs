@@ -2,15 +2,17 @@
 
 mod storage;
 mod event;
+mod math;
 mod protocol;
 
-use soroban_fixed_point_math::{FixedPoint, SorobanFixedPoint};
+use soroban_fixed_point_math::SorobanFixedPoint;
 use storage::*;
 #[allow(unused_imports)]
 use event::*;
-use protocol::CometPoolClient;
-use hoops_adapter_interface::{AdapterTrait, AdapterError};
-use soroban_sdk::{contract, contractimpl, token, Address, BytesN, Env, Vec, I256, unwrap::UnwrapOptimized};
+use protocol::{CometPoolClient, Error as CometError};
+use hoops_adapter_interface::{AdapterTrait, AdapterError, Capability, PoolInfo, PoolKind, PRICE_SCALE};
+use hoops_common::adapter_base;
+use soroban_sdk::{contract, contractimpl, token, Address, BytesN, Env, IntoVal, InvokeError, Vec};
 
 const PROTOCOL_ID: i128 = 1;
 pub const STROOP: i128 = 10i128.pow(7);
@@ -21,22 +23,572 @@ pub const ONE_E9: i128 = 1_000_000_000;
 #[contract]
 pub struct CometAdapter;
 
+hoops_common::adapter_admin!(CometAdapter, AdapterError);
+
 pub trait CometAdapterTrait {
-    /// Register a pool for a set of tokens (sorted for canonicalization)
-    fn set_pool_for_tokens(e: Env, tokens: Vec<Address>, pool: Address);
+    /// Register a pool for a set of tokens (sorted for canonicalization).
+    /// The set can be a pair or the pool's full token list. `caller` must be
+    /// the adapter admin or the registered router.
+    fn set_pool_for_tokens(e: Env, caller: Address, tokens: Vec<Address>, pool: Address) -> Result<(), AdapterError>;
+    /// Remove the pool registered for a set of tokens (admin or router only)
+    fn remove_pool_for_tokens(e: Env, caller: Address, tokens: Vec<Address>) -> Result<(), AdapterError>;
     /// Get a pool for a set of tokens (sorted for canonicalization)
     fn get_pool_for_tokens(e: Env, tokens: Vec<Address>) -> Option<Address>;
+
+    /* ---------- price limits ---------- */
+    /// Set the tolerance (bps) used for the `max_price` of the plain
+    /// `AdapterTrait` swaps and the LP floor of single-sided
+    /// `add_liquidity`. Admin only.
+    fn set_price_tolerance(e: Env, bps: u32) -> Result<(), AdapterError>;
+    fn get_price_tolerance(e: Env) -> u32;
+    /// `max_price` for swapping `amount_in` of `token_in` on `pool`: the
+    /// spot price the quoted trade leaves behind, plus `tolerance_bps`. For an
+    /// exact-out swap pass the input from `quote_out`.
+    fn max_price_for(
+        e: Env,
+        pool: Address,
+        token_in: Address,
+        token_out: Address,
+        amount_in: i128,
+        tolerance_bps: u32,
+    ) -> Result<i128, AdapterError>;
+    /// `swap_exact_in` with an explicit Comet `max_price`.
+    fn swap_exact_in_limit(
+        e: Env,
+        amount_in: i128,
+        min_out: i128,
+        max_price: i128,
+        path: Vec<Address>,
+        to: Address,
+        deadline: u64,
+    ) -> Result<i128, AdapterError>;
+    /// `swap_exact_out` with an explicit Comet `max_price`.
+    fn swap_exact_out_limit(
+        e: Env,
+        amount_out: i128,
+        max_in: i128,
+        max_price: i128,
+        path: Vec<Address>,
+        to: Address,
+        deadline: u64,
+    ) -> Result<i128, AdapterError>;
+
+    /* ---------- multi-token / single-sided liquidity ---------- */
+    /// Proportional join of an N-token pool. `max_amounts` follows the pool's
+    /// `get_tokens()` order. Returns the amounts taken (same order) and the
+    /// LP minted.
+    fn deposit(
+        e: Env,
+        pool: Address,
+        max_amounts: Vec<i128>,
+        to: Address,
+        deadline: u64,
+    ) -> Result<(Vec<i128>, i128), AdapterError>;
+    /// Single-asset join: deposit `amount_in` of `token_in` for at least
+    /// `min_lp_out` LP tokens.
+    fn deposit_single(
+        e: Env,
+        pool: Address,
+        token_in: Address,
+        amount_in: i128,
+        min_lp_out: i128,
+        to: Address,
+        deadline: u64,
+    ) -> Result<i128, AdapterError>;
+    /// Proportional exit of an N-token pool. `min_amounts` and the result
+    /// follow the pool's `get_tokens()` order.
+    fn withdraw(
+        e: Env,
+        pool: Address,
+        lp_amount: i128,
+        min_amounts: Vec<i128>,
+        to: Address,
+        deadline: u64,
+    ) -> Result<Vec<i128>, AdapterError>;
+    /// Single-asset exit: receive exactly `amount_out` of `token_out`,
+    /// burning at most `max_lp_in`. Returns the LP burned.
+    fn withdraw_single(
+        e: Env,
+        pool: Address,
+        token_out: Address,
+        amount_out: i128,
+        max_lp_in: i128,
+        to: Address,
+        deadline: u64,
+    ) -> Result<i128, AdapterError>;
+}
+
+fn check_live(e: &Env, deadline: u64) -> Result<(), AdapterError> {
+    if !is_init(e) { return Err(AdapterError::NotInitialized); }
+    if e.ledger().timestamp() > deadline {
+        return Err(AdapterError::DeadlinePassed);
+    }
+    Ok(())
+}
+
+/// Liquidity entry points take a pool address from the caller; only pools
+/// registered with the adapter are accepted.
+fn check_registered(e: &Env, pool: &Address) -> Result<(), AdapterError> {
+    if !get_all_pools(e).contains(pool) {
+        return Err(AdapterError::PoolNotFound);
+    }
+    Ok(())
+}
+
+/// Translate a Comet pool error into an `AdapterError`. Comet rejects trades
+/// and joins that breach a caller limit with one of the limit errors; those
+/// surface as `MinAmountNotMet`. Unknown errors stay `ExternalFailure`.
+fn pool_err(err: Result<soroban_sdk::Error, InvokeError>) -> AdapterError {
+    const MAP: [(CometError, AdapterError); 11] = [
+        (CometError::ErrLimitIn, AdapterError::MinAmountNotMet),
+        (CometError::ErrLimitOut, AdapterError::MinAmountNotMet),
+        (CometError::ErrLimitPrice, AdapterError::MinAmountNotMet),
+        (CometError::ErrBadLimitPrice, AdapterError::MinAmountNotMet),
+        (CometError::ErrMaxInRatio, AdapterError::MaxInRatio),
+        (CometError::ErrMaxOutRatio, AdapterError::MaxOutRatio),
+        (CometError::ErrMinBalance, AdapterError::InsufficientLiquidity),
+        (CometError::ErrInsufficientBalance, AdapterError::InsufficientBalance),
+        (CometError::ErrNotBound, AdapterError::UnsupportedPair),
+        (CometError::ErrTokenInvalid, AdapterError::UnsupportedPair),
+        (CometError::ErrNegativeOrZero, AdapterError::InvalidAmount),
+    ];
+    let Ok(code) = err else { return AdapterError::ExternalFailure };
+    MAP.into_iter()
+        .find(|(c, _)| code == (*c).into())
+        .map_or(AdapterError::ExternalFailure, |(_, a)| a)
+}
+
+/// Balances and normalized weights of `token_in` / `token_out` in `pool`, and
+/// the swap fee, scaled for `math`.
+fn pool_state(e: &Env, pool: &Address, token_in: &Address, token_out: &Address) -> Result<(i128, i128, i128, i128, i128), AdapterError> {
+    let client = CometPoolClient::new(e, pool);
+    let balance = |t: &Address| client.try_get_balance(t).map_err(pool_err)?.map_err(|_| AdapterError::ExternalFailure);
+    let weight = |t: &Address| client.try_get_normalized_weight(t).map_err(pool_err)?.map_err(|_| AdapterError::ExternalFailure);
+    let fee = client.try_get_swap_fee().map_err(pool_err)?.map_err(|_| AdapterError::ExternalFailure)?;
+    Ok((balance(token_in)?, weight(token_in)?, balance(token_out)?, weight(token_out)?, fee * STROOP_SCALAR))
+}
+
+/// Spot price the quoted trade of `amount_in` leaves behind, plus `bps`, in
+/// Comet's STROOP-scaled `token_in`-per-`token_out`.
+fn price_limit(e: &Env, pool: &Address, token_in: &Address, token_out: &Address, amount_in: i128, bps: u32) -> Result<i128, AdapterError> {
+    if amount_in <= 0 {
+        return Err(AdapterError::InvalidAmount);
+    }
+    let (balance_in, weight_in, balance_out, weight_out, fee) = pool_state(e, pool, token_in, token_out)?;
+    let amount_out = math::out_given_in(balance_in, weight_in, balance_out, weight_out, amount_in, fee)?;
+    let price = math::spot_price_after(balance_in, weight_in, balance_out, weight_out, amount_in, amount_out, fee)?;
+    // `math` works in 1e18; Comet prices are in STROOP
+    let price = (price + STROOP_SCALAR - 1) / STROOP_SCALAR;
+    Ok(price.fixed_mul_ceil(e, &(10_000 + bps as i128), &10_000))
+}
+
+/// Input the pool asks for `amount_out`, from the same math as `quote_out`.
+fn quote_in_for(e: &Env, pool: &Address, token_in: &Address, token_out: &Address, amount_out: i128) -> Result<i128, AdapterError> {
+    if amount_out <= 0 {
+        return Err(AdapterError::InvalidAmount);
+    }
+    let (balance_in, weight_in, balance_out, weight_out, fee) = pool_state(e, pool, token_in, token_out)?;
+    math::in_given_out(balance_in, weight_in, balance_out, weight_out, amount_out, fee)
+}
+
+fn single_hop(path: &Vec<Address>) -> Result<(), AdapterError> {
+    if path.len() != 2 {
+        return Err(if path.len() < 2 { AdapterError::InvalidPath } else { AdapterError::MultipathUnsupported });
+    }
+    Ok(())
+}
+
+/// Pool mapped to the pair of a two-token `path`.
+fn swap_pool(e: &Env, path: &Vec<Address>) -> Result<Address, AdapterError> {
+    single_hop(path)?;
+    get_pool_for_tokens(e, path).ok_or(AdapterError::PoolNotFound)
+}
+
+/// `pool` for a two-token `path`, after checking it is registered and holds
+/// both tokens. It need not be the pool mapped to the pair itself.
+fn swap_pool_on(e: &Env, pool: &Address, path: &Vec<Address>) -> Result<Address, AdapterError> {
+    single_hop(path)?;
+    check_registered(e, pool)?;
+    let tokens = CometPoolClient::new(e, pool).get_tokens();
+    if !path.iter().all(|t| tokens.contains(&t)) {
+        return Err(AdapterError::UnsupportedPair);
+    }
+    Ok(pool.clone())
+}
+
+/// Pre-authorizes the allowance a Comet pool grants itself from the adapter
+/// (`approve` up to `max_amount`, then `transfer_from`) to pull its input.
+fn authorize_pull(e: &Env, pool: &Address, token: &Address, max_amount: i128) {
+    // Comet rounds the expiration up to the next 100_000 ledgers
+    let ledger = (e.ledger().sequence() / 100_000 + 1) * 100_000;
+    let args = (e.current_contract_address(), pool.clone(), max_amount, ledger).into_val(e);
+    adapter_base::authorize_call(e, token, "approve", args);
+}
+
+/// Exact-in swap from the adapter's balance. The output is checked against
+/// `min_out` as a balance delta of the adapter, then paid to `to`.
+fn swap_in(
+    e: &Env,
+    pool: &Address,
+    amount_in: i128,
+    min_out: i128,
+    max_price: i128,
+    path: &Vec<Address>,
+    to: &Address,
+) -> Result<i128, AdapterError> {
+    let pool = CometPoolClient::new(e, pool);
+    let (token_in, token_out) = (path.get_unchecked(0), path.get_unchecked(1));
+    adapter_base::take(e, &token_in, to, amount_in).ok_or(AdapterError::InsufficientBalance)?;
+    let before = adapter_base::held(e, &token_out);
+    authorize_pull(e, &pool.address, &token_in, amount_in);
+    let me = e.current_contract_address();
+    match pool.try_swap_exact_amount_in(&token_in, &amount_in, &token_out, &min_out, &max_price, &me) {
+        Ok(Ok(_)) => {}
+        Ok(Err(_)) => return Err(AdapterError::ExternalFailure),
+        Err(err) => return Err(pool_err(err)),
+    }
+    let amt_out = adapter_base::held(e, &token_out) - before;
+    if amt_out < min_out {
+        return Err(AdapterError::MinAmountNotMet);
+    }
+    adapter_base::pay(e, &token_out, to, amt_out);
+    bump(e);
+    Ok(amt_out)
+}
+
+/// Exact-out swap. Takes `max_in` from `to`, pays the output and the unused
+/// input back to `to` and returns the input spent.
+fn swap_out(
+    e: &Env,
+    pool: &Address,
+    amount_out: i128,
+    max_in: i128,
+    max_price: i128,
+    path: &Vec<Address>,
+    to: &Address,
+) -> Result<i128, AdapterError> {
+    let pool = CometPoolClient::new(e, pool);
+    let (token_in, token_out) = (path.get_unchecked(0), path.get_unchecked(1));
+    adapter_base::take(e, &token_in, to, max_in).ok_or(AdapterError::InsufficientBalance)?;
+    let before_in = adapter_base::held(e, &token_in);
+    let before_out = adapter_base::held(e, &token_out);
+    authorize_pull(e, &pool.address, &token_in, max_in);
+    let me = e.current_contract_address();
+    match pool.try_swap_exact_amount_out(&token_in, &max_in, &token_out, &amount_out, &max_price, &me) {
+        Ok(Ok(_)) => {}
+        Ok(Err(_)) => return Err(AdapterError::ExternalFailure),
+        Err(err) => return Err(pool_err(err)),
+    }
+    let amt_in = before_in - adapter_base::held(e, &token_in);
+    let amt_out = adapter_base::held(e, &token_out) - before_out;
+    if amt_in > max_in || amt_out < amount_out {
+        return Err(AdapterError::MinAmountNotMet);
+    }
+    adapter_base::pay(e, &token_out, to, amt_out);
+    adapter_base::pay(e, &token_in, to, max_in - amt_in);
+    bump(e);
+    Ok(amt_in)
+}
+
+/// `swap_in` on `pool` with `max_price` from the quote plus the stored
+/// tolerance.
+fn swap_in_quoted(e: &Env, pool: &Address, amount_in: i128, min_out: i128, path: &Vec<Address>, to: &Address) -> Result<i128, AdapterError> {
+    let (token_in, token_out) = (path.get_unchecked(0), path.get_unchecked(1));
+    let max_price = price_limit(e, pool, &token_in, &token_out, amount_in, get_price_tolerance(e))?;
+    swap_in(e, pool, amount_in, min_out, max_price, path, to)
+}
+
+/// `swap_out` on `pool` with `max_price` for the quoted input plus the
+/// stored tolerance.
+fn swap_out_quoted(e: &Env, pool: &Address, amount_out: i128, max_in: i128, path: &Vec<Address>, to: &Address) -> Result<i128, AdapterError> {
+    let (token_in, token_out) = (path.get_unchecked(0), path.get_unchecked(1));
+    let quoted_in = quote_in_for(e, pool, &token_in, &token_out, amount_out)?;
+    let max_price = price_limit(e, pool, &token_in, &token_out, quoted_in, get_price_tolerance(e))?;
+    swap_out(e, pool, amount_out, max_in, max_price, path, to)
+}
+
+fn balances_of(e: &Env, tokens: &Vec<Address>, who: &Address) -> Vec<i128> {
+    let mut out = Vec::new(e);
+    for t in tokens.iter() {
+        out.push_back(token::Client::new(e, &t).balance(who));
+    }
+    out
+}
+
+/// LP a proportional join can mint for `max_amounts` (pool token order):
+/// `total_supply * min(amount_i / balance_i)`, computed as a mul-div per
+/// token so large balances cannot overflow.
+fn join_amount_out(e: &Env, pool: &CometPoolClient, tokens: &Vec<Address>, max_amounts: &Vec<i128>) -> i128 {
+    let total = pool.get_total_supply();
+    let mut lp_out: Option<i128> = None;
+    for (i, t) in tokens.iter().enumerate() {
+        let bal = pool.get_balance(&t);
+        let amt = max_amounts.get_unchecked(i as u32);
+        let out = if bal > 0 { total.fixed_mul_floor(e, &amt, &bal) } else { 0 };
+        lp_out = Some(lp_out.map_or(out, |m| m.min(out)));
+    }
+    lp_out.unwrap_or(0)
+}
+
+/// Proportional join; returns the amounts taken (pool order) and LP minted,
+/// both measured as balance deltas of `to`.
+fn join(e: &Env, pool_addr: &Address, max_amounts: &Vec<i128>, to: &Address) -> Result<(Vec<i128>, i128), AdapterError> {
+    let pool = CometPoolClient::new(e, pool_addr);
+    let tokens = pool.get_tokens();
+    if max_amounts.len() != tokens.len() {
+        return Err(AdapterError::InvalidArgument);
+    }
+    if max_amounts.iter().any(|a| a <= 0) {
+        return Err(AdapterError::InvalidAmount);
+    }
+    let pool_amount_out = join_amount_out(e, &pool, &tokens, max_amounts);
+    if pool_amount_out <= 0 {
+        return Err(AdapterError::InvalidAmount);
+    }
+    let before_lp = pool.balance(to);
+    let before = balances_of(e, &tokens, to);
+    pool.try_join_pool(&pool_amount_out, max_amounts, to)
+        .map_err(pool_err)?
+        .map_err(|_| AdapterError::ExternalFailure)?;
+    bump(e);
+    let after = balances_of(e, &tokens, to);
+    let mut taken = Vec::new(e);
+    for i in 0..tokens.len() {
+        taken.push_back(before.get_unchecked(i) - after.get_unchecked(i));
+    }
+    Ok((taken, pool.balance(to) - before_lp))
+}
+
+fn join_single(
+    e: &Env,
+    pool_addr: &Address,
+    token_in: &Address,
+    amount_in: i128,
+    min_lp_out: i128,
+    to: &Address,
+) -> Result<i128, AdapterError> {
+    let pool = CometPoolClient::new(e, pool_addr);
+    if !pool.get_tokens().contains(token_in) {
+        return Err(AdapterError::UnsupportedPair);
+    }
+    if amount_in <= 0 || min_lp_out < 0 {
+        return Err(AdapterError::InvalidAmount);
+    }
+    let before_lp = pool.balance(to);
+    pool.try_dep_tokn_amt_in_get_lp_tokns_out(token_in, &amount_in, &min_lp_out, to)
+        .map_err(pool_err)?
+        .map_err(|_| AdapterError::ExternalFailure)?;
+    bump(e);
+    let lp = pool.balance(to) - before_lp;
+    if lp < min_lp_out {
+        return Err(AdapterError::MinAmountNotMet);
+    }
+    Ok(lp)
+}
+
+/// LP floor for a single-sided join of `amount_in`: the quoted LP less the
+/// stored tolerance.
+fn single_lp_floor(e: &Env, pool_addr: &Address, token_in: &Address, amount_in: i128) -> Result<i128, AdapterError> {
+    let pool = CometPoolClient::new(e, pool_addr);
+    if !pool.get_tokens().contains(token_in) {
+        return Err(AdapterError::UnsupportedPair);
+    }
+    let balance = pool.try_get_balance(token_in).map_err(pool_err)?.map_err(|_| AdapterError::ExternalFailure)?;
+    let weight = pool.try_get_normalized_weight(token_in).map_err(pool_err)?.map_err(|_| AdapterError::ExternalFailure)?;
+    let fee = pool.try_get_swap_fee().map_err(pool_err)?.map_err(|_| AdapterError::ExternalFailure)?;
+    let supply = pool.try_get_total_supply().map_err(pool_err)?.map_err(|_| AdapterError::ExternalFailure)?;
+    let lp = math::lp_out_given_in(balance, weight * STROOP_SCALAR, supply, amount_in, fee * STROOP_SCALAR)?;
+    Ok(lp.fixed_mul_floor(e, &(10_000 - get_price_tolerance(e) as i128), &10_000))
+}
+
+/// Proportional exit; returns the amounts received (pool order).
+fn exit(e: &Env, pool_addr: &Address, lp_amount: i128, min_amounts: &Vec<i128>, to: &Address) -> Result<Vec<i128>, AdapterError> {
+    let pool = CometPoolClient::new(e, pool_addr);
+    let tokens = pool.get_tokens();
+    if min_amounts.len() != tokens.len() {
+        return Err(AdapterError::InvalidArgument);
+    }
+    if lp_amount <= 0 || min_amounts.iter().any(|a| a < 0) {
+        return Err(AdapterError::InvalidAmount);
+    }
+    let before = balances_of(e, &tokens, to);
+    pool.try_exit_pool(&lp_amount, min_amounts, to)
+        .map_err(pool_err)?
+        .map_err(|_| AdapterError::ExternalFailure)?;
+    bump(e);
+    let after = balances_of(e, &tokens, to);
+    let mut received = Vec::new(e);
+    for i in 0..tokens.len() {
+        let got = after.get_unchecked(i) - before.get_unchecked(i);
+        if got < min_amounts.get_unchecked(i) {
+            return Err(AdapterError::MinAmountNotMet);
+        }
+        received.push_back(got);
+    }
+    Ok(received)
 }
 #[contractimpl]
 impl CometAdapterTrait for CometAdapter {
     /// Register a pool for a set of tokens (sorted for canonicalization)
-    fn set_pool_for_tokens(e: Env, tokens: Vec<Address>, pool: Address) {
+    fn set_pool_for_tokens(e: Env, caller: Address, tokens: Vec<Address>, pool: Address) -> Result<(), AdapterError> {
+        if !adapter_base::require_admin_or_router(&e, &caller) {
+            return Err(AdapterError::NotAuthorized);
+        }
+        let pool_tokens = CometPoolClient::new(&e, &pool).get_tokens();
+        if !adapter_base::pool_has_token_set(&tokens, &pool_tokens) {
+            return Err(AdapterError::UnsupportedPair);
+        }
         set_pool_for_tokens(&e, &tokens, &pool);
+        pool_set(&e, PoolSetEvent { tokens, pool, by: caller });
+        bump(&e);
+        Ok(())
+    }
+    /// Remove the pool registered for a set of tokens
+    fn remove_pool_for_tokens(e: Env, caller: Address, tokens: Vec<Address>) -> Result<(), AdapterError> {
+        if !adapter_base::require_admin_or_router(&e, &caller) {
+            return Err(AdapterError::NotAuthorized);
+        }
+        if tokens.len() < 2 {
+            return Err(AdapterError::InvalidArgument);
+        }
+        let pool = remove_pool_for_tokens(&e, &tokens).ok_or(AdapterError::PoolNotFound)?;
+        pool_rem(&e, PoolRemEvent { tokens, pool, by: caller });
+        bump(&e);
+        Ok(())
     }
     /// Get a pool for a set of tokens (sorted for canonicalization)
     fn get_pool_for_tokens(e: Env, tokens: Vec<Address>) -> Option<Address> {
         get_pool_for_tokens(&e, &tokens)
     }
+
+    fn set_price_tolerance(e: Env, bps: u32) -> Result<(), AdapterError> {
+        adapter_base::require_admin(&e).ok_or(AdapterError::NotInitialized)?;
+        if bps >= 10_000 {
+            return Err(AdapterError::InvalidArgument);
+        }
+        set_price_tolerance(&e, bps);
+        bump(&e);
+        Ok(())
+    }
+
+    fn get_price_tolerance(e: Env) -> u32 {
+        get_price_tolerance(&e)
+    }
+
+    fn max_price_for(
+        e: Env,
+        pool: Address,
+        token_in: Address,
+        token_out: Address,
+        amount_in: i128,
+        tolerance_bps: u32,
+    ) -> Result<i128, AdapterError> {
+        if !is_init(&e) {
+            return Err(AdapterError::NotInitialized);
+        }
+        price_limit(&e, &pool, &token_in, &token_out, amount_in, tolerance_bps)
+    }
+
+    fn swap_exact_in_limit(
+        e: Env,
+        amount_in: i128,
+        min_out: i128,
+        max_price: i128,
+        path: Vec<Address>,
+        to: Address,
+        deadline: u64,
+    ) -> Result<i128, AdapterError> {
+        to.require_auth();
+        check_live(&e, deadline)?;
+        let pool = swap_pool(&e, &path)?;
+        swap_in(&e, &pool, amount_in, min_out, max_price, &path, &to)
+    }
+
+    fn swap_exact_out_limit(
+        e: Env,
+        amount_out: i128,
+        max_in: i128,
+        max_price: i128,
+        path: Vec<Address>,
+        to: Address,
+        deadline: u64,
+    ) -> Result<i128, AdapterError> {
+        to.require_auth();
+        check_live(&e, deadline)?;
+        let pool = swap_pool(&e, &path)?;
+        swap_out(&e, &pool, amount_out, max_in, max_price, &path, &to)
+    }
+
+    fn deposit(
+        e: Env,
+        pool: Address,
+        max_amounts: Vec<i128>,
+        to: Address,
+        deadline: u64,
+    ) -> Result<(Vec<i128>, i128), AdapterError> {
+        to.require_auth();
+        check_live(&e, deadline)?;
+        check_registered(&e, &pool)?;
+        join(&e, &pool, &max_amounts, &to)
+    }
+
+    fn deposit_single(
+        e: Env,
+        pool: Address,
+        token_in: Address,
+        amount_in: i128,
+        min_lp_out: i128,
+        to: Address,
+        deadline: u64,
+    ) -> Result<i128, AdapterError> {
+        to.require_auth();
+        check_live(&e, deadline)?;
+        check_registered(&e, &pool)?;
+        join_single(&e, &pool, &token_in, amount_in, min_lp_out, &to)
+    }
+
+    fn withdraw(
+        e: Env,
+        pool: Address,
+        lp_amount: i128,
+        min_amounts: Vec<i128>,
+        to: Address,
+        deadline: u64,
+    ) -> Result<Vec<i128>, AdapterError> {
+        to.require_auth();
+        check_live(&e, deadline)?;
+        check_registered(&e, &pool)?;
+        exit(&e, &pool, lp_amount, &min_amounts, &to)
+    }
+
+    fn withdraw_single(
+        e: Env,
+        pool: Address,
+        token_out: Address,
+        amount_out: i128,
+        max_lp_in: i128,
+        to: Address,
+        deadline: u64,
+    ) -> Result<i128, AdapterError> {
+        to.require_auth();
+        check_live(&e, deadline)?;
+        check_registered(&e, &pool)?;
+        let pool = CometPoolClient::new(&e, &pool);
+        if !pool.get_tokens().contains(&token_out) {
+            return Err(AdapterError::UnsupportedPair);
+        }
+        if amount_out <= 0 || max_lp_in <= 0 {
+            return Err(AdapterError::InvalidAmount);
+        }
+        let lp_in = pool.try_wdr_tokn_amt_out_get_lp_tokns_in(&token_out, &amount_out, &max_lp_in, &to)
+            .map_err(pool_err)?
+            .map_err(|_| AdapterError::ExternalFailure)?;
+        if lp_in > max_lp_in {
+            return Err(AdapterError::MinAmountNotMet);
+        }
+        bump(&e);
+        Ok(lp_in)
+    }
 }
 
 #[contractimpl]
@@ -47,10 +599,17 @@ impl AdapterTrait for CometAdapter {
     }
 
     /* ---------- lifecycle ---------- */
-    fn initialize(e: Env, amm_id: i128, amm_addr: Address) -> Result<(), AdapterError> {
+    fn initialize(
+        e: Env,
+        amm_id: i128,
+        amm_addr: Address,
+        admin: Address,
+        usdc: Address,
+    ) -> Result<(), AdapterError> {
         if is_init(&e) { return Err(AdapterError::AlreadyInitialized); }
         if amm_id != PROTOCOL_ID { return Err(AdapterError::InvalidID); }
 
+        adapter_base::init_core_config(&e, &admin, &usdc);
         set_amm(&e, amm_addr.clone());
         mark_init(&e);
         bump(&e);
@@ -59,10 +618,9 @@ impl AdapterTrait for CometAdapter {
     }
 
     fn upgrade(e: Env, new_wasm_hash: BytesN<32>) -> Result<(), AdapterError> {
-        // setup the config to get the admin address. set it during initialization.
-        let config = get_core_config(&e);
-        config.admin.require_auth();
-        Ok(e.deployer().update_current_contract_wasm(new_wasm_hash))
+        adapter_base::require_admin(&e).ok_or(AdapterError::NotInitialized)?;
+        e.deployer().update_current_contract_wasm(new_wasm_hash);
+        Ok(())
     }
 
     /* ---------- swaps ---------- */
@@ -75,26 +633,9 @@ impl AdapterTrait for CometAdapter {
         deadline: u64
     ) -> Result<i128, AdapterError> {
         to.require_auth();
-        if !is_init(&e){ return Err(AdapterError::ExternalFailure); }
-        if e.ledger().timestamp() > deadline {
-            return Err(AdapterError::ExternalFailure);
-        }
-        // Use pool mapping
-        let pool_addr = CometAdapter::get_pool_for_tokens(e.clone(), path.clone())
-            .ok_or(AdapterError::ExternalFailure)?;
-        let pool = CometPoolClient::new(&e, &pool_addr);
-        let offer_asset = path.get(0).ok_or(AdapterError::UnsupportedPair)?;
-        let ask_asset = path.get(1).ok_or(AdapterError::UnsupportedPair)?;
-        let (amt_out, _) = pool.swap_exact_amount_in(
-            &offer_asset,
-            &amount_in,
-            &ask_asset,
-            &min_out,
-            &i128::MAX, // max_price
-            &to
-        );
-        bump(&e);
-        Ok(amt_out)
+        check_live(&e, deadline)?;
+        let pool = swap_pool(&e, &path)?;
+        swap_in_quoted(&e, &pool, amount_in, min_out, &path, &to)
     }
 
     fn swap_exact_out(
@@ -106,101 +647,97 @@ impl AdapterTrait for CometAdapter {
         deadline: u64
     ) -> Result<i128, AdapterError> {
         to.require_auth();
-        if !is_init(&e){ return Err(AdapterError::ExternalFailure); }
-        if e.ledger().timestamp() > deadline {
-            return Err(AdapterError::ExternalFailure);
-        }
-        // Use pool mapping
-        let pool_addr = CometAdapter::get_pool_for_tokens(e.clone(), path.clone())
-            .ok_or(AdapterError::ExternalFailure)?;
-        let pool = CometPoolClient::new(&e, &pool_addr);
-        let offer_asset = path.get(0).ok_or(AdapterError::UnsupportedPair)?;
-        let ask_asset = path.get(1).ok_or(AdapterError::UnsupportedPair)?;
-        let (amt_in, _) = pool.swap_exact_amount_out(
-            &offer_asset,
-            &max_in,
-            &ask_asset,
-            &amount_out,
-            &i128::MAX, // max_price
-            &to
-        );
-        bump(&e);
-        Ok(amt_in)
+        check_live(&e, deadline)?;
+        let pool = swap_pool(&e, &path)?;
+        swap_out_quoted(&e, &pool, amount_out, max_in, &path, &to)
+    }
+
+    fn swap_exact_in_on(
+        e: Env,
+        pool: Address,
+        amount_in: i128,
+        min_out: i128,
+        path: Vec<Address>,
+        to: Address,
+        deadline: u64,
+    ) -> Result<i128, AdapterError> {
+        to.require_auth();
+        check_live(&e, deadline)?;
+        let pool = swap_pool_on(&e, &pool, &path)?;
+        swap_in_quoted(&e, &pool, amount_in, min_out, &path, &to)
+    }
+
+    fn swap_exact_out_on(
+        e: Env,
+        pool: Address,
+        amount_out: i128,
+        max_in: i128,
+        path: Vec<Address>,
+        to: Address,
+        deadline: u64,
+    ) -> Result<i128, AdapterError> {
+        to.require_auth();
+        check_live(&e, deadline)?;
+        let pool = swap_pool_on(&e, &pool, &path)?;
+        swap_out_quoted(&e, &pool, amount_out, max_in, &path, &to)
     }
 
     /* ---------- liquidity ---------- */
+    #[allow(clippy::too_many_arguments)]
     fn add_liquidity(
         e: Env,
         token_a: Address,
         token_b: Address,
         amt_a: i128,
         amt_b: i128,
-        _amt_a_min: i128,
-        _amt_b_min: i128,
+        amt_a_min: i128,
+        amt_b_min: i128,
         to: Address,
         deadline: u64
     ) -> Result<(i128, i128, i128), AdapterError> {
         to.require_auth();
-        if !is_init(&e) { return Err(AdapterError::ExternalFailure); }
-        if e.ledger().timestamp() > deadline {
-            return Err(AdapterError::ExternalFailure);
-        }
+        check_live(&e, deadline)?;
         let tokens = Vec::from_array(&e, [token_a.clone(), token_b.clone()]);
         let pool_addr = CometAdapter::get_pool_for_tokens(e.clone(), tokens.clone())
-            .ok_or(AdapterError::ExternalFailure)?;
-        let pool = CometPoolClient::new(&e, &pool_addr);
-        let max_amounts = Vec::from_array(&e, [amt_a, amt_b]);
-
-        // Fetch pool metadata
-        let pool_total = pool.get_total_supply();
-        let balances = Vec::from_array(&e, [pool.get_balance(&token_a), pool.get_balance(&token_b)]);
-
-        let decimals_a = token::Client::new(&e, &token_a).decimals();
-        let decimals_b = token::Client::new(&e, &token_b).decimals();
-        let scalars = Vec::from_array(&e, [10i128.pow(decimals_a as u32), 10i128.pow(decimals_b as u32)]);
-
-        // Calculate join ratios for each token
-        let mut min_ratio = None;
-        for i in 0..2 {
-            let user_amt = max_amounts.get_unchecked(i);
-            let pool_bal = balances.get_unchecked(i);
-            let scalar = scalars.get_unchecked(i);
-            // Upscale user_amt and pool_bal to I256
-            let user_amt_scaled = I256::from_i128(&e, user_amt * scalar);
-            let pool_bal_scaled = I256::from_i128(&e, pool_bal * scalar);
-            // ratio = user_amt / pool_bal
-            let ratio = if pool_bal_scaled > I256::from_i32(&e, 0) {
-                user_amt_scaled.fixed_div_floor(&e, &pool_bal_scaled, &I256::from_i128(&e, ONE_E9))
-            } else {
-                I256::from_i32(&e, 0)
-            };
-            min_ratio = Some(match min_ratio {
-                None => ratio,
-                Some(r) => if ratio < r { ratio } else { r },
-            });
+            .ok_or(AdapterError::PoolNotFound)?;
+        if amt_a < 0 || amt_b < 0 || (amt_a == 0 && amt_b == 0) || amt_a_min < 0 || amt_b_min < 0 {
+            return Err(AdapterError::InvalidAmount);
         }
-        // pool_amount_out = pool_total * min_ratio
-        let pool_total_256 = I256::from_i128(&e, pool_total);
-        let min_ratio = min_ratio.unwrap_or(I256::from_i32(&e, 0));
-        let pool_amount_out_256 = pool_total_256.fixed_mul_floor(&e, &min_ratio, &I256::from_i128(&e, ONE_E9));
-        let pool_amount_out = pool_amount_out_256.to_i128().unwrap_optimized();
-
-        let before_lp = pool.balance(&to);
-        let before_a = token::Client::new(&e, &token_a).balance(&to);
-        let before_b = token::Client::new(&e, &token_b).balance(&to);
-        pool.join_pool(
-            &pool_amount_out,
-            &max_amounts,
-            &to
-        );
-        bump(&e);
-        let after_lp = pool.balance(&to);
-        let after_a = token::Client::new(&e, &token_a).balance(&to);
-        let after_b = token::Client::new(&e, &token_b).balance(&to);
-        let actual_lp = after_lp - before_lp;
-        let actual_a = before_a - after_a;
-        let actual_b = before_b - after_b;
-        Ok((actual_a, actual_b, actual_lp))
+        // One side only: single-asset join of exactly the given amount; nothing
+        // of the other side is taken
+        if amt_a == 0 || amt_b == 0 {
+            if (amt_a == 0 && amt_a_min > 0) || (amt_b == 0 && amt_b_min > 0) {
+                return Err(AdapterError::MinAmountNotMet);
+            }
+            let (token_in, amount_in, amount_min) = if amt_a > 0 { (token_a, amt_a, amt_a_min) } else { (token_b, amt_b, amt_b_min) };
+            if amount_min > amount_in {
+                return Err(AdapterError::MinAmountNotMet);
+            }
+            let min_lp = single_lp_floor(&e, &pool_addr, &token_in, amount_in)?;
+            let lp = join_single(&e, &pool_addr, &token_in, amount_in, min_lp, &to)?;
+            return Ok(if amt_a > 0 { (amount_in, 0, lp) } else { (0, amount_in, lp) });
+        }
+        // A proportional join needs an amount for every pool token
+        let pool_tokens = CometPoolClient::new(&e, &pool_addr).get_tokens();
+        if pool_tokens.len() != 2 {
+            return Err(AdapterError::InvalidArgument);
+        }
+        let a_first = pool_tokens.get_unchecked(0) == token_a;
+        let max_amounts = if a_first {
+            Vec::from_array(&e, [amt_a, amt_b])
+        } else {
+            Vec::from_array(&e, [amt_b, amt_a])
+        };
+        let (taken, lp) = join(&e, &pool_addr, &max_amounts, &to)?;
+        let (actual_a, actual_b) = if a_first {
+            (taken.get_unchecked(0), taken.get_unchecked(1))
+        } else {
+            (taken.get_unchecked(1), taken.get_unchecked(0))
+        };
+        if actual_a < amt_a_min || actual_b < amt_b_min {
+            return Err(AdapterError::MinAmountNotMet);
+        }
+        Ok((actual_a, actual_b, lp))
     }
 
     fn remove_liquidity(
@@ -213,32 +750,28 @@ impl AdapterTrait for CometAdapter {
         deadline: u64
     ) -> Result<(i128, i128), AdapterError> {
         to.require_auth();
-        if !is_init(&e) { return Err(AdapterError::ExternalFailure); }
-        if e.ledger().timestamp() > deadline {
-            return Err(AdapterError::ExternalFailure);
-        }
-        // For Comet, the LP token address is the pool address
+        check_live(&e, deadline)?;
+        check_registered(&e, &lp_token)?;
+        // For Comet, the LP token address is the pool address; the two
+        // minimums follow the pool's token order
         let pool = CometPoolClient::new(&e, &lp_token);
-        let min_amounts_out = Vec::from_array(&e, [amt_a_min, amt_b_min]);
         let tokens = pool.get_tokens();
-        let token_a = tokens.get_unchecked(0);
-        let token_b = tokens.get_unchecked(1);
-        
-        let before_a = token::Client::new(&e, &token_a).balance(&to);
-        let before_b = token::Client::new(&e, &token_b).balance(&to);
-       
-        pool.exit_pool(
-            &lp_amount,
-            &min_amounts_out,
-            &to
-        );
-        bump(&e);
-        let after_a = token::Client::new(&e, &token_a).balance(&to);
-        let after_b = token::Client::new(&e, &token_b).balance(&to);
-        let actual_a = after_a - before_a;
-        let actual_b = after_b - before_b;
-       
-        Ok((actual_a, actual_b))
+        if tokens.len() != 2 {
+            // Multi-token pools go through `withdraw`
+            return Err(AdapterError::InvalidArgument);
+        }
+        if lp_amount <= 0 {
+            return Err(AdapterError::InvalidAmount);
+        }
+        // Exit from the adapter's balance, as for swaps, so `to` only
+        // authorizes the LP transfer here
+        adapter_base::take(&e, &lp_token, &to, lp_amount).ok_or(AdapterError::InsufficientBalance)?;
+        let min_amounts_out = Vec::from_array(&e, [amt_a_min, amt_b_min]);
+        let received = exit(&e, &lp_token, lp_amount, &min_amounts_out, &e.current_contract_address())?;
+        for i in 0..tokens.len() {
+            adapter_base::pay(&e, &tokens.get_unchecked(i), &to, received.get_unchecked(i));
+        }
+        Ok((received.get_unchecked(0), received.get_unchecked(1)))
     }
 
     /* ---------- quotes ---------- */
@@ -249,12 +782,8 @@ impl AdapterTrait for CometAdapter {
         if amount_in <= 0 {
             return Err(AdapterError::InvalidAmount);
         }
-        let pool = CometPoolClient::new(&e, &pool_address);
-        // get_spot_price returns the price per unit of token_in in terms of token_out
-        let price_per_unit = pool.get_spot_price(&token_in, &token_out);
-        // Total output = price_per_unit * amount_in
-        let amount_out = price_per_unit * amount_in;
-        Ok(amount_out)
+        let (balance_in, weight_in, balance_out, weight_out, fee) = pool_state(&e, &pool_address, &token_in, &token_out)?;
+        math::out_given_in(balance_in, weight_in, balance_out, weight_out, amount_in, fee)
     }
 
     fn quote_out(e: Env, pool_address: Address, amount_out: i128, token_in: Address, token_out: Address) -> Result<i128, AdapterError> {
@@ -264,15 +793,61 @@ impl AdapterTrait for CometAdapter {
         if amount_out <= 0 {
             return Err(AdapterError::InvalidAmount);
         }
-        let pool = CometPoolClient::new(&e, &pool_address);
-        // get_spot_price returns the price per unit of token_in in terms of token_out
-        let price_per_unit = pool.get_spot_price(&token_in, &token_out);
-        // Required input = amount_out / price_per_unit
-        if price_per_unit == 0 {
-            return Err(AdapterError::InvalidAmount);
+        let (balance_in, weight_in, balance_out, weight_out, fee) = pool_state(&e, &pool_address, &token_in, &token_out)?;
+        math::in_given_out(balance_in, weight_in, balance_out, weight_out, amount_out, fee)
+    }
+
+    fn get_spot_price(e: Env, pool: Address, token_in: Address, token_out: Address) -> Result<i128, AdapterError> {
+        if !is_init(&e) {
+            return Err(AdapterError::NotInitialized);
+        }
+        // Comet prices `token_out` in `token_in` (Balancer convention), in STROOP
+        let in_per_out = CometPoolClient::new(&e, &pool)
+            .try_get_spot_price_sans_fee(&token_in, &token_out)
+            .map_err(pool_err)?
+            .map_err(|_| AdapterError::ExternalFailure)?;
+        if in_per_out <= 0 {
+            return Err(AdapterError::InsufficientLiquidity);
         }
-        let amount_in = amount_out / price_per_unit;
-        Ok(amount_in)
+        Ok(PRICE_SCALE * STROOP / in_per_out)
+    }
+
+    /* ---------- discovery ---------- */
+    fn get_pool_info(e: Env, pool: Address) -> Result<PoolInfo, AdapterError> {
+        if !is_init(&e) {
+            return Err(AdapterError::NotInitialized);
+        }
+        let client = CometPoolClient::new(&e, &pool);
+        let tokens = match client.try_get_tokens() {
+            Ok(Ok(tokens)) => tokens,
+            _ => return Err(AdapterError::PoolNotFound),
+        };
+        let mut reserves = Vec::new(&e);
+        let mut weights = Vec::new(&e);
+        for t in tokens.iter() {
+            reserves.push_back(client.get_balance(&t));
+            weights.push_back(client.get_normalized_weight(&t));
+        }
+        // Comet fees are scaled by STROOP
+        let fee_bps = client.get_swap_fee() * 10_000 / STROOP;
+        Ok(PoolInfo {
+            pool: pool.clone(),
+            lp_token: pool,
+            tokens,
+            reserves,
+            weights,
+            fee_bps: fee_bps as u32,
+            kind: PoolKind::Weighted,
+            lp_supply: client.get_total_supply(),
+        })
+    }
+
+    fn supports(_e: Env, capability: Capability) -> bool {
+        matches!(capability, Capability::ExactOut | Capability::SingleSidedLp)
+    }
+
+    fn list_pools(e: Env, start: u32, limit: u32) -> Vec<Address> {
+        adapter_base::page(&get_all_pools(&e), start, limit)
     }
 }
 
@@ -283,4 +858,4 @@ pub struct Record {
     pub weight: i128,
     pub scalar: i128,
     pub index: u32,
-}
+}
\ No newline at end of file
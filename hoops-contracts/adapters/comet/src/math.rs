@@ -0,0 +1,206 @@
+//! Balancer weighted-pool math, as used by Comet pools, for off-pool quotes.
+//! Values are fixed point with 18 decimals (`BONE`); token amounts are plain
+//! 7-decimal amounts.
+
+use hoops_adapter_interface::AdapterError;
+
+pub const BONE: i128 = 1_000_000_000_000_000_000;
+const BPOW_PRECISION: i128 = BONE / 10_000_000_000;
+/// Comet rejects trades above half the input balance or a third of the
+/// output balance.
+const MAX_IN_RATIO: i128 = BONE / 2;
+const MAX_OUT_RATIO: i128 = BONE / 3 + 1;
+
+fn bmul(a: i128, b: i128) -> i128 {
+    (a * b + BONE / 2) / BONE
+}
+
+fn bdiv(a: i128, b: i128) -> i128 {
+    (a * BONE + b / 2) / b
+}
+
+fn bpowi(a: i128, n: i128) -> i128 {
+    let mut z = if n % 2 != 0 { a } else { BONE };
+    let (mut a, mut n) = (a, n / 2);
+    while n != 0 {
+        a = bmul(a, a);
+        if n % 2 != 0 {
+            z = bmul(z, a);
+        }
+        n /= 2;
+    }
+    z
+}
+
+/// `base ^ exp` for a fractional `exp`, by binomial expansion.
+fn bpow_approx(base: i128, exp: i128) -> i128 {
+    let (x, xneg) = if base >= BONE { (base - BONE, false) } else { (BONE - base, true) };
+    let mut term = BONE;
+    let mut sum = term;
+    let mut negative = false;
+    let mut i = 1;
+    while term >= BPOW_PRECISION {
+        let big_k = i * BONE;
+        let (c, cneg) = if exp >= big_k - BONE { (exp - (big_k - BONE), false) } else { (big_k - BONE - exp, true) };
+        term = bdiv(bmul(term, bmul(c, x)), big_k);
+        if term == 0 {
+            break;
+        }
+        if xneg {
+            negative = !negative;
+        }
+        if cneg {
+            negative = !negative;
+        }
+        sum = if negative { sum - term } else { sum + term };
+        i += 1;
+    }
+    sum
+}
+
+fn bpow(base: i128, exp: i128) -> i128 {
+    let whole = exp / BONE;
+    let remain = exp - whole * BONE;
+    let whole_pow = bpowi(base, whole);
+    if remain == 0 {
+        return whole_pow;
+    }
+    bmul(whole_pow, bpow_approx(base, remain))
+}
+
+/// Output for `amount_in`, after the swap fee. `fee` is in `BONE` scale.
+pub fn out_given_in(
+    balance_in: i128,
+    weight_in: i128,
+    balance_out: i128,
+    weight_out: i128,
+    amount_in: i128,
+    fee: i128,
+) -> Result<i128, AdapterError> {
+    if balance_in <= 0 || balance_out <= 0 || weight_in <= 0 || weight_out <= 0 {
+        return Err(AdapterError::InsufficientLiquidity);
+    }
+    if amount_in > bmul(balance_in, MAX_IN_RATIO) {
+        return Err(AdapterError::MaxInRatio);
+    }
+    let weight_ratio = bdiv(weight_in, weight_out);
+    let adjusted_in = bmul(amount_in, BONE - fee);
+    let y = bdiv(balance_in, balance_in + adjusted_in);
+    Ok(bmul(balance_out, BONE - bpow(y, weight_ratio)))
+}
+
+/// Input needed for `amount_out`, including the swap fee, rounded up.
+pub fn in_given_out(
+    balance_in: i128,
+    weight_in: i128,
+    balance_out: i128,
+    weight_out: i128,
+    amount_out: i128,
+    fee: i128,
+) -> Result<i128, AdapterError> {
+    if balance_in <= 0 || balance_out <= 0 || weight_in <= 0 || weight_out <= 0 {
+        return Err(AdapterError::InsufficientLiquidity);
+    }
+    if amount_out > bmul(balance_out, MAX_OUT_RATIO) {
+        return Err(AdapterError::MaxOutRatio);
+    }
+    let weight_ratio = bdiv(weight_out, weight_in);
+    let y = bdiv(balance_out, balance_out - amount_out);
+    let before_fee = bmul(balance_in, bpow(y, weight_ratio) - BONE);
+    Ok((before_fee * BONE + BONE - fee - 1) / (BONE - fee))
+}
+
+/// Comet's spot price (`token_in` per `token_out`, fee included) after
+/// `amount_in` went into the pool and `amount_out` came out.
+pub fn spot_price_after(
+    balance_in: i128,
+    weight_in: i128,
+    balance_out: i128,
+    weight_out: i128,
+    amount_in: i128,
+    amount_out: i128,
+    fee: i128,
+) -> Result<i128, AdapterError> {
+    if balance_out <= amount_out || weight_in <= 0 || weight_out <= 0 {
+        return Err(AdapterError::InsufficientLiquidity);
+    }
+    let ratio = bmul(bdiv(balance_in + amount_in, balance_out - amount_out), bdiv(weight_out, weight_in));
+    Ok(bdiv(ratio, BONE - fee))
+}
+
+/// LP minted for a single-sided deposit of `amount_in`, rounded down. The
+/// part of the input swapped into the other tokens pays `(1 - weight_in) * fee`.
+pub fn lp_out_given_in(
+    balance_in: i128,
+    weight_in: i128,
+    supply: i128,
+    amount_in: i128,
+    fee: i128,
+) -> Result<i128, AdapterError> {
+    if balance_in <= 0 || weight_in <= 0 || supply <= 0 {
+        return Err(AdapterError::InsufficientLiquidity);
+    }
+    if amount_in > bmul(balance_in, MAX_IN_RATIO) {
+        return Err(AdapterError::MaxInRatio);
+    }
+    let adjusted_in = bmul(amount_in, BONE - bmul(BONE - weight_in, fee));
+    let ratio = bdiv(balance_in + adjusted_in, balance_in);
+    let new_supply = bmul(bpow(ratio, weight_in), supply);
+    Ok((new_supply - supply).max(0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HALF: i128 = BONE / 2;
+    const FEE: i128 = BONE * 3 / 1000;
+
+    #[test]
+    fn test_out_given_in_matches_constant_product() {
+        // Equal weights without fee: out = balance_out * in / (balance_in + in)
+        let out = out_given_in(10_000_000_000, HALF, 10_000_000_000, HALF, 100_000_000, 0).unwrap();
+        assert!((out - 99_009_901).abs() <= 1, "{}", out);
+        let with_fee = out_given_in(10_000_000_000, HALF, 10_000_000_000, HALF, 100_000_000, FEE).unwrap();
+        assert!(with_fee < out);
+    }
+
+    #[test]
+    fn test_in_given_out_inverts_out_given_in() {
+        let (b_in, w_in, b_out, w_out) = (50_000_000_000, BONE * 8 / 10, 20_000_000_000, BONE * 2 / 10);
+        let amount_in = 250_000_000;
+        let out = out_given_in(b_in, w_in, b_out, w_out, amount_in, FEE).unwrap();
+        let needed = in_given_out(b_in, w_in, b_out, w_out, out, FEE).unwrap();
+        assert!((needed - amount_in).abs() <= 10, "{} vs {}", needed, amount_in);
+    }
+
+    #[test]
+    fn test_trade_size_limits() {
+        assert_eq!(out_given_in(100, HALF, 100, HALF, 51, 0), Err(AdapterError::MaxInRatio));
+        assert_eq!(in_given_out(100, HALF, 100, HALF, 34, 0), Err(AdapterError::MaxOutRatio));
+        assert_eq!(out_given_in(0, HALF, 100, HALF, 1, 0), Err(AdapterError::InsufficientLiquidity));
+    }
+
+    #[test]
+    fn test_lp_out_given_in() {
+        // Without fee, a single-sided deposit of a whole-weight token grows the
+        // supply by the balance ratio
+        let lp = lp_out_given_in(1_000_000_000, BONE, 1_000_000_000, 100_000_000, 0).unwrap();
+        assert!((lp - 100_000_000).abs() <= 1, "{}", lp);
+        // Half weight: supply * (sqrt(1.21) - 1) = 10% for 21% more balance
+        let lp = lp_out_given_in(1_000_000_000, HALF, 1_000_000_000, 210_000_000, 0).unwrap();
+        assert!((lp - 100_000_000).abs() <= 10, "{}", lp);
+        let with_fee = lp_out_given_in(1_000_000_000, HALF, 1_000_000_000, 210_000_000, FEE).unwrap();
+        assert!(with_fee < lp);
+        assert_eq!(lp_out_given_in(100, HALF, 100, 51, 0), Err(AdapterError::MaxInRatio));
+    }
+
+    #[test]
+    fn test_spot_price_after() {
+        // Before any trade the spot price is the weighted balance ratio over (1 - fee)
+        let spot = spot_price_after(2_000, HALF, 1_000, HALF, 0, 0, 0).unwrap();
+        assert_eq!(spot, 2 * BONE);
+        let after = spot_price_after(2_000, HALF, 1_000, HALF, 100, 50, FEE).unwrap();
+        assert!(after > spot);
+    }
+}
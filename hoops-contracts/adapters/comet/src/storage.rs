@@ -1,22 +1,11 @@
-use soroban_sdk::{contracttype, symbol_short, Address, Env, Symbol, unwrap::UnwrapOptimized, Vec};
+use soroban_sdk::{contracttype, Address, Env, Vec};
 use hoops_adapter_interface::AdapterError;
 
-#[derive(Clone)]
-#[contracttype]
-enum Key { Amm, Init, Pool(PoolKey) }
-
-const DAY_LEDGER: u32 = 17_280;
-const BUMP: u32 = 60 * DAY_LEDGER;
+pub use hoops_common::adapter_base::bump;
 
+#[derive(Clone)]
 #[contracttype]
-#[derive(Clone, Debug, Eq, PartialEq)]
-pub struct CoreConfig {
-    pub admin: Address,
-    pub usdc: Address,
-    pub next: u32,
-    pub ttl_thresh: u32,
-    pub ttl_bump: u32,
-}
+enum Key { Amm, Init, Pool(PoolKey), PriceTol, Sets }
 
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -24,44 +13,44 @@ pub struct PoolKey {
     pub tokens: Vec<Address>,
 }
 
-pub const KEY_CORE_CONFIG: Symbol = symbol_short!("CONFIG");
-
-pub fn set_core_config(e: &Env, config: &CoreConfig) {
-    e.storage().instance().set(&KEY_CORE_CONFIG, config);
-}
-pub fn get_core_config(e: &Env) -> CoreConfig {
-    e.storage().instance().get(&KEY_CORE_CONFIG).unwrap_optimized()
-}
-#[allow(dead_code)]
-pub fn update_core_config<F: FnOnce(&mut CoreConfig)>(e: &Env, f: F) {
-    let mut config = get_core_config(e);
-    f(&mut config);
-    set_core_config(e, &config);
-}
-
 pub fn set_amm(e:&Env, a:Address){ e.storage().instance().set(&Key::Amm,&a); }
 pub fn get_amm(e:&Env)->Result<Address,AdapterError>{
-    e.storage().instance().get(&Key::Amm).ok_or(AdapterError::ExternalFailure)
+    e.storage().instance().get(&Key::Amm).ok_or(AdapterError::NotInitialized)
 }
 pub fn mark_init(e:&Env){ e.storage().instance().set(&Key::Init,&true); }
 pub fn is_init(e:&Env)->bool{ e.storage().instance().has(&Key::Init) }
 
-pub fn bump(e:&Env){
-    e.storage().instance().extend_ttl(BUMP-DAY_LEDGER, BUMP);
+/// Default tolerance, in bps, for the price limits of the plain swaps and the
+/// LP floor of single-sided joins.
+pub const DEFAULT_PRICE_TOLERANCE_BPS: u32 = 100;
+pub fn set_price_tolerance(e:&Env, bps:u32){ e.storage().instance().set(&Key::PriceTol,&bps); }
+pub fn get_price_tolerance(e:&Env)->u32{
+    e.storage().instance().get(&Key::PriceTol).unwrap_or(DEFAULT_PRICE_TOLERANCE_BPS)
 }
 
-// Helper to sort a soroban_sdk::Vec<Address> canonically
+// Helper to sort a soroban_sdk::Vec<Address> canonically (any number of tokens)
 fn sort_addresses(e: &Env, tokens: &Vec<Address>) -> Vec<Address> {
-    let mut arr: [Address; 2] = [tokens.get_unchecked(0), tokens.get_unchecked(1)];
-    if arr[0] > arr[1] {
-        arr.swap(0, 1);
+    let mut sorted: Vec<Address> = Vec::new(e);
+    for t in tokens.iter() {
+        let pos = sorted.iter().position(|s| s > t).unwrap_or(sorted.len() as usize);
+        sorted.insert(pos as u32, t);
     }
-    Vec::from_array(e, arr)
+    sorted
+}
+
+// Registered token sets (sorted), in registration order
+fn get_token_sets(e: &Env) -> Vec<Vec<Address>> {
+    e.storage().instance().get(&Key::Sets).unwrap_or(Vec::new(e))
 }
 
 // Store a pool address for a given set of tokens (sorted for canonicalization)
 pub fn set_pool_for_tokens(e: &Env, tokens: &Vec<Address>, pool: &Address) {
     let tokens_sorted = sort_addresses(e, tokens);
+    let mut sets = get_token_sets(e);
+    if !sets.contains(&tokens_sorted) {
+        sets.push_back(tokens_sorted.clone());
+        e.storage().instance().set(&Key::Sets, &sets);
+    }
     let key = Key::Pool(PoolKey { tokens: tokens_sorted });
     e.storage().instance().set(&key, pool);
 }
@@ -72,3 +61,30 @@ pub fn get_pool_for_tokens(e: &Env, tokens: &Vec<Address>) -> Option<Address> {
     let key = Key::Pool(PoolKey { tokens: tokens_sorted });
     e.storage().instance().get(&key)
 }
+
+// Remove the pool address for a given set of tokens (sorted for canonicalization)
+pub fn remove_pool_for_tokens(e: &Env, tokens: &Vec<Address>) -> Option<Address> {
+    let tokens_sorted = sort_addresses(e, tokens);
+    let key = Key::Pool(PoolKey { tokens: tokens_sorted });
+    let pool: Address = e.storage().instance().get(&key)?;
+    e.storage().instance().remove(&key);
+    let mut sets = get_token_sets(e);
+    if let Some(i) = sets.first_index_of(sort_addresses(e, tokens)) {
+        sets.remove(i);
+        e.storage().instance().set(&Key::Sets, &sets);
+    }
+    Some(pool)
+}
+
+// Every pool with at least one mapping, without duplicates
+pub fn get_all_pools(e: &Env) -> Vec<Address> {
+    let mut pools = Vec::new(e);
+    for set in get_token_sets(e).iter() {
+        if let Some(pool) = get_pool_for_tokens(e, &set) {
+            if !pools.contains(&pool) {
+                pools.push_back(pool);
+            }
+        }
+    }
+    pools
+}
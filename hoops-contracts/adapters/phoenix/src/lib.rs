@@ -7,15 +7,137 @@ mod protocol;
 use storage::*;
 #[allow(unused_imports)]
 use event::*;
-use hoops_adapter_interface::{AdapterTrait, AdapterError};
-use soroban_sdk::{contract, contractimpl, Address, BytesN, Env, Vec};
-use protocol::phoenix_pair::PhoenixPoolClient;
+use hoops_adapter_interface::{AdapterTrait, AdapterError, Capability, PoolInfo, PoolKind, PRICE_SCALE};
+use hoops_common::adapter_base;
+use soroban_sdk::{contract, contractimpl, vec, Address, BytesN, Env, InvokeError, Vec};
+use protocol::phoenix_pair::{ContractError as PhoenixError, PhoenixPoolClient};
 
 const PROTOCOL_ID: i128 = 2;
 
+fn check_live(e: &Env, deadline: u64) -> Result<(), AdapterError> {
+    if !is_init(e) { return Err(AdapterError::NotInitialized); }
+    if e.ledger().timestamp() > deadline {
+        return Err(AdapterError::DeadlinePassed);
+    }
+    Ok(())
+}
+
+/// Translate a Phoenix pool error into an `AdapterError`. Spread and minimum
+/// checks surface as `MinAmountNotMet`; unknown errors stay `ExternalFailure`.
+fn pool_err(err: Result<soroban_sdk::Error, InvokeError>) -> AdapterError {
+    const MAP: [(PhoenixError, AdapterError); 14] = [
+        (PhoenixError::SpreadExceedsLimit, AdapterError::MinAmountNotMet),
+        (PhoenixError::SwapMinReceivedBiggerThanReturn, AdapterError::MinAmountNotMet),
+        (PhoenixError::ProvideLiquiditySlippageToleranceTooHigh, AdapterError::MinAmountNotMet),
+        (PhoenixError::WithdrawLiquidityMinimumAmountOfAOrBIsNotSatisfied, AdapterError::MinAmountNotMet),
+        (PhoenixError::GetDepositAmountsAmountALessThenMinA, AdapterError::MinAmountNotMet),
+        (PhoenixError::GetDepositAmountsAmountBLessThenMinB, AdapterError::MinAmountNotMet),
+        (PhoenixError::TransactionAfterTimestampDeadline, AdapterError::DeadlinePassed),
+        (PhoenixError::AssetNotInPool, AdapterError::UnsupportedPair),
+        (PhoenixError::TotalSharesEqualZero, AdapterError::InsufficientLiquidity),
+        (PhoenixError::NotEnoughLiquidityProvided, AdapterError::InsufficientLiquidity),
+        (PhoenixError::NotEnoughSharesToBeMinted, AdapterError::InsufficientLiquidity),
+        (PhoenixError::DesiredAmountsBelowOrEqualZero, AdapterError::InvalidAmount),
+        (PhoenixError::MinAmountsBelowZero, AdapterError::InvalidAmount),
+        (PhoenixError::NegativeInputProvided, AdapterError::InvalidAmount),
+    ];
+    let Ok(code) = err else { return AdapterError::ExternalFailure };
+    MAP.into_iter()
+        .find(|(c, _)| code == (*c).into())
+        .map_or(AdapterError::ExternalFailure, |(_, a)| a)
+}
+
+/// The adapter trades the configured pool only, one hop at a time.
+fn single_hop(path: &Vec<Address>) -> Result<(Address, Address), AdapterError> {
+    if path.len() != 2 {
+        return Err(if path.len() < 2 { AdapterError::InvalidPath } else { AdapterError::MultipathUnsupported });
+    }
+    Ok((path.get_unchecked(0), path.get_unchecked(1)))
+}
+
+/// Exact-in swap on the configured pool from the adapter's balance; pays
+/// what actually arrived to `to`.
+fn swap_in(e: &Env, amt_in: i128, min_out: i128, path: Vec<Address>, to: Address, deadline: u64) -> Result<i128, AdapterError> {
+    let (offer_asset, ask_asset) = single_hop(&path)?;
+
+    let pool_addr = get_amm(e)?;
+    let pool = PhoenixPoolClient::new(e, &pool_addr);
+    // Trade from the adapter's balance; the pool pulls the input from here
+    adapter_base::take(e, &offer_asset, &to, amt_in).ok_or(AdapterError::InsufficientBalance)?;
+    let before = adapter_base::held(e, &ask_asset);
+    adapter_base::authorize_transfer(e, &offer_asset, &pool_addr, amt_in);
+    pool.try_swap(
+        &e.current_contract_address(), // sender
+        &offer_asset,
+        &amt_in,
+        &Some(min_out),
+        &None, // max_spread_bps
+        &Some(deadline as u64),
+        &None // max_allowed_fee_bps
+    )
+        .map_err(pool_err)?
+        .map_err(|_| AdapterError::ExternalFailure)?;
+    // Pay out what actually arrived
+    let amt_out = adapter_base::held(e, &ask_asset) - before;
+    if amt_out < min_out {
+        return Err(AdapterError::MinAmountNotMet);
+    }
+    adapter_base::pay(e, &ask_asset, &to, amt_out);
+    bump(e);
+    Ok(amt_out)
+}
+
+/// Exact-out swap on the configured pool. Takes `max_in` from `to`, spends
+/// the simulated input and refunds the rest.
+fn swap_out(e: &Env, out: i128, max_in: i128, path: Vec<Address>, to: Address, deadline: u64) -> Result<i128, AdapterError> {
+    let (offer_asset, ask_asset) = single_hop(&path)?;
+    let pool_addr = get_amm(e)?;
+    let pool = PhoenixPoolClient::new(e, &pool_addr);
+    let resp = pool.try_simulate_reverse_swap(&ask_asset, &out)
+        .map_err(pool_err)?
+        .map_err(|_| AdapterError::ExternalFailure)?;
+    let required_in = resp.offer_amount;
+    if required_in > max_in {
+        return Err(AdapterError::MaxInExceeded);
+    }
+    // Take `max_in`, spend the simulated input and refund the rest
+    adapter_base::take(e, &offer_asset, &to, max_in).ok_or(AdapterError::InsufficientBalance)?;
+    let before = adapter_base::held(e, &ask_asset);
+    adapter_base::authorize_transfer(e, &offer_asset, &pool_addr, required_in);
+    pool.try_swap(
+        &e.current_contract_address(), // sender
+        &offer_asset,
+        &required_in,
+        &Some(out),
+        &None, // max_spread_bps
+        &Some(deadline as u64),
+        &None // max_allowed_fee_bps
+    )
+        .map_err(pool_err)?
+        .map_err(|_| AdapterError::ExternalFailure)?;
+    let amt_out = adapter_base::held(e, &ask_asset) - before;
+    if amt_out < out {
+        return Err(AdapterError::MinAmountNotMet);
+    }
+    adapter_base::pay(e, &ask_asset, &to, amt_out);
+    adapter_base::pay(e, &offer_asset, &to, max_in - required_in);
+    bump(e);
+    Ok(required_in)
+}
+
+/// `PoolNotFound` unless `pool` is the configured pool.
+fn check_pool(e: &Env, pool: &Address) -> Result<(), AdapterError> {
+    if *pool != get_amm(e)? {
+        return Err(AdapterError::PoolNotFound);
+    }
+    Ok(())
+}
+
 #[contract]
 pub struct PhoenixAdapter;
 
+hoops_common::adapter_admin!(PhoenixAdapter, AdapterError);
+
 #[contractimpl]
 impl AdapterTrait for PhoenixAdapter {
 
@@ -24,10 +146,17 @@ impl AdapterTrait for PhoenixAdapter {
     }
 
     /* ---------- lifecycle ---------- */
-    fn initialize(e: Env, amm_id: i128, amm_addr: Address) -> Result<(), AdapterError> {
+    fn initialize(
+        e: Env,
+        amm_id: i128,
+        amm_addr: Address,
+        admin: Address,
+        usdc: Address,
+    ) -> Result<(), AdapterError> {
         if is_init(&e) { return Err(AdapterError::AlreadyInitialized); }
         if amm_id != PROTOCOL_ID { return Err(AdapterError::InvalidID); }
 
+        adapter_base::init_core_config(&e, &admin, &usdc);
         set_amm(&e, amm_addr.clone());
         mark_init(&e);
         bump(&e);
@@ -35,9 +164,10 @@ impl AdapterTrait for PhoenixAdapter {
         Ok(())
     }
 
-    fn upgrade(_e: Env, _hash: BytesN<32>) -> Result<(), AdapterError> {
-        // Stub: always fail for now
-        Err(AdapterError::ExternalFailure)
+    fn upgrade(e: Env, new_wasm_hash: BytesN<32>) -> Result<(), AdapterError> {
+        adapter_base::require_admin(&e).ok_or(AdapterError::NotInitialized)?;
+        e.deployer().update_current_contract_wasm(new_wasm_hash);
+        Ok(())
     }
 
     /* ---------- swaps ---------- */
@@ -50,27 +180,8 @@ impl AdapterTrait for PhoenixAdapter {
         deadline: u64
     ) -> Result<i128, AdapterError> {
         to.require_auth();
-        if !is_init(&e){ return Err(AdapterError::ExternalFailure); }
-        if e.ledger().timestamp()>deadline{
-            return Err(AdapterError::ExternalFailure);
-        }
-
-        let pool = PhoenixPoolClient::new(&e, &get_amm(&e)?);
-        // Only support single-hop for now
-        let offer_asset = path.get(0).ok_or(AdapterError::UnsupportedPair)?;
-        
-        //let ask_asset = path.get(1).ok_or(AdapterError::UnsupportedPair)?;
-        let amt_out = pool.swap(
-            &to, // sender
-            &offer_asset,
-            &amt_in,
-            &Some(min_out),
-            &None, // max_spread_bps
-            &Some(deadline as u64),
-            &None // max_allowed_fee_bps
-        );
-        bump(&e);
-        Ok(amt_out)
+        check_live(&e, deadline)?;
+        swap_in(&e, amt_in, min_out, path, to, deadline)
     }
 
     fn swap_exact_out(
@@ -78,32 +189,42 @@ impl AdapterTrait for PhoenixAdapter {
         to: Address, deadline: u64
     ) -> Result<i128, AdapterError> {
         to.require_auth();
-        if !is_init(&e){ return Err(AdapterError::ExternalFailure); }
-        if e.ledger().timestamp()>deadline{
-            return Err(AdapterError::ExternalFailure); }
-        let pool = PhoenixPoolClient::new(&e, &get_amm(&e)?);
-        let ask_asset = &path.get(1).ok_or(AdapterError::UnsupportedPair)?;
-        let resp = pool.simulate_reverse_swap(&ask_asset, &out);
-        let required_in = resp.offer_amount;
-        if required_in > max_in {
-            return Err(AdapterError::ExternalFailure);
-        }
-        // Actually perform the swap
-        let offer_asset = path.get(0).ok_or(AdapterError::UnsupportedPair)?;
-        let _amt_out = pool.swap(
-            &to, // sender
-            &offer_asset,
-            &required_in,
-            &Some(out),
-            &None, // max_spread_bps
-            &Some(deadline as u64),
-            &None // max_allowed_fee_bps
-        );
-        bump(&e);
-        Ok(required_in)
+        check_live(&e, deadline)?;
+        swap_out(&e, out, max_in, path, to, deadline)
+    }
+
+    fn swap_exact_in_on(
+        e: Env,
+        pool: Address,
+        amt_in: i128,
+        min_out: i128,
+        path: Vec<Address>,
+        to: Address,
+        deadline: u64
+    ) -> Result<i128, AdapterError> {
+        to.require_auth();
+        check_live(&e, deadline)?;
+        check_pool(&e, &pool)?;
+        swap_in(&e, amt_in, min_out, path, to, deadline)
+    }
+
+    fn swap_exact_out_on(
+        e: Env,
+        pool: Address,
+        out: i128,
+        max_in: i128,
+        path: Vec<Address>,
+        to: Address,
+        deadline: u64
+    ) -> Result<i128, AdapterError> {
+        to.require_auth();
+        check_live(&e, deadline)?;
+        check_pool(&e, &pool)?;
+        swap_out(&e, out, max_in, path, to, deadline)
     }
 
     /* ---------- liquidity ---------- */
+    #[allow(clippy::too_many_arguments)]
     fn add_liquidity(
         e: Env,
         _a: Address,
@@ -116,17 +237,16 @@ impl AdapterTrait for PhoenixAdapter {
         deadline: u64
     ) -> Result<(i128, i128, i128), AdapterError> {
         to.require_auth();
-        if !is_init(&e) { return Err(AdapterError::ExternalFailure); }
-        if e.ledger().timestamp() > deadline {
-            return Err(AdapterError::ExternalFailure);
-        }
+        check_live(&e, deadline)?;
         let pool = PhoenixPoolClient::new(&e, &get_amm(&e)?);
         // Query the pool for the share token address
-        let pool_info = pool.query_pool_info();
+        let pool_info = pool.try_query_pool_info()
+            .map_err(pool_err)?
+            .map_err(|_| AdapterError::ExternalFailure)?;
         let share_token_addr = pool_info.asset_lp_share.address;
         let share_token_client = soroban_sdk::token::Client::new(&e, &share_token_addr);
         let before_lp = share_token_client.balance(&to);
-        pool.provide_liquidity(
+        pool.try_provide_liquidity(
             &to, // sender
             &Some(amt_a),
             &Some(amt_a_min), // min_a
@@ -135,7 +255,9 @@ impl AdapterTrait for PhoenixAdapter {
             &None, // custom_slippage_bps
             &Some(deadline),
             &false // auto_stake
-        );
+        )
+            .map_err(pool_err)?
+            .map_err(|_| AdapterError::ExternalFailure)?;
         let after_lp = share_token_client.balance(&to);
         let lp_minted = after_lp - before_lp;
         bump(&e);
@@ -152,19 +274,29 @@ impl AdapterTrait for PhoenixAdapter {
         deadline: u64
     ) -> Result<(i128, i128), AdapterError> {
         to.require_auth();
-        if !is_init(&e) { return Err(AdapterError::ExternalFailure); }
-        if e.ledger().timestamp() > deadline {
-            return Err(AdapterError::ExternalFailure);
-        }
-        let pool = PhoenixPoolClient::new(&e, &get_amm(&e)?);
-        let (amt_a, amt_b) = pool.withdraw_liquidity(
-            &to, // sender
+        check_live(&e, deadline)?;
+        let pool_addr = get_amm(&e)?;
+        let pool = PhoenixPoolClient::new(&e, &pool_addr);
+        let pool_info = pool.try_query_pool_info()
+            .map_err(pool_err)?
+            .map_err(|_| AdapterError::ExternalFailure)?;
+        let share_token_addr = pool_info.asset_lp_share.address;
+        // Withdraw from the adapter's balance, as for swaps, so `to` only
+        // authorizes the share transfer here
+        adapter_base::take(&e, &share_token_addr, &to, lp_amt).ok_or(AdapterError::InsufficientBalance)?;
+        adapter_base::authorize_transfer(&e, &share_token_addr, &pool_addr, lp_amt);
+        let (amt_a, amt_b) = pool.try_withdraw_liquidity(
+            &e.current_contract_address(), // sender
             &lp_amt,
             &amt_a_min, // min_a
             &amt_b_min, // min_b
             &Some(deadline),
             &None // auto_unstake
-        );
+        )
+            .map_err(pool_err)?
+            .map_err(|_| AdapterError::ExternalFailure)?;
+        adapter_base::pay(&e, &pool_info.asset_a.address, &to, amt_a);
+        adapter_base::pay(&e, &pool_info.asset_b.address, &to, amt_b);
         bump(&e);
         Ok((amt_a, amt_b))
     }
@@ -177,10 +309,15 @@ impl AdapterTrait for PhoenixAdapter {
         if amount_in <= 0 {
             return Err(AdapterError::InvalidAmount);
         }
+        if pool_address != get_amm(&e)? {
+            return Err(AdapterError::PoolNotFound);
+        }
         let pool = PhoenixPoolClient::new(&e, &pool_address);
-        let offer_asset = token_in.clone();
-        let resp = pool.simulate_swap(&offer_asset, &amount_in);
-        Ok(resp.total_return)
+        let resp = pool.try_simulate_swap(&token_in, &amount_in)
+            .map_err(pool_err)?
+            .map_err(|_| AdapterError::ExternalFailure)?;
+        // `total_return` adds back commission and spread; the trader gets `ask_amount`
+        Ok(resp.ask_amount)
     }
 
     fn quote_out(e: Env, pool_address: Address, amount_out: i128, token_in: Address, token_out: Address) -> Result<i128, AdapterError> {
@@ -190,9 +327,78 @@ impl AdapterTrait for PhoenixAdapter {
         if amount_out <= 0 {
             return Err(AdapterError::InvalidAmount);
         }
+        if pool_address != get_amm(&e)? {
+            return Err(AdapterError::PoolNotFound);
+        }
         let pool = PhoenixPoolClient::new(&e, &pool_address);
-        let ask_asset = token_out.clone();
-        let resp = pool.simulate_reverse_swap(&ask_asset, &amount_out);
+        let resp = pool.try_simulate_reverse_swap(&token_out, &amount_out)
+            .map_err(pool_err)?
+            .map_err(|_| AdapterError::ExternalFailure)?;
         Ok(resp.offer_amount)
     }
+
+    fn get_spot_price(e: Env, pool: Address, token_in: Address, token_out: Address) -> Result<i128, AdapterError> {
+        if !is_init(&e) {
+            return Err(AdapterError::NotInitialized);
+        }
+        if pool != get_amm(&e)? {
+            return Err(AdapterError::PoolNotFound);
+        }
+        let client = PhoenixPoolClient::new(&e, &pool);
+        let info = client.try_query_pool_info()
+            .map_err(pool_err)?
+            .map_err(|_| AdapterError::ExternalFailure)?;
+        let (a, b) = (info.asset_a, info.asset_b);
+        // The pool is constant product, so spot is the reserve ratio
+        let (reserve_in, reserve_out) = if (a.address.clone(), b.address.clone()) == (token_in.clone(), token_out.clone()) {
+            (a.amount, b.amount)
+        } else if (a.address, b.address) == (token_out, token_in) {
+            (b.amount, a.amount)
+        } else {
+            return Err(AdapterError::UnsupportedPair);
+        };
+        if reserve_in <= 0 || reserve_out <= 0 {
+            return Err(AdapterError::InsufficientLiquidity);
+        }
+        Ok(reserve_out * PRICE_SCALE / reserve_in)
+    }
+
+    /* ---------- discovery ---------- */
+    fn get_pool_info(e: Env, pool: Address) -> Result<PoolInfo, AdapterError> {
+        if !is_init(&e) {
+            return Err(AdapterError::NotInitialized);
+        }
+        // The adapter trades a single Phoenix pool
+        if pool != get_amm(&e)? {
+            return Err(AdapterError::PoolNotFound);
+        }
+        let client = PhoenixPoolClient::new(&e, &pool);
+        let config = client.try_query_config()
+            .map_err(pool_err)?
+            .map_err(|_| AdapterError::ExternalFailure)?;
+        let info = client.try_query_pool_info()
+            .map_err(pool_err)?
+            .map_err(|_| AdapterError::ExternalFailure)?;
+        Ok(PoolInfo {
+            pool,
+            lp_token: config.share_token,
+            tokens: vec![&e, info.asset_a.address, info.asset_b.address],
+            reserves: vec![&e, info.asset_a.amount, info.asset_b.amount],
+            weights: Vec::new(&e),
+            fee_bps: config.total_fee_bps as u32,
+            kind: PoolKind::ConstantProduct,
+            lp_supply: info.asset_lp_share.amount,
+        })
+    }
+
+    fn supports(_e: Env, capability: Capability) -> bool {
+        matches!(capability, Capability::ExactOut)
+    }
+
+    fn list_pools(e: Env, start: u32, limit: u32) -> Vec<Address> {
+        match get_amm(&e) {
+            Ok(pool) => adapter_base::page(&vec![&e, pool], start, limit),
+            Err(_) => Vec::new(&e),
+        }
+    }
 }
@@ -1,3 +1,4 @@
+#[allow(clippy::too_many_arguments)]
 pub mod phoenix_pair {
     soroban_sdk::contractimport!(
         file = "../../bytecodes/phoenix_pool.wasm"
@@ -6,24 +6,257 @@ mod storage;
 
 #[allow(unused_imports)]
 use event::*;
-use hoops_adapter_interface::{AdapterError, AdapterTrait};
+use hoops_adapter_interface::{AdapterError, AdapterTrait, Capability, PoolInfo, PoolKind, PRICE_SCALE};
+use hoops_common::adapter_base;
+use protocol::soroswap_factory::SoroswapFactoryClient;
 use protocol::soroswap_pair::SoroswapPairClient;
-use protocol::soroswap_router::SoroswapRouterClient;
-use soroban_sdk::{contract, contractimpl, Address, BytesN, Env, Vec};
+use protocol::soroswap_router::{CombinedRouterError, SoroswapRouterClient};
+use soroban_sdk::{contract, contractimpl, vec, Address, BytesN, Env, InvokeError, Vec};
 use storage::*;
 
 const PROTOCOL_ID: i128 = 3;
+/// Soroswap pairs charge a flat 0.3% on the input.
+const FEE_BPS: u32 = 30;
 
 #[contract]
 pub struct SoroswapAdapter;
 
+hoops_common::adapter_admin!(SoroswapAdapter, AdapterError);
+
+pub trait SoroswapAdapterTrait {
+    /// Output of `swap_exact_in` along `path` (router `router_get_amounts_out`).
+    fn quote_path_in(e: Env, path: Vec<Address>, amt_in: i128) -> Result<i128, AdapterError>;
+    /// Input `swap_exact_out` needs along `path` to receive `out`
+    /// (router `router_get_amounts_in`).
+    fn quote_path_out(e: Env, path: Vec<Address>, out: i128) -> Result<i128, AdapterError>;
+    /// The factory's pair for `a`/`b`, if it has been created.
+    fn get_pair(e: Env, a: Address, b: Address) -> Result<Option<Address>, AdapterError>;
+}
+
+fn check_live(e: &Env, deadline: u64) -> Result<(), AdapterError> {
+    if !is_init(e) {
+        return Err(AdapterError::NotInitialized);
+    }
+    if e.ledger().timestamp() > deadline {
+        return Err(AdapterError::DeadlinePassed);
+    }
+    Ok(())
+}
+
+/// Map a Soroswap router failure onto the adapter's error set. Errors the
+/// router does not declare (pair or token panics) stay `ExternalFailure`.
+fn router_err(err: Result<CombinedRouterError, InvokeError>) -> AdapterError {
+    match err {
+        Ok(CombinedRouterError::RouterDeadlineExpired) => AdapterError::DeadlinePassed,
+        Ok(CombinedRouterError::RouterNegativeNotAllowed) => AdapterError::InvalidAmount,
+        Ok(CombinedRouterError::RouterInsufficientAAmount)
+        | Ok(CombinedRouterError::RouterInsufficientBAmount)
+        | Ok(CombinedRouterError::RouterInsufficientOutputAmount)
+        | Ok(CombinedRouterError::RouterExcessiveInputAmount) => AdapterError::MinAmountNotMet,
+        Ok(CombinedRouterError::RouterPairDoesNotExist) => AdapterError::PairNotFound,
+        Ok(CombinedRouterError::LibraryInvalidPath)
+        | Ok(CombinedRouterError::LibrarySortIdenticalTokens) => AdapterError::InvalidPath,
+        Ok(CombinedRouterError::LibraryInsufficientLiquidity) => AdapterError::InsufficientLiquidity,
+        Ok(CombinedRouterError::LibraryInsufficientAmount)
+        | Ok(CombinedRouterError::LibraryInsufficientInputAmount)
+        | Ok(CombinedRouterError::LibraryInsufficientOutputAmount) => AdapterError::InvalidAmount,
+        _ => AdapterError::ExternalFailure,
+    }
+}
+
+fn amounts_out(e: &Env, path: &Vec<Address>, amt_in: i128) -> Result<i128, AdapterError> {
+    if path.len() < 2 {
+        return Err(AdapterError::InvalidPath);
+    }
+    let router = SoroswapRouterClient::new(e, &get_amm(e)?);
+    let amounts = router
+        .try_router_get_amounts_out(&amt_in, path)
+        .map_err(router_err)?
+        .map_err(|_| AdapterError::ExternalFailure)?;
+    amounts.last().ok_or(AdapterError::ExternalFailure)
+}
+
+fn amounts_in(e: &Env, path: &Vec<Address>, out: i128) -> Result<i128, AdapterError> {
+    if path.len() < 2 {
+        return Err(AdapterError::InvalidPath);
+    }
+    let router = SoroswapRouterClient::new(e, &get_amm(e)?);
+    let amounts = router
+        .try_router_get_amounts_in(&out, path)
+        .map_err(router_err)?
+        .map_err(|_| AdapterError::ExternalFailure)?;
+    amounts.first().ok_or(AdapterError::ExternalFailure)
+}
+
+fn factory<'a>(e: &'a Env, router: &SoroswapRouterClient) -> Result<SoroswapFactoryClient<'a>, AdapterError> {
+    let addr = router
+        .try_get_factory()
+        .map_err(|_| AdapterError::ExternalFailure)?
+        .map_err(|_| AdapterError::ExternalFailure)?;
+    Ok(SoroswapFactoryClient::new(e, &addr))
+}
+
+/// Check `pool` is the pair the router's factory derives for `a`/`b`.
+fn verify_pair(router: &SoroswapRouterClient, pool: &Address, a: &Address, b: &Address) -> Result<(), AdapterError> {
+    match router.try_router_pair_for(a, b) {
+        Ok(Ok(pair)) if pair == *pool => Ok(()),
+        _ => Err(AdapterError::PairNotFound),
+    }
+}
+
+/// Underlying tokens of `lp`, after checking it is a pair of the configured
+/// factory.
+fn pair_tokens(e: &Env, router: &SoroswapRouterClient, lp: &Address) -> Result<(Address, Address), AdapterError> {
+    let pair = SoroswapPairClient::new(e, lp);
+    let token_0 = match pair.try_token_0() {
+        Ok(Ok(t)) => t,
+        _ => return Err(AdapterError::PairNotFound),
+    };
+    let token_1 = match pair.try_token_1() {
+        Ok(Ok(t)) => t,
+        _ => return Err(AdapterError::PairNotFound),
+    };
+    verify_pair(router, lp, &token_0, &token_1)?;
+    Ok((token_0, token_1))
+}
+
+/// Exact-in swap along `path` through the Soroswap router, from the
+/// adapter's balance; pays the output to `to`.
+fn swap_in(e: &Env, amt_in: i128, min_out: i128, path: Vec<Address>, to: Address, deadline: u64) -> Result<i128, AdapterError> {
+    if path.len() < 2 {
+        return Err(AdapterError::InvalidPath);
+    }
+    let router = SoroswapRouterClient::new(e, &get_amm(e)?);
+    let (token_in, token_out) = (path.get_unchecked(0), path.get_unchecked(path.len() - 1));
+    let first_pair = router
+        .try_router_pair_for(&token_in, &path.get_unchecked(1))
+        .map_err(|_| AdapterError::PairNotFound)?
+        .map_err(|_| AdapterError::PairNotFound)?;
+    // Trade from the adapter's balance; the router moves the input to
+    // the first pair and pays the output back here
+    adapter_base::take(e, &token_in, &to, amt_in).ok_or(AdapterError::InsufficientBalance)?;
+    adapter_base::authorize_transfer(e, &token_in, &first_pair, amt_in);
+    let amounts = router
+        .try_swap_exact_tokens_for_tokens(&amt_in, &min_out, &path, &e.current_contract_address(), &deadline)
+        .map_err(router_err)?
+        .map_err(|_| AdapterError::ExternalFailure)?;
+
+    // Extract the final output amount (last element in the amounts vector)
+    let amt_out = amounts
+        .get(amounts.len() - 1)
+        .ok_or(AdapterError::ExternalFailure)?;
+    adapter_base::pay(e, &token_out, &to, amt_out);
+
+    swap(
+        e,
+        SwapEvent {
+            amt_in,
+            amt_out,
+            path,
+            to,
+        },
+    );
+    bump(e);
+    Ok(amt_out)
+}
+
+/// Exact-out swap along `path`. Takes `max_in` from `to`, pays the output and
+/// the unused input back to `to`.
+fn swap_out(e: &Env, out: i128, max_in: i128, path: Vec<Address>, to: Address, deadline: u64) -> Result<i128, AdapterError> {
+    if path.len() < 2 {
+        return Err(AdapterError::InvalidPath);
+    }
+
+    let router = SoroswapRouterClient::new(e, &get_amm(e)?);
+    let (token_in, token_out) = (path.get_unchecked(0), path.get_unchecked(path.len() - 1));
+    let first_pair = router
+        .try_router_pair_for(&token_in, &path.get_unchecked(1))
+        .map_err(|_| AdapterError::PairNotFound)?
+        .map_err(|_| AdapterError::PairNotFound)?;
+    // The router moves exactly the quoted input to the first pair
+    let needed = amounts_in(e, &path, out)?;
+    if needed > max_in {
+        return Err(AdapterError::MinAmountNotMet);
+    }
+    adapter_base::take(e, &token_in, &to, max_in).ok_or(AdapterError::InsufficientBalance)?;
+    adapter_base::authorize_transfer(e, &token_in, &first_pair, needed);
+    let amounts = router
+        .try_swap_tokens_for_exact_tokens(&out, &max_in, &path, &e.current_contract_address(), &deadline)
+        .map_err(router_err)?
+        .map_err(|_| AdapterError::ExternalFailure)?;
+
+    // Extract the input amount (first element in the amounts vector)
+    let amt_in = amounts.get(0).ok_or(AdapterError::ExternalFailure)?;
+    adapter_base::pay(e, &token_out, &to, out);
+    adapter_base::pay(e, &token_in, &to, max_in - amt_in);
+
+    swap(
+        e,
+        SwapEvent {
+            amt_in,
+            amt_out: out,
+            path,
+            to,
+        },
+    );
+    bump(e);
+    Ok(amt_in)
+}
+
+/// Two-token `path` traded on `pool`, which must be the factory's pair for it.
+fn check_pair_path(e: &Env, pool: &Address, path: &Vec<Address>) -> Result<(), AdapterError> {
+    if path.len() != 2 {
+        return Err(if path.len() < 2 { AdapterError::InvalidPath } else { AdapterError::MultipathUnsupported });
+    }
+    let router = SoroswapRouterClient::new(e, &get_amm(e)?);
+    verify_pair(&router, pool, &path.get_unchecked(0), &path.get_unchecked(1))
+}
+
+#[contractimpl]
+impl SoroswapAdapterTrait for SoroswapAdapter {
+    fn quote_path_in(e: Env, path: Vec<Address>, amt_in: i128) -> Result<i128, AdapterError> {
+        if !is_init(&e) {
+            return Err(AdapterError::NotInitialized);
+        }
+        if amt_in <= 0 {
+            return Err(AdapterError::InvalidAmount);
+        }
+        amounts_out(&e, &path, amt_in)
+    }
+
+    fn quote_path_out(e: Env, path: Vec<Address>, out: i128) -> Result<i128, AdapterError> {
+        if !is_init(&e) {
+            return Err(AdapterError::NotInitialized);
+        }
+        if out <= 0 {
+            return Err(AdapterError::InvalidAmount);
+        }
+        amounts_in(&e, &path, out)
+    }
+
+    fn get_pair(e: Env, a: Address, b: Address) -> Result<Option<Address>, AdapterError> {
+        if !is_init(&e) {
+            return Err(AdapterError::NotInitialized);
+        }
+        let router = SoroswapRouterClient::new(&e, &get_amm(&e)?);
+        let factory = factory(&e, &router)?;
+        match factory.try_pair_exists(&a, &b) {
+            Ok(Ok(true)) => factory
+                .try_get_pair(&a, &b)
+                .map(|r| r.ok())
+                .map_err(|_| AdapterError::ExternalFailure),
+            Ok(Ok(false)) => Ok(None),
+            _ => Err(AdapterError::ExternalFailure),
+        }
+    }
+}
+
 #[contractimpl]
 impl AdapterTrait for SoroswapAdapter {
     fn upgrade(e: Env, new_wasm_hash: BytesN<32>) -> Result<(), AdapterError> {
-        // setup the config to get the admin address. set it during initialization.
-        let config = get_core_config(&e);
-        config.admin.require_auth();
-        Ok(e.deployer().update_current_contract_wasm(new_wasm_hash))
+        adapter_base::require_admin(&e).ok_or(AdapterError::NotInitialized)?;
+        e.deployer().update_current_contract_wasm(new_wasm_hash);
+        Ok(())
     }
 
     fn version() -> u32 {
@@ -31,7 +264,13 @@ impl AdapterTrait for SoroswapAdapter {
     }
 
     /* ---------- lifecycle ---------- */
-    fn initialize(e: Env, amm_id: i128, amm_addr: Address) -> Result<(), AdapterError> {
+    fn initialize(
+        e: Env,
+        amm_id: i128,
+        amm_addr: Address,
+        admin: Address,
+        usdc: Address,
+    ) -> Result<(), AdapterError> {
         if is_init(&e) {
             return Err(AdapterError::AlreadyInitialized);
         }
@@ -39,10 +278,10 @@ impl AdapterTrait for SoroswapAdapter {
             return Err(AdapterError::InvalidID);
         }
 
+        adapter_base::init_core_config(&e, &admin, &usdc);
         set_amm(&e, amm_addr.clone());
         mark_init(&e);
         bump(&e);
-        
         init(&e, amm_addr);
         Ok(())
     }
@@ -57,33 +296,8 @@ impl AdapterTrait for SoroswapAdapter {
         deadline: u64,
     ) -> Result<i128, AdapterError> {
         to.require_auth();
-        if !is_init(&e) {
-            return Err(AdapterError::ExternalFailure);
-        }
-        if e.ledger().timestamp() > deadline {
-            return Err(AdapterError::ExternalFailure);
-        }
-        // Call external router
-        let router = SoroswapRouterClient::new(&e, &get_amm(&e)?);
-        let amounts = router
-            .swap_exact_tokens_for_tokens(&amt_in, &min_out, &path, &to, &deadline);
-
-        // Extract the final output amount (last element in the amounts vector)
-        let amt_out = amounts
-            .get(amounts.len() - 1)
-            .ok_or(AdapterError::ExternalFailure)?;
-
-        swap(
-            &e,
-            SwapEvent {
-                amt_in,
-                amt_out,
-                path,
-                to,
-            },
-        );
-        bump(&e);
-        Ok(amt_out)
+        check_live(&e, deadline)?;
+        swap_in(&e, amt_in, min_out, path, to, deadline)
     }
 
     fn swap_exact_out(
@@ -95,36 +309,43 @@ impl AdapterTrait for SoroswapAdapter {
         deadline: u64,
     ) -> Result<i128, AdapterError> {
         to.require_auth();
-        if !is_init(&e) {
-            return Err(AdapterError::ExternalFailure);
-        }
-        if e.ledger().timestamp() > deadline {
-            return Err(AdapterError::ExternalFailure);
-        }
+        check_live(&e, deadline)?;
+        swap_out(&e, out, max_in, path, to, deadline)
+    }
 
-        // Call external router
-        let router = SoroswapRouterClient::new(&e, &get_amm(&e)?);
-        let amounts = router
-            .swap_tokens_for_exact_tokens(&out, &max_in, &path, &to, &deadline);
-
-        // Extract the input amount (first element in the amounts vector)
-        let amt_in = amounts.get(0).ok_or(AdapterError::ExternalFailure)?;
-
-        swap(
-            &e,
-            SwapEvent {
-                amt_in,
-                amt_out: out,
-                path,
-                to,
-            },
-        );
-        bump(&e);
-        Ok(amt_in)
+    fn swap_exact_in_on(
+        e: Env,
+        pool: Address,
+        amt_in: i128,
+        min_out: i128,
+        path: Vec<Address>,
+        to: Address,
+        deadline: u64,
+    ) -> Result<i128, AdapterError> {
+        to.require_auth();
+        check_live(&e, deadline)?;
+        check_pair_path(&e, &pool, &path)?;
+        swap_in(&e, amt_in, min_out, path, to, deadline)
+    }
+
+    fn swap_exact_out_on(
+        e: Env,
+        pool: Address,
+        out: i128,
+        max_in: i128,
+        path: Vec<Address>,
+        to: Address,
+        deadline: u64,
+    ) -> Result<i128, AdapterError> {
+        to.require_auth();
+        check_live(&e, deadline)?;
+        check_pair_path(&e, &pool, &path)?;
+        swap_out(&e, out, max_in, path, to, deadline)
     }
 
     /* ---------- liquidity ---------- */
     #[allow(unused_variables)]
+    #[allow(clippy::too_many_arguments)]
     fn add_liquidity(
         e: Env,
         a: Address,
@@ -137,16 +358,13 @@ impl AdapterTrait for SoroswapAdapter {
         deadline: u64,
     ) -> Result<(i128, i128, i128), AdapterError> {
         to.require_auth();
-        if !is_init(&e) {
-            return Err(AdapterError::ExternalFailure);
-        }
-        if e.ledger().timestamp() > deadline {
-            return Err(AdapterError::ExternalFailure);
-        }
-        // Call external router to add liquidity
+        check_live(&e, deadline)?;
+        // The Soroswap router creates the pair if it does not exist yet
         let router = SoroswapRouterClient::new(&e, &get_amm(&e)?);
         let (amount_a, amount_b, liquidity) = router
-            .add_liquidity(&a, &b, &amt_a, &amt_b, &amt_a_min, &amt_b_min, &to, &deadline);
+            .try_add_liquidity(&a, &b, &amt_a, &amt_b, &amt_a_min, &amt_b_min, &to, &deadline)
+            .map_err(router_err)?
+            .map_err(|_| AdapterError::ExternalFailure)?;
         bump(&e);
         Ok((amount_a, amount_b, liquidity))
     }
@@ -162,20 +380,21 @@ impl AdapterTrait for SoroswapAdapter {
         deadline: u64,
     ) -> Result<(i128, i128), AdapterError> {
         to.require_auth();
-        if !is_init(&e) {
-            return Err(AdapterError::ExternalFailure);
-        }
-        if e.ledger().timestamp() > deadline {
-            return Err(AdapterError::ExternalFailure);
-        }
-        // Get the underlying tokens from the LP token (pair contract)
-        let pair_client = SoroswapPairClient::new(&e, &lp);
-        let token_a = pair_client.token_0();
-        let token_b = pair_client.token_1();
-        // Call external router
+        check_live(&e, deadline)?;
+        // The LP token must be a pair of the router's factory
         let router = SoroswapRouterClient::new(&e, &get_amm(&e)?);
+        let (token_a, token_b) = pair_tokens(&e, &router, &lp)?;
+        // Redeem from the adapter's balance, as for swaps, so `to` only
+        // authorizes the LP transfer here; the router sends the LP on to
+        // the pair itself
+        adapter_base::take(&e, &lp, &to, lp_amt).ok_or(AdapterError::InsufficientBalance)?;
+        adapter_base::authorize_transfer(&e, &lp, &lp, lp_amt);
         let (amt_a, amt_b) = router
-            .remove_liquidity(&token_a, &token_b, &lp_amt, &amt_a_min, &amt_b_min, &to, &deadline);
+            .try_remove_liquidity(&token_a, &token_b, &lp_amt, &amt_a_min, &amt_b_min, &e.current_contract_address(), &deadline)
+            .map_err(router_err)?
+            .map_err(|_| AdapterError::ExternalFailure)?;
+        adapter_base::pay(&e, &token_a, &to, amt_a);
+        adapter_base::pay(&e, &token_b, &to, amt_b);
         bump(&e);
         Ok((amt_a, amt_b))
     }
@@ -188,24 +407,9 @@ impl AdapterTrait for SoroswapAdapter {
         if amount_in <= 0 {
             return Err(AdapterError::InvalidAmount);
         }
-        let pair = protocol::soroswap_pair::SoroswapPairClient::new(&e, &pool_address);
-        let (reserve_0, reserve_1) = pair.get_reserves();
-        // Canonical token order
-        let (reserve_in, reserve_out) = if token_in < token_out {
-            (reserve_0 as u128, reserve_1 as u128)
-        } else {
-            (reserve_1 as u128, reserve_0 as u128)
-        };
-        let amount_in_u128 = amount_in as u128;
-        if reserve_in == 0 || reserve_out == 0 {
-            return Ok(0);
-        }
-        // Apply 0.3% fee: amount_in_with_fee = amount_in * 997 / 1000
-        let amount_in_with_fee = amount_in_u128 * 997;
-        let numerator = amount_in_with_fee * reserve_out;
-        let denominator = reserve_in * 1000 + amount_in_with_fee;
-        let amount_out = numerator / denominator;
-        Ok(amount_out as i128)
+        let router = SoroswapRouterClient::new(&e, &get_amm(&e)?);
+        verify_pair(&router, &pool_address, &token_in, &token_out)?;
+        amounts_out(&e, &vec![&e, token_in, token_out], amount_in)
     }
 
     fn quote_out(e: Env, pool_address: Address, amount_out: i128, token_in: Address, token_out: Address) -> Result<i128, AdapterError> {
@@ -215,21 +419,73 @@ impl AdapterTrait for SoroswapAdapter {
         if amount_out <= 0 {
             return Err(AdapterError::InvalidAmount);
         }
-        let pair = protocol::soroswap_pair::SoroswapPairClient::new(&e, &pool_address);
-        let (reserve_0, reserve_1) = pair.get_reserves();
-        let (reserve_in, reserve_out) = if token_in < token_out {
-            (reserve_0 as u128, reserve_1 as u128)
-        } else {
-            (reserve_1 as u128, reserve_0 as u128)
-        };
-        let amount_out_u128 = amount_out as u128;
-        if reserve_in == 0 || reserve_out == 0 || reserve_out <= amount_out_u128 {
+        let router = SoroswapRouterClient::new(&e, &get_amm(&e)?);
+        verify_pair(&router, &pool_address, &token_in, &token_out)?;
+        amounts_in(&e, &vec![&e, token_in, token_out], amount_out)
+    }
+
+    fn get_spot_price(e: Env, pool: Address, token_in: Address, token_out: Address) -> Result<i128, AdapterError> {
+        if !is_init(&e) {
+            return Err(AdapterError::NotInitialized);
+        }
+        let router = SoroswapRouterClient::new(&e, &get_amm(&e)?);
+        let (token_0, token_1) = pair_tokens(&e, &router, &pool)?;
+        if !(token_in == token_0 && token_out == token_1 || token_in == token_1 && token_out == token_0) {
+            return Err(AdapterError::PairNotFound);
+        }
+        let (reserve_0, reserve_1) = SoroswapPairClient::new(&e, &pool)
+            .try_get_reserves()
+            .map_err(|_| AdapterError::ExternalFailure)?
+            .map_err(|_| AdapterError::ExternalFailure)?;
+        let (reserve_in, reserve_out) = if token_in == token_0 { (reserve_0, reserve_1) } else { (reserve_1, reserve_0) };
+        if reserve_in <= 0 || reserve_out <= 0 {
             return Err(AdapterError::InsufficientLiquidity);
         }
-        // Reverse formula for required input, including fee
-        let numerator = reserve_in * amount_out_u128 * 1000;
-        let denominator = (reserve_out - amount_out_u128) * 997;
-        let amount_in = numerator / denominator + 1;
-        Ok(amount_in as i128)
+        Ok(reserve_out * PRICE_SCALE / reserve_in)
+    }
+
+    /* ---------- discovery ---------- */
+    fn get_pool_info(e: Env, pool: Address) -> Result<PoolInfo, AdapterError> {
+        if !is_init(&e) {
+            return Err(AdapterError::NotInitialized);
+        }
+        let router = SoroswapRouterClient::new(&e, &get_amm(&e)?);
+        let (token_0, token_1) = pair_tokens(&e, &router, &pool)?;
+        let pair = SoroswapPairClient::new(&e, &pool);
+        let (reserve_0, reserve_1) = pair
+            .try_get_reserves()
+            .map_err(|_| AdapterError::ExternalFailure)?
+            .map_err(|_| AdapterError::ExternalFailure)?;
+        Ok(PoolInfo {
+            pool: pool.clone(),
+            lp_token: pool,
+            tokens: vec![&e, token_0, token_1],
+            reserves: vec![&e, reserve_0, reserve_1],
+            weights: Vec::new(&e),
+            fee_bps: FEE_BPS,
+            kind: PoolKind::ConstantProduct,
+            lp_supply: pair.total_supply(),
+        })
+    }
+
+    fn supports(_e: Env, capability: Capability) -> bool {
+        matches!(capability, Capability::ExactOut | Capability::MultiHop)
+    }
+
+    fn list_pools(e: Env, start: u32, limit: u32) -> Vec<Address> {
+        let mut pools = Vec::new(&e);
+        let Ok(amm) = get_amm(&e) else { return pools; };
+        let Ok(factory) = factory(&e, &SoroswapRouterClient::new(&e, &amm)) else { return pools; };
+        let count = match factory.try_all_pairs_length() {
+            Ok(Ok(n)) => n,
+            _ => return pools,
+        };
+        // One cross-contract call per pair, so only the requested page
+        for n in start.min(count)..start.saturating_add(limit).min(count) {
+            if let Ok(Ok(pair)) = factory.try_all_pairs(&n) {
+                pools.push_back(pair);
+            }
+        }
+        pools
     }
 }
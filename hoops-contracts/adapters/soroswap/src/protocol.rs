@@ -1,3 +1,4 @@
+#[allow(clippy::too_many_arguments)]
 pub mod soroswap_router {
 soroban_sdk::contractimport!(
     file = "../../bytecodes/soroswap_router.wasm"
@@ -5,9 +6,18 @@ soroban_sdk::contractimport!(
 pub type SoroswapRouterClient<'a> = Client<'a>;
 } 
 
+#[allow(clippy::too_many_arguments)]
 pub mod soroswap_pair {
     soroban_sdk::contractimport!(
     file = "../../bytecodes/soroswap_pair.wasm"
 );
 pub type SoroswapPairClient<'a> = Client<'a>;
 }
+
+#[allow(clippy::too_many_arguments)]
+pub mod soroswap_factory {
+    soroban_sdk::contractimport!(
+    file = "../../bytecodes/soroswap_factory.wasm"
+);
+pub type SoroswapFactoryClient<'a> = Client<'a>;
+}
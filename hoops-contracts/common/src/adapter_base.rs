@@ -0,0 +1,261 @@
+//! Admin configuration, TTL handling and custody helpers shared by every AMM
+//! adapter.
+//!
+//! Adapters keep their protocol specific state (AMM address, pool maps) in
+//! their own `storage.rs`; everything that is identical across protocols
+//! lives here so the admin/upgrade path behaves the same everywhere.
+use soroban_sdk::auth::{ContractContext, InvokerContractAuthEntry, SubContractInvocation};
+use soroban_sdk::{contracttype, symbol_short, token, vec, Address, Env, IntoVal, Symbol, Val, Vec};
+
+pub const KEY_CORE_CONFIG: Symbol = symbol_short!("CONFIG");
+pub const KEY_ROUTER: Symbol = symbol_short!("ROUTER");
+
+pub const DAY_LEDGER: u32 = 17_280;
+/// Default instance TTL extension (~60 days).
+pub const DEFAULT_TTL_BUMP: u32 = 60 * DAY_LEDGER;
+/// Default threshold below which the instance TTL gets extended.
+pub const DEFAULT_TTL_THRESH: u32 = DEFAULT_TTL_BUMP - DAY_LEDGER;
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CoreConfig {
+    pub admin: Address,
+    pub usdc: Address,
+    pub ttl_thresh: u32,
+    pub ttl_bump: u32,
+}
+
+impl CoreConfig {
+    pub fn new(admin: Address, usdc: Address) -> Self {
+        CoreConfig {
+            admin,
+            usdc,
+            ttl_thresh: DEFAULT_TTL_THRESH,
+            ttl_bump: DEFAULT_TTL_BUMP,
+        }
+    }
+}
+
+pub fn has_core_config(e: &Env) -> bool {
+    e.storage().instance().has(&KEY_CORE_CONFIG)
+}
+pub fn set_core_config(e: &Env, config: &CoreConfig) {
+    e.storage().instance().set(&KEY_CORE_CONFIG, config);
+}
+pub fn get_core_config(e: &Env) -> Option<CoreConfig> {
+    e.storage().instance().get(&KEY_CORE_CONFIG)
+}
+/// Applies `f` to the stored config. Returns `None` if the adapter was never
+/// configured.
+pub fn update_core_config<F: FnOnce(&mut CoreConfig)>(e: &Env, f: F) -> Option<CoreConfig> {
+    let mut config = get_core_config(e)?;
+    f(&mut config);
+    set_core_config(e, &config);
+    Some(config)
+}
+
+/// Stores the initial config. The admin must sign the initialize call so a
+/// front-runner cannot claim the adapter.
+pub fn init_core_config(e: &Env, admin: &Address, usdc: &Address) {
+    admin.require_auth();
+    set_core_config(e, &CoreConfig::new(admin.clone(), usdc.clone()));
+}
+
+/// Requires the configured admin's auth and returns it.
+pub fn require_admin(e: &Env) -> Option<Address> {
+    let config = get_core_config(e)?;
+    config.admin.require_auth();
+    Some(config.admin)
+}
+
+pub fn set_admin(e: &Env, new_admin: &Address) -> Option<CoreConfig> {
+    require_admin(e)?;
+    update_core_config(e, |c| c.admin = new_admin.clone())
+}
+
+pub fn set_usdc(e: &Env, usdc: &Address) -> Option<CoreConfig> {
+    require_admin(e)?;
+    update_core_config(e, |c| c.usdc = usdc.clone())
+}
+
+/// Registers the Hoops router, which may manage pool mappings alongside the
+/// admin.
+pub fn set_router(e: &Env, router: &Address) -> Option<()> {
+    require_admin(e)?;
+    e.storage().instance().set(&KEY_ROUTER, router);
+    Some(())
+}
+pub fn get_router(e: &Env) -> Option<Address> {
+    e.storage().instance().get(&KEY_ROUTER)
+}
+
+/// Returns `false` if `caller` is neither the admin nor the registered
+/// router; otherwise requires `caller`'s auth and returns `true`.
+pub fn require_admin_or_router(e: &Env, caller: &Address) -> bool {
+    let is_admin = get_core_config(e).is_some_and(|c| c.admin == *caller);
+    let is_router = get_router(e).is_some_and(|r| r == *caller);
+    if !is_admin && !is_router {
+        return false;
+    }
+    caller.require_auth();
+    true
+}
+
+/// True if `tokens` is a pair of distinct addresses that are both listed in
+/// `pool_tokens` (as returned by the pool's `get_tokens`).
+pub fn pool_has_tokens(tokens: &Vec<Address>, pool_tokens: &Vec<Address>) -> bool {
+    if tokens.len() != 2 || tokens.get_unchecked(0) == tokens.get_unchecked(1) {
+        return false;
+    }
+    tokens.iter().all(|t| pool_tokens.contains(&t))
+}
+
+/// Like [`pool_has_tokens`], but for a set of two or more distinct tokens
+/// (multi-token pools).
+pub fn pool_has_token_set(tokens: &Vec<Address>, pool_tokens: &Vec<Address>) -> bool {
+    if tokens.len() < 2 {
+        return false;
+    }
+    for (i, t) in tokens.iter().enumerate() {
+        if tokens.first_index_of(&t) != Some(i as u32) || !pool_tokens.contains(&t) {
+            return false;
+        }
+    }
+    true
+}
+
+/// Items `start..start + limit` of `items`; empty past the end.
+pub fn page(items: &Vec<Address>, start: u32, limit: u32) -> Vec<Address> {
+    let end = start.saturating_add(limit).min(items.len());
+    items.slice(start.min(end)..end)
+}
+
+/// `ttl_thresh` must be non-zero and not above `ttl_bump`, and `ttl_bump`
+/// must fit the network's max TTL.
+pub fn valid_ttl(e: &Env, ttl_thresh: u32, ttl_bump: u32) -> bool {
+    ttl_thresh > 0 && ttl_thresh <= ttl_bump && ttl_bump <= e.storage().max_ttl()
+}
+
+pub fn set_ttl(e: &Env, ttl_thresh: u32, ttl_bump: u32) -> Option<CoreConfig> {
+    require_admin(e)?;
+    update_core_config(e, |c| {
+        c.ttl_thresh = ttl_thresh;
+        c.ttl_bump = ttl_bump;
+    })
+}
+
+/// Extends the instance TTL using the configured thresholds, falling back to
+/// the defaults before the adapter is configured.
+pub fn bump(e: &Env) {
+    let (thresh, bump) = match get_core_config(e) {
+        Some(c) => (c.ttl_thresh, c.ttl_bump),
+        None => (DEFAULT_TTL_THRESH, DEFAULT_TTL_BUMP),
+    };
+    e.storage().instance().extend_ttl(thresh, bump);
+}
+
+/* ---------- custody ----------
+ * Adapters trade from their own balance: the input is taken from the
+ * caller, the protocol pulls it from the adapter and the output is paid out
+ * by the adapter. A caller (the router included) then authorizes a single
+ * transfer to the adapter, whatever the protocol does underneath.
+ */
+
+/// Moves `amount` of `token` from `from` to the adapter. `None` if the
+/// transfer fails (balance or authorization).
+pub fn take(e: &Env, token: &Address, from: &Address, amount: i128) -> Option<()> {
+    match token::Client::new(e, token).try_transfer(from, &e.current_contract_address(), &amount) {
+        Ok(Ok(())) => Some(()),
+        _ => None,
+    }
+}
+
+/// Pays `amount` of `token` from the adapter to `to`; nothing for zero.
+pub fn pay(e: &Env, token: &Address, to: &Address, amount: i128) {
+    if amount > 0 {
+        token::Client::new(e, token).transfer(&e.current_contract_address(), to, &amount);
+    }
+}
+
+/// The adapter's own balance of `token`.
+pub fn held(e: &Env, token: &Address) -> i128 {
+    token::Client::new(e, token).balance(&e.current_contract_address())
+}
+
+/// Pre-authorizes `contract.fn_name(args)` when the protocol invoked next
+/// makes that call with the adapter as the authorizing address.
+pub fn authorize_call(e: &Env, contract: &Address, fn_name: &str, args: Vec<Val>) {
+    e.authorize_as_current_contract(vec![
+        e,
+        InvokerContractAuthEntry::Contract(SubContractInvocation {
+            context: ContractContext { contract: contract.clone(), fn_name: Symbol::new(e, fn_name), args },
+            sub_invocations: Vec::new(e),
+        }),
+    ]);
+}
+
+/// [`authorize_call`] for the `token.transfer(adapter, to, amount)` a
+/// protocol makes to pull its input.
+pub fn authorize_transfer(e: &Env, token: &Address, to: &Address, amount: i128) {
+    let args = (e.current_contract_address(), to.clone(), amount).into_val(e);
+    authorize_call(e, token, "transfer", args);
+}
+
+/// Implements the admin entry points every adapter exposes (`set_admin`,
+/// `set_usdc`, `set_ttl`, `set_router` and the `get_router`, `get_admin`,
+/// `get_config` views) as an extra `#[contractimpl]` block on `$contract`.
+/// `$err` is the adapter error enum; calls before `initialize` fail with
+/// `NotInitialized`, invalid TTLs with `InvalidArgument`.
+///
+/// ```ignore
+/// hoops_common::adapter_admin!(SoroswapAdapter, AdapterError);
+/// ```
+#[macro_export]
+macro_rules! adapter_admin {
+    ($contract:ident, $err:ident) => {
+        #[soroban_sdk::contractimpl]
+        impl $contract {
+            pub fn set_admin(e: soroban_sdk::Env, new_admin: soroban_sdk::Address) -> Result<(), $err> {
+                $crate::adapter_base::set_admin(&e, &new_admin).ok_or($err::NotInitialized)?;
+                $crate::adapter_base::bump(&e);
+                Ok(())
+            }
+
+            pub fn set_usdc(e: soroban_sdk::Env, usdc: soroban_sdk::Address) -> Result<(), $err> {
+                $crate::adapter_base::set_usdc(&e, &usdc).ok_or($err::NotInitialized)?;
+                $crate::adapter_base::bump(&e);
+                Ok(())
+            }
+
+            pub fn set_ttl(e: soroban_sdk::Env, ttl_thresh: u32, ttl_bump: u32) -> Result<(), $err> {
+                if !$crate::adapter_base::valid_ttl(&e, ttl_thresh, ttl_bump) {
+                    return Err($err::InvalidArgument);
+                }
+                $crate::adapter_base::set_ttl(&e, ttl_thresh, ttl_bump).ok_or($err::NotInitialized)?;
+                $crate::adapter_base::bump(&e);
+                Ok(())
+            }
+
+            pub fn set_router(e: soroban_sdk::Env, router: soroban_sdk::Address) -> Result<(), $err> {
+                $crate::adapter_base::set_router(&e, &router).ok_or($err::NotInitialized)?;
+                $crate::adapter_base::bump(&e);
+                Ok(())
+            }
+
+            /// Fails with `NotInitialized` until a router is registered.
+            pub fn get_router(e: soroban_sdk::Env) -> Result<soroban_sdk::Address, $err> {
+                $crate::adapter_base::get_router(&e).ok_or($err::NotInitialized)
+            }
+
+            pub fn get_admin(e: soroban_sdk::Env) -> Result<soroban_sdk::Address, $err> {
+                $crate::adapter_base::get_core_config(&e)
+                    .map(|c| c.admin)
+                    .ok_or($err::NotInitialized)
+            }
+
+            pub fn get_config(e: soroban_sdk::Env) -> Result<$crate::adapter_base::CoreConfig, $err> {
+                $crate::adapter_base::get_core_config(&e).ok_or($err::NotInitialized)
+            }
+        }
+    };
+}
@@ -2,8 +2,9 @@
 
 use soroban_sdk::{contractclient, contractspecfn, Address, Env, Vec, BytesN, contracterror};
 
-/// Adapter‑local error.
-#[contracterror]
+/// Adapter‑local error. Superseded by `hoops_adapter_interface::AdapterError`
+/// and kept out of the spec so contracts linking both still import cleanly.
+#[contracterror(export = false)]
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 #[repr(u32)]
 pub enum AdapterError {
@@ -12,6 +13,7 @@ pub enum AdapterError {
     ExternalFailure = 102,
 }
 
+pub mod adapter_base;
 pub mod types;
 pub struct Spec;
 
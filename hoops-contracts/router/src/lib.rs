@@ -1,16 +1,25 @@
 #![no_std]
 
-mod client;
+mod quote;
+mod rewards;
 mod storage;
 mod types;
 
-use soroban_sdk::{contract, contracterror, contractimpl, token, Address, Env, String, Vec};
+#[cfg(test)]
+mod tests;
+
+use soroban_sdk::auth::{ContractContext, InvokerContractAuthEntry, SubContractInvocation};
+use soroban_sdk::{contract, contracterror, contractimpl, panic_with_error, symbol_short, token, vec, Address, Env, IntoVal, String, Vec};
 
 use crate::storage::{
-    get_adapters, get_core_config, get_markets, set_adapters, set_core_config, set_markets,
+    get_adapters, get_core_config, get_lp_market, get_markets, get_pair_markets, put_market, set_adapters,
+    set_core_config,
+};
+use crate::rewards::RewardsAdapterClient;
+use crate::types::{
+    CoreConfig, LpPlan, MarketData, MarketQuote, QuoteKind, QuoteRequest, QuoteResult, RewardPosition, SwapQuote, SwapResult,
 };
-use crate::types::{CoreConfig, LpPlan, MarketData};
-use hoops_adapter_interface::AdapterClient;
+use hoops_adapter_interface::{AdapterClient, PoolInfo};
 /*
 pub mod adapter_interface {
     soroban_sdk::contractimport!(file = "../bytecodes/hoops_adapter_interface.wasm");
@@ -18,17 +27,6 @@ pub mod adapter_interface {
 }
 pub use adapter_interface::AdapterClient;*/
 
-pub mod soroswap_factory {
-    soroban_sdk::contractimport!(file = "../bytecodes/soroswap_factory.wasm");
-    pub type SoroswapClient<'a> = Client<'a>;
-}
-pub use soroswap_factory::SoroswapClient;
-pub mod soroswap_pair {
-    soroban_sdk::contractimport!(file = "../bytecodes/soroswap_pair.wasm");
-    pub type SoroswapPairClient<'a> = Client<'a>;
-}
-pub use soroswap_pair::SoroswapPairClient;
-
 #[contracterror]
 #[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
 #[repr(u32)]
@@ -51,6 +49,9 @@ pub enum RouterError {
     InvalidPath = 210,
     InsufficientBalance = 211,
 }
+/// Largest batch `simulate_swaps` accepts.
+pub const MAX_SIMULATE_REQUESTS: u32 = 10;
+
 pub trait HoopsRouterTrait {
     fn initialize(e: Env, admin: Address);
     fn get_version(e: Env) -> u32;
@@ -69,7 +70,62 @@ pub trait HoopsRouterTrait {
         token_in: Address,
         token_out: Address,
     ) -> Option<crate::types::SwapQuote>;
+    /// The market needing the least `token_in` to deliver exactly
+    /// `amount_out` of `token_out`, quoted through the adapters' `quote_out`.
+    fn get_best_quote_exact_out(
+        e: Env,
+        amount_out: i128,
+        token_in: Address,
+        token_out: Address,
+    ) -> Option<crate::types::SwapQuote>;
+    /// Quote every request against every market for its pair, exact-in
+    /// through the adapters' `quote_in` and exact-out through `quote_out`.
+    /// Failed quotes are kept with their error code. Results follow the
+    /// order of `requests`. Each request makes up to three adapter calls per
+    /// market for its pair, so at most `MAX_SIMULATE_REQUESTS` requests are
+    /// accepted per call; larger batches fail with `InvalidArgument` and
+    /// should be split to stay within the simulation budget.
+    fn simulate_swaps(e: Env, requests: Vec<QuoteRequest>) -> Vec<QuoteResult>;
+    /// Spot price, execution price, price impact and fee of selling
+    /// `amount_in` of `token_in` on each market for the pair.
+    fn get_price_impact(
+        e: Env,
+        amount_in: i128,
+        token_in: Address,
+        token_out: Address,
+    ) -> Vec<MarketQuote>;
     fn swap(e: Env, amount: i128, token_in: Address, token_out: Address, best_hop: Address);
+    /// Swap `amount_in` of `token_in` for at least `min_out` of `token_out`
+    /// on the best quoted market. The router takes `amount_in` from `to` and
+    /// trades it on that market's pool through its adapter's
+    /// `swap_exact_in_on`, paying the output to `to`. If the swap fails, the
+    /// next best market still quoting `min_out` or more is tried, including
+    /// other pools of the same adapter. If every attempt fails the call
+    /// returns an error and, being reverted, moves nothing. Returns the
+    /// market that filled the swap.
+    fn swap_exact_in(
+        e: Env,
+        amount_in: i128,
+        min_out: i128,
+        token_in: Address,
+        token_out: Address,
+        to: Address,
+        deadline: u64,
+    ) -> Result<SwapResult, RouterError>;
+    /// Deliver exactly `amount_out` of `token_out` to `to`, spending at most
+    /// `max_in` of `token_in`, on the market needing the least input, through
+    /// its adapter's `swap_exact_out_on`. Falls back to the next-cheapest
+    /// market within `max_in` like `swap_exact_in`. The router takes `max_in`
+    /// from `to` and refunds the part the market did not spend.
+    fn swap_exact_out(
+        e: Env,
+        amount_out: i128,
+        max_in: i128,
+        token_in: Address,
+        token_out: Address,
+        to: Address,
+        deadline: u64,
+    ) -> Result<SwapResult, RouterError>;
     fn provide_liquidity(
         e: Env,
         amount: i128,
@@ -77,6 +133,10 @@ pub trait HoopsRouterTrait {
         sender: Address,
         deadline: u64,
     );
+    /// Takes `lp_amount` of `lp_token` from `sender`, has the adapter of the
+    /// token's market redeem it for the router and forwards both tokens to
+    /// `sender`. Like the swaps, `sender` only authorizes the LP transfer in,
+    /// so a contract can pre-authorize the whole call.
     fn redeem_liquidity(
         e: Env,
         lp_token: Address,
@@ -85,11 +145,87 @@ pub trait HoopsRouterTrait {
         deadline: u64,
     );
 
+    // Rewards
+    /// Pending LP rewards of `user` in markets `start..start + limit` whose
+    /// adapter supports rewards. Only positions with something to claim are
+    /// listed.
+    fn get_user_rewards(e: Env, user: Address, start: u32, limit: u32) -> Vec<RewardPosition>;
+    /// Claim `user`'s pending rewards in markets `start..start + limit` to
+    /// `user`. Claims that fail are skipped. Returns the total claimed.
+    fn claim_all_rewards(e: Env, user: Address, start: u32, limit: u32) -> i128;
+
     // Pool discovery
-    fn discover_soroswap_pools(e: Env, factory: Address, pairs_to_check: Vec<(Address, Address)>);
-    fn discover_aqua_pools(e: Env, factory: Address, pairs_to_check: Vec<(Address, Address)>);
-    fn discover_phoenix_pools(e: Env, factory: Address, pairs_to_check: Vec<(Address, Address)>);
-    fn discover_comet_pools(e: Env, factory: Address, pairs_to_check: Vec<(Address, Address)>);
+    /// Refresh the markets of `adapter_id` for pools `start..start + limit`
+    /// of its `list_pools`, from `get_pool_info`. Pools with more than two
+    /// tokens get one market per token pair. Returns the number of markets
+    /// written.
+    fn discover_pools(e: Env, adapter_id: i128, start: u32, limit: u32) -> u32;
+    /// `pool`'s tokens, reserves and LP supply from adapter `adapter_id`'s
+    /// `get_pool_info`; `None` if the adapter is unknown or the call fails.
+    fn get_pool_info(e: Env, adapter_id: i128, pool: Address) -> Option<PoolInfo>;
+}
+
+/// Quotes for the pair, best first: most output for `ExactIn`, least input
+/// for `ExactOut`.
+fn sorted_quotes(e: &Env, amount: i128, token_in: &Address, token_out: &Address, kind: QuoteKind) -> Vec<SwapQuote> {
+    let quotes = match kind {
+        QuoteKind::ExactIn => HoopsRouter::get_all_quotes(e.clone(), amount, token_in.clone(), token_out.clone()),
+        QuoteKind::ExactOut => quotes_exact_out(e, amount, token_in, token_out),
+    };
+    let mut sorted: Vec<SwapQuote> = Vec::new(e);
+    for quote in quotes.iter() {
+        let at = sorted
+            .iter()
+            .position(|q| match kind {
+                QuoteKind::ExactIn => q.amount_out < quote.amount_out,
+                QuoteKind::ExactOut => q.amount_in > quote.amount_in,
+            })
+            .unwrap_or(sorted.len() as usize);
+        sorted.insert(at as u32, quote);
+    }
+    sorted
+}
+
+/// Pre-authorizes the `token.transfer(router, to, amount)` the adapter
+/// invoked next makes to take its input from the router.
+fn authorize_transfer(e: &Env, token: &Address, to: &Address, amount: i128) {
+    let args = (e.current_contract_address(), to.clone(), amount).into_val(e);
+    e.authorize_as_current_contract(vec![
+        e,
+        InvokerContractAuthEntry::Contract(SubContractInvocation {
+            context: ContractContext { contract: token.clone(), fn_name: symbol_short!("transfer"), args },
+            sub_invocations: Vec::new(e),
+        }),
+    ]);
+}
+
+/// Input each market for the pair needs to deliver `amount_out`, through the
+/// adapters' `quote_out`. Markets that cannot quote are left out.
+fn quotes_exact_out(e: &Env, amount_out: i128, token_in: &Address, token_out: &Address) -> Vec<SwapQuote> {
+    let adapters = get_adapters(e);
+    let mut quotes = Vec::new(e);
+    let (token_a, token_b) = if token_in < token_out {
+        (token_in.clone(), token_out.clone())
+    } else {
+        (token_out.clone(), token_in.clone())
+    };
+    for market in get_pair_markets(e, &token_a, &token_b).iter() {
+        let Some(adapter_address) = adapters.get(market.adapter_id) else { continue; };
+        let adapter = AdapterClient::new(e, &adapter_address);
+        if let Ok(Ok(amount_in)) = adapter.try_quote_out(&market.pool_address, &amount_out, token_in, token_out) {
+            quotes.push_back(SwapQuote {
+                adapter_id: market.adapter_id,
+                pool_address: market.pool_address.clone(),
+                token_in: token_in.clone(),
+                token_out: token_out.clone(),
+                amount_in,
+                amount_out,
+                pool_type: market.pool_type,
+                lp_token: market.lp_token.clone(),
+            });
+        }
+    }
+    quotes
 }
 
 #[contract]
@@ -142,11 +278,21 @@ impl HoopsRouterTrait for HoopsRouter {
         let config = get_core_config(&e);
         config.admin.require_auth();
 
-        let mut markets = get_markets(&e);
         for market in markets_to_add.iter() {
-            markets.push_back(market);
+            // Markets are stored in canonical token order
+            let market = if market.token_a < market.token_b {
+                market
+            } else {
+                MarketData {
+                    token_a: market.token_b,
+                    token_b: market.token_a,
+                    reserve_a: market.reserve_b,
+                    reserve_b: market.reserve_a,
+                    ..market
+                }
+            };
+            put_market(&e, &market);
         }
-        set_markets(&e, &markets);
     }
 
     fn get_all_quotes(
@@ -155,7 +301,6 @@ impl HoopsRouterTrait for HoopsRouter {
         token_in: Address,
         token_out: Address,
     ) -> Vec<crate::types::SwapQuote> {
-        let markets = get_markets(&e);
         let adapters = get_adapters(&e);
         let mut quotes = Vec::new(&e);
 
@@ -166,12 +311,7 @@ impl HoopsRouterTrait for HoopsRouter {
             (token_out.clone(), token_in.clone())
         };
 
-        for market in markets.iter() {
-            // Only consider pools for this token pair
-            let is_match = market.token_a == token_a && market.token_b == token_b;
-            if !is_match {
-                continue;
-            }
+        for market in get_pair_markets(&e, &token_a, &token_b).iter() {
             let Some(adapter_address) = adapters.get(market.adapter_id) else {
                 continue;
             };
@@ -211,12 +351,181 @@ impl HoopsRouterTrait for HoopsRouter {
         best
     }
 
+    fn get_best_quote_exact_out(
+        e: Env,
+        amount_out: i128,
+        token_in: Address,
+        token_out: Address,
+    ) -> Option<crate::types::SwapQuote> {
+        sorted_quotes(&e, amount_out, &token_in, &token_out, QuoteKind::ExactOut).first()
+    }
+
+    fn simulate_swaps(e: Env, requests: Vec<QuoteRequest>) -> Vec<QuoteResult> {
+        if requests.len() > MAX_SIMULATE_REQUESTS {
+            panic_with_error!(&e, RouterError::InvalidArgument);
+        }
+        let adapters = get_adapters(&e);
+        let mut results = Vec::new(&e);
+        for req in requests.iter() {
+            let (token_a, token_b) = if req.token_in < req.token_out {
+                (req.token_in.clone(), req.token_out.clone())
+            } else {
+                (req.token_out.clone(), req.token_in.clone())
+            };
+            let mut quotes = Vec::new(&e);
+            for market in get_pair_markets(&e, &token_a, &token_b).iter() {
+                let Some(adapter_address) = adapters.get(market.adapter_id) else { continue; };
+                let adapter = AdapterClient::new(&e, &adapter_address);
+                quotes.push_back(quote::simulate(&adapter, &market, &req));
+            }
+            results.push_back(QuoteResult { request: req, quotes });
+        }
+        results
+    }
+
+    fn get_price_impact(
+        e: Env,
+        amount_in: i128,
+        token_in: Address,
+        token_out: Address,
+    ) -> Vec<MarketQuote> {
+        let req = QuoteRequest { token_in, token_out, amount: amount_in, kind: QuoteKind::ExactIn };
+        Self::simulate_swaps(e.clone(), vec![&e, req])
+            .first()
+            .map_or(Vec::new(&e), |r| r.quotes)
+    }
+
     fn swap(e: Env, amount: i128, token_in: Address, token_out: Address, best_hop: Address) {
         // This function will need to find the correct adapter for the best_hop (pool address)
         // and then call the swap function on that adapter.
         // For now, we'll leave it as a placeholder.
     }
 
+    fn swap_exact_in(
+        e: Env,
+        amount_in: i128,
+        min_out: i128,
+        token_in: Address,
+        token_out: Address,
+        to: Address,
+        deadline: u64,
+    ) -> Result<SwapResult, RouterError> {
+        to.require_auth();
+        if amount_in <= 0 {
+            return Err(RouterError::InvalidAmount);
+        }
+        if e.ledger().timestamp() > deadline {
+            return Err(RouterError::DeadlinePassed);
+        }
+        let quotes = sorted_quotes(&e, amount_in, &token_in, &token_out, QuoteKind::ExactIn);
+        if quotes.is_empty() {
+            return Err(RouterError::PoolNotFound);
+        }
+        let adapters = get_adapters(&e);
+        let path = vec![&e, token_in.clone(), token_out.clone()];
+        let router = e.current_contract_address();
+        let input = token::Client::new(&e, &token_in);
+        let output = token::Client::new(&e, &token_out);
+        input.transfer(&to, &router, &amount_in);
+        let mut err = RouterError::MinAmountNotMet;
+        for quote in quotes.iter() {
+            if quote.amount_out < min_out {
+                break;
+            }
+            let Some(adapter_address) = adapters.get(quote.adapter_id) else { continue; };
+            let adapter = AdapterClient::new(&e, &adapter_address);
+            let before = output.balance(&router);
+            authorize_transfer(&e, &token_in, &adapter_address, amount_in);
+            // Trade on the quoted pool; a failed attempt is rolled back, so
+            // move on to the next market
+            match adapter.try_swap_exact_in_on(&quote.pool_address, &amount_in, &min_out, &path, &router, &deadline) {
+                Ok(Ok(_)) => {
+                    // What actually reached the router, whatever the adapter reports
+                    let amount_out = output.balance(&router) - before;
+                    if amount_out < min_out {
+                        return Err(RouterError::MinAmountNotMet);
+                    }
+                    output.transfer(&router, &to, &amount_out);
+                    return Ok(SwapResult {
+                        adapter_id: quote.adapter_id,
+                        pool_address: quote.pool_address,
+                        amount_in,
+                        amount_out,
+                    });
+                }
+                _ => err = RouterError::ExternalFailure,
+            }
+        }
+        // Returning an error reverts the transfer in above
+        Err(err)
+    }
+
+    fn swap_exact_out(
+        e: Env,
+        amount_out: i128,
+        max_in: i128,
+        token_in: Address,
+        token_out: Address,
+        to: Address,
+        deadline: u64,
+    ) -> Result<SwapResult, RouterError> {
+        to.require_auth();
+        if amount_out <= 0 || max_in <= 0 {
+            return Err(RouterError::InvalidAmount);
+        }
+        if e.ledger().timestamp() > deadline {
+            return Err(RouterError::DeadlinePassed);
+        }
+        let quotes = sorted_quotes(&e, amount_out, &token_in, &token_out, QuoteKind::ExactOut);
+        if quotes.is_empty() {
+            return Err(RouterError::PoolNotFound);
+        }
+        let adapters = get_adapters(&e);
+        let path = vec![&e, token_in.clone(), token_out.clone()];
+        let router = e.current_contract_address();
+        let input = token::Client::new(&e, &token_in);
+        let output = token::Client::new(&e, &token_out);
+        input.transfer(&to, &router, &max_in);
+        let mut err = RouterError::MinAmountNotMet;
+        for quote in quotes.iter() {
+            if quote.amount_in > max_in {
+                break;
+            }
+            let Some(adapter_address) = adapters.get(quote.adapter_id) else { continue; };
+            let adapter = AdapterClient::new(&e, &adapter_address);
+            let before_in = input.balance(&router);
+            let before_out = output.balance(&router);
+            authorize_transfer(&e, &token_in, &adapter_address, max_in);
+            match adapter.try_swap_exact_out_on(&quote.pool_address, &amount_out, &max_in, &path, &router, &deadline) {
+                Ok(Ok(_)) => {
+                    // What actually moved, whatever the adapter reports
+                    let amount_in = before_in - input.balance(&router);
+                    let received = output.balance(&router) - before_out;
+                    if amount_in > max_in || received < amount_out {
+                        return Err(RouterError::MinAmountNotMet);
+                    }
+                    let to_before = output.balance(&to);
+                    output.transfer(&router, &to, &received);
+                    if output.balance(&to) - to_before < amount_out {
+                        return Err(RouterError::MinAmountNotMet);
+                    }
+                    if amount_in < max_in {
+                        input.transfer(&router, &to, &(max_in - amount_in));
+                    }
+                    return Ok(SwapResult {
+                        adapter_id: quote.adapter_id,
+                        pool_address: quote.pool_address,
+                        amount_in,
+                        amount_out,
+                    });
+                }
+                _ => err = RouterError::ExternalFailure,
+            }
+        }
+        // Returning an error reverts the transfer in above
+        Err(err)
+    }
+
     fn provide_liquidity(
         e: Env,
         amount: i128,
@@ -250,67 +559,123 @@ impl HoopsRouterTrait for HoopsRouter {
         sender: Address,
         deadline: u64,
     ) {
+        sender.require_auth();
         // Find the adapter for this lp_token
-        let markets = get_markets(&e);
-        let adapters = get_adapters(&e);
-        let mut found = false;
-        for market in markets.iter() {
-            if market.lp_token == lp_token {
-                let Some(adapter_address) = adapters.get(market.adapter_id) else { continue; };
-                let adapter = AdapterClient::new(&e, &adapter_address);
-                adapter.remove_liquidity(
-                    &lp_token,
-                    &lp_amount,
-                    &0i128,
-                    &0i128,
-                    &sender,
-                    &deadline,
-                );
-                found = true;
-                break;
+        let market = get_lp_market(&e, &lp_token).expect("No adapter found for lp_token");
+        let adapter_address = get_adapters(&e)
+            .get(market.adapter_id)
+            .expect("No adapter found for lp_token");
+        let adapter = AdapterClient::new(&e, &adapter_address);
+        let router = e.current_contract_address();
+        let token_a = token::Client::new(&e, &market.token_a);
+        let token_b = token::Client::new(&e, &market.token_b);
+        let (before_a, before_b) = (token_a.balance(&router), token_b.balance(&router));
+        token::Client::new(&e, &lp_token).transfer(&sender, &router, &lp_amount);
+        authorize_transfer(&e, &lp_token, &adapter_address, lp_amount);
+        adapter.remove_liquidity(
+            &lp_token,
+            &lp_amount,
+            &0i128,
+            &0i128,
+            &router,
+            &deadline,
+        );
+        // Forward what the adapter paid out, in whichever order it reports it
+        for (client, before) in [(token_a, before_a), (token_b, before_b)] {
+            let received = client.balance(&router) - before;
+            if received > 0 {
+                client.transfer(&router, &sender, &received);
             }
         }
-        if !found {
-            panic!("No adapter found for lp_token");
-        }
     }
 
-    fn discover_soroswap_pools(e: Env, factory: Address, pairs_to_check: Vec<(Address, Address)>) {
-        let soroswap_factory = soroswap_factory::Client::new(&e, &factory);
-        let mut markets = get_markets(&e);
-
-        for pair in pairs_to_check.iter() {
-            let (token_a, token_b) = pair;
-            let pair_address = soroswap_factory.get_pair(&token_a, &token_b);
-
-            
-            let soroswap_pair = soroswap_pair::Client::new(&e, &pair_address);
-            let reserves = soroswap_pair.get_reserves();
-            let market = MarketData {
-                adapter_id: 3, // Soroswap PROTOCOL_ID
-                pool_address: pair_address.clone(),
-                lp_token: pair_address.clone(),
-                token_a: token_a.clone(),
-                token_b: token_b.clone(),
-                reserve_a: reserves.0,
-                reserve_b: reserves.1,
-                pool_type: 0, // ConstantProduct
-                ledger: e.ledger().sequence(),
-            };
-            markets.push_back(market);
+    fn get_user_rewards(e: Env, user: Address, start: u32, limit: u32) -> Vec<RewardPosition> {
+        let adapters = get_adapters(&e);
+        let mut positions: Vec<RewardPosition> = Vec::new(&e);
+        for market in get_markets(&e, start, limit).iter() {
+            // Markets can be listed more than once
+            if positions.iter().any(|p| p.lp_token == market.lp_token) {
+                continue;
+            }
+            let Some(adapter_address) = adapters.get(market.adapter_id) else { continue; };
+            let adapter = RewardsAdapterClient::new(&e, &adapter_address);
+            if let Ok(Ok(pending)) = adapter.try_get_user_reward(&user, &market.lp_token) {
+                if pending > 0 {
+                    positions.push_back(RewardPosition {
+                        adapter_id: market.adapter_id,
+                        pool_address: market.pool_address.clone(),
+                        lp_token: market.lp_token.clone(),
+                        pending,
+                    });
+                }
+            }
         }
-        set_markets(&e, &markets);
+        positions
     }
 
-    fn discover_aqua_pools(e: Env, factory: Address, pairs_to_check: Vec<(Address, Address)>) {
-        // Similar logic to Soroswap, but using the Aqua factory and pair contracts
+    fn claim_all_rewards(e: Env, user: Address, start: u32, limit: u32) -> i128 {
+        user.require_auth();
+        let adapters = get_adapters(&e);
+        let mut total = 0i128;
+        for position in Self::get_user_rewards(e.clone(), user.clone(), start, limit).iter() {
+            let Some(adapter_address) = adapters.get(position.adapter_id) else { continue; };
+            let adapter = RewardsAdapterClient::new(&e, &adapter_address);
+            // One failing claim must not revert the others
+            if let Ok(Ok(claimed)) = adapter.try_claim(&user, &position.lp_token, &user) {
+                total += claimed;
+            }
+        }
+        total
     }
 
-    fn discover_phoenix_pools(e: Env, factory: Address, pairs_to_check: Vec<(Address, Address)>) {
-        // Similar logic to Soroswap, but using the Phoenix factory and pair contracts
+    fn discover_pools(e: Env, adapter_id: i128, start: u32, limit: u32) -> u32 {
+        let config = get_core_config(&e);
+        config.admin.require_auth();
+
+        let Some(adapter_address) = get_adapters(&e).get(adapter_id) else { return 0; };
+        let adapter = AdapterClient::new(&e, &adapter_address);
+        let pools = match adapter.try_list_pools(&start, &limit) {
+            Ok(Ok(pools)) => pools,
+            _ => return 0,
+        };
+        let mut written = 0u32;
+        for pool in pools.iter() {
+            let Ok(Ok(info)) = adapter.try_get_pool_info(&pool) else { continue; };
+            let n = info.tokens.len();
+            for i in 0..n {
+                for j in (i + 1)..n {
+                    let (t_i, t_j) = (info.tokens.get_unchecked(i), info.tokens.get_unchecked(j));
+                    let (r_i, r_j) = (info.reserves.get(i).unwrap_or(0), info.reserves.get(j).unwrap_or(0));
+                    // Markets are stored in canonical token order
+                    let ((token_a, reserve_a), (token_b, reserve_b)) = if t_i < t_j {
+                        ((t_i, r_i), (t_j, r_j))
+                    } else {
+                        ((t_j, r_j), (t_i, r_i))
+                    };
+                    // Replaces this adapter's market for the pool and pair, if any
+                    put_market(&e, &MarketData {
+                        adapter_id,
+                        pool_address: pool.clone(),
+                        lp_token: info.lp_token.clone(),
+                        token_a,
+                        token_b,
+                        reserve_a,
+                        reserve_b,
+                        pool_type: info.kind as u32,
+                        ledger: e.ledger().sequence(),
+                    });
+                    written += 1;
+                }
+            }
+        }
+        written
     }
 
-    fn discover_comet_pools(e: Env, factory: Address, pairs_to_check: Vec<(Address, Address)>) {
-        // Similar logic to Soroswap, but using the Comet factory and pair contracts
+    fn get_pool_info(e: Env, adapter_id: i128, pool: Address) -> Option<PoolInfo> {
+        let adapter_address = get_adapters(&e).get(adapter_id)?;
+        match AdapterClient::new(&e, &adapter_address).try_get_pool_info(&pool) {
+            Ok(Ok(info)) => Some(info),
+            _ => None,
+        }
     }
 }
@@ -0,0 +1,72 @@
+use hoops_adapter_interface::{AdapterClient, AdapterError, PRICE_SCALE};
+use soroban_sdk::InvokeError;
+
+use crate::types::{MarketData, MarketQuote, QuoteKind, QuoteRequest};
+
+const BPS: i128 = 10_000;
+
+/// Error code reported for a failed adapter call.
+fn err_code(err: Result<AdapterError, InvokeError>) -> u32 {
+    match err {
+        Ok(err) => err as u32,
+        Err(_) => AdapterError::ExternalFailure as u32,
+    }
+}
+
+/// Quote one market for `req`. A failed quote is kept with its error code
+/// and zero amounts. Spot price comes from the adapter's `get_spot_price`
+/// and fees from `get_pool_info`; both are left at 0 if unavailable.
+pub fn simulate(adapter: &AdapterClient, market: &MarketData, req: &QuoteRequest) -> MarketQuote {
+    let mut quote = MarketQuote {
+        adapter_id: market.adapter_id,
+        pool_address: market.pool_address.clone(),
+        amount_in: 0,
+        amount_out: 0,
+        error: 0,
+        spot_price: 0,
+        exec_price: 0,
+        price_impact_bps: 0,
+        fee_bps: 0,
+        fee_amount: 0,
+    };
+    let pool = &market.pool_address;
+    let res = match req.kind {
+        QuoteKind::ExactIn => adapter.try_quote_in(pool, &req.amount, &req.token_in, &req.token_out),
+        QuoteKind::ExactOut => adapter.try_quote_out(pool, &req.amount, &req.token_in, &req.token_out),
+    };
+    let quoted = match res {
+        Ok(Ok(amount)) => amount,
+        Ok(Err(_)) => {
+            quote.error = AdapterError::ExternalFailure as u32;
+            return quote;
+        }
+        Err(err) => {
+            quote.error = err_code(err);
+            return quote;
+        }
+    };
+    (quote.amount_in, quote.amount_out) = match req.kind {
+        QuoteKind::ExactIn => (req.amount, quoted),
+        QuoteKind::ExactOut => (quoted, req.amount),
+    };
+    if quote.amount_in <= 0 {
+        return quote;
+    }
+    quote.exec_price = quote.amount_out * PRICE_SCALE / quote.amount_in;
+
+    // Pool data is best effort; the quote stands without it
+    let Ok(Ok(info)) = adapter.try_get_pool_info(pool) else { return quote };
+    quote.fee_bps = info.fee_bps;
+    quote.fee_amount = quote.amount_in * info.fee_bps as i128 / BPS;
+    quote.spot_price = adapter
+        .try_get_spot_price(pool, &req.token_in, &req.token_out)
+        .ok()
+        .and_then(|r| r.ok())
+        .unwrap_or(0);
+    // Impact is measured against the spot price net of the pool fee
+    let net_spot = quote.spot_price * (BPS - info.fee_bps as i128) / BPS;
+    if net_spot > 0 {
+        quote.price_impact_bps = ((net_spot - quote.exec_price) * BPS / net_spot).max(0);
+    }
+    quote
+}
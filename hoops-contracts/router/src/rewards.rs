@@ -0,0 +1,10 @@
+use hoops_adapter_interface::AdapterError;
+use soroban_sdk::{contractclient, Address, Env};
+
+/// Optional reward entrypoints. Adapters whose pools pay LP incentives (Aqua)
+/// implement these; calls to any other adapter simply fail and are skipped.
+#[contractclient(name = "RewardsAdapterClient")]
+pub trait RewardsAdapter {
+    fn get_user_reward(e: Env, user: Address, lp: Address) -> Result<i128, AdapterError>;
+    fn claim(e: Env, user: Address, lp: Address, to: Address) -> Result<i128, AdapterError>;
+}
@@ -6,7 +6,19 @@ use crate::types::{CoreConfig, MarketData};
 pub enum DataKey {
     CoreConfig,
     Adapters, // Map<i128, Address>
-    Markets, // Vec<MarketData>
+    MarketCount, // u32
+    Market(u32), // MarketData (persistent)
+    PairMarkets(Address, Address), // Vec<u32> of market ids, canonical token order (persistent)
+    LpMarket(Address), // u32, first market listing the LP token (persistent)
+}
+
+const DAY_IN_LEDGERS: u32 = 17_280;
+const TTL_THRESHOLD: u32 = 30 * DAY_IN_LEDGERS;
+const TTL_BUMP: u32 = 120 * DAY_IN_LEDGERS;
+
+/// Keeps a persistent entry alive; called whenever one is read or written.
+fn bump(e: &Env, key: &DataKey) {
+    e.storage().persistent().extend_ttl(key, TTL_THRESHOLD, TTL_BUMP);
 }
 
 pub fn get_core_config(e: &Env) -> CoreConfig {
@@ -25,10 +37,83 @@ pub fn set_adapters(e: &Env, adapters: &Map<i128, Address>) {
     e.storage().instance().set(&DataKey::Adapters, adapters);
 }
 
-pub fn get_markets(e: &Env) -> Vec<MarketData> {
-    e.storage().instance().get(&DataKey::Markets).unwrap_or(Vec::new(e))
+pub fn market_count(e: &Env) -> u32 {
+    e.storage().instance().get(&DataKey::MarketCount).unwrap_or(0)
+}
+
+pub fn get_market(e: &Env, id: u32) -> Option<MarketData> {
+    let key = DataKey::Market(id);
+    let market = e.storage().persistent().get(&key)?;
+    bump(e, &key);
+    Some(market)
+}
+
+/// Markets `start..start + limit`, in the order they were added.
+pub fn get_markets(e: &Env, start: u32, limit: u32) -> Vec<MarketData> {
+    let end = start.saturating_add(limit).min(market_count(e));
+    let mut markets = Vec::new(e);
+    for id in start.min(end)..end {
+        if let Some(market) = get_market(e, id) {
+            markets.push_back(market);
+        }
+    }
+    markets
+}
+
+fn pair_ids(e: &Env, token_a: &Address, token_b: &Address) -> Vec<u32> {
+    let key = DataKey::PairMarkets(token_a.clone(), token_b.clone());
+    let ids = e.storage().persistent().get(&key).unwrap_or(Vec::new(e));
+    if !ids.is_empty() {
+        bump(e, &key);
+    }
+    ids
+}
+
+/// Markets for a pair given in canonical order (`token_a < token_b`).
+pub fn get_pair_markets(e: &Env, token_a: &Address, token_b: &Address) -> Vec<MarketData> {
+    let mut markets = Vec::new(e);
+    for id in pair_ids(e, token_a, token_b).iter() {
+        if let Some(market) = get_market(e, id) {
+            markets.push_back(market);
+        }
+    }
+    markets
+}
+
+pub fn get_lp_market(e: &Env, lp_token: &Address) -> Option<MarketData> {
+    let key = DataKey::LpMarket(lp_token.clone());
+    let id = e.storage().persistent().get(&key)?;
+    bump(e, &key);
+    get_market(e, id)
 }
 
-pub fn set_markets(e: &Env, markets: &Vec<MarketData>) {
-    e.storage().instance().set(&DataKey::Markets, markets);
+/// Store `market`, replacing the entry with the same adapter, pool and pair
+/// if there is one. Returns the market id.
+pub fn put_market(e: &Env, market: &MarketData) -> u32 {
+    let ids = pair_ids(e, &market.token_a, &market.token_b);
+    let existing = ids.iter().find(|id| {
+        get_market(e, *id).is_some_and(|m| m.adapter_id == market.adapter_id && m.pool_address == market.pool_address)
+    });
+    let id = match existing {
+        Some(id) => id,
+        None => {
+            let id = market_count(e);
+            e.storage().instance().set(&DataKey::MarketCount, &(id + 1));
+            let mut ids = ids;
+            ids.push_back(id);
+            let key = DataKey::PairMarkets(market.token_a.clone(), market.token_b.clone());
+            e.storage().persistent().set(&key, &ids);
+            bump(e, &key);
+            id
+        }
+    };
+    let key = DataKey::Market(id);
+    e.storage().persistent().set(&key, market);
+    bump(e, &key);
+    let lp_key = DataKey::LpMarket(market.lp_token.clone());
+    if !e.storage().persistent().has(&lp_key) {
+        e.storage().persistent().set(&lp_key, &id);
+    }
+    bump(e, &lp_key);
+    id
 }
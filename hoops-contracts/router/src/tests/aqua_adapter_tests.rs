@@ -1,7 +1,14 @@
 // Aqua Adapter tests: swap and liquidity
 #![cfg(test)]
-use crate::tests::test_setup::{aqua_pool_constant::AquaPoolClient, HoopsTestEnvironment};
-use soroban_sdk::token;
+use crate::tests::test_setup::{
+    aqua_adapter::{AdapterError, AquaPoolInfo, Capability, PoolKind},
+    aqua_pool_constant::AquaPoolClient,
+    aqua_pool_index,
+    aqua_router::AquaRouterClient,
+    redeem_with_lp_auth_only, HoopsTestEnvironment,
+};
+use soroban_sdk::testutils::Address as _;
+use soroban_sdk::{token, Address, BytesN};
 #[allow(unused_imports)]
 use soroban_sdk::{vec, Env};
 use soroban_sdk::testutils::Logs;
@@ -121,6 +128,479 @@ pub fn run_swap_exact_out(test_env: &HoopsTestEnvironment) {
     );
 }
 
+pub fn run_swap_multihop(test_env: &HoopsTestEnvironment) {
+    let env = &test_env.env;
+    env.mock_all_auths();
+    let user = &test_env.user;
+    let token_a_client = token::Client::new(env, &test_env.tokens.client_a);
+    let token_c_client = token::Client::new(env, &test_env.tokens.client_c);
+    let aqua_adapter_client = &test_env.adapters.aqua;
+    let deadline = env.ledger().timestamp() + 100;
+    let path = vec![
+        env,
+        test_env.tokens.client_a.clone(),
+        test_env.tokens.client_b.clone(),
+        test_env.tokens.client_c.clone(),
+    ];
+
+    // A -> B -> C through the A/B and B/C pools via swap_chained
+    let amount_in: i128 = 1_000_000;
+    let quoted = aqua_adapter_client.quote_path_in(&path, &amount_in);
+    let initial_a = token_a_client.balance(user);
+    let initial_c = token_c_client.balance(user);
+    let amount_out = aqua_adapter_client.swap_exact_in(&amount_in, &0, &path, user, &deadline);
+    std::println!(
+        "[AQUA][multihop] exact in: amount_in = {}, quoted = {}, amount_out = {}",
+        amount_in,
+        quoted,
+        amount_out
+    );
+    assert_eq!(amount_out, quoted, "swap_chained should match the per-hop estimate");
+    assert_eq!(token_a_client.balance(user), initial_a - amount_in);
+    assert_eq!(token_c_client.balance(user), initial_c + amount_out);
+
+    // Exact out along the same path
+    let desired_out: i128 = 100_000;
+    let quoted_in = aqua_adapter_client.quote_path_out(&path, &desired_out);
+    let initial_a = token_a_client.balance(user);
+    let initial_c = token_c_client.balance(user);
+    let amount_in_used =
+        aqua_adapter_client.swap_exact_out(&desired_out, &(quoted_in * 2), &path, user, &deadline);
+    assert!(amount_in_used <= quoted_in * 2);
+    assert_eq!(token_a_client.balance(user), initial_a - amount_in_used);
+    assert_eq!(token_c_client.balance(user), initial_c + desired_out);
+
+    // Explicit pool swaps stay single hop
+    let pool_index = aqua_pool_index(
+        env,
+        &test_env.aqua.router_id.clone().unwrap(),
+        &test_env.aqua.pool_ids.get(0).unwrap(),
+    );
+    let res = aqua_adapter_client.try_swap_exact_in_pool(
+        &pool_index,
+        &amount_in,
+        &0,
+        &path,
+        user,
+        &deadline,
+    );
+    assert_eq!(res, Err(Ok(AdapterError::MultipathUnsupported)));
+}
+
+pub fn run_pool_mapping_auth(test_env: &HoopsTestEnvironment) {
+    let env = &test_env.env;
+    env.mock_all_auths();
+    let admin = &test_env.admin;
+    let aqua_adapter_client = &test_env.adapters.aqua;
+    let tokens_ab = vec![
+        env,
+        test_env.tokens.client_a.clone(),
+        test_env.tokens.client_b.clone(),
+    ];
+    let pool_ab = test_env.aqua.pool_ids.get(0).unwrap();
+    let pool_bc = test_env.aqua.pool_ids.get(1).unwrap();
+    let aqua_router_id = test_env.aqua.router_id.clone().unwrap();
+    let info_ab = AquaPoolInfo {
+        pool_address: pool_ab.clone(),
+        lp_token_address: AquaPoolClient::new(env, &pool_ab).share_id(),
+        pool_index: aqua_pool_index(env, &aqua_router_id, &pool_ab),
+    };
+    let info_bc = AquaPoolInfo {
+        pool_address: pool_bc.clone(),
+        lp_token_address: AquaPoolClient::new(env, &pool_bc).share_id(),
+        pool_index: aqua_pool_index(env, &aqua_router_id, &pool_bc),
+    };
+
+    // Anyone other than the admin/router is rejected
+    let stranger = Address::generate(env);
+    let res = aqua_adapter_client.try_set_pool_for_tokens(&stranger, &tokens_ab, &info_ab);
+    assert_eq!(res, Err(Ok(AdapterError::NotAuthorized)));
+    let res =
+        aqua_adapter_client.try_remove_pool_for_tokens(&stranger, &tokens_ab, &info_ab.pool_index);
+    assert_eq!(res, Err(Ok(AdapterError::NotAuthorized)));
+
+    // The B/C pool does not hold token A
+    let res = aqua_adapter_client.try_set_pool_for_tokens(admin, &tokens_ab, &info_bc);
+    assert_eq!(res, Err(Ok(AdapterError::UnsupportedPair)));
+
+    // The pool index must be the one the Aqua router lists the pool under
+    let wrong_index = AquaPoolInfo {
+        pool_index: info_bc.pool_index.clone(),
+        ..info_ab.clone()
+    };
+    let res = aqua_adapter_client.try_set_pool_for_tokens(admin, &tokens_ab, &wrong_index);
+    assert_eq!(res, Err(Ok(AdapterError::InvalidArgument)));
+
+    // Remove and restore the A/B mapping
+    aqua_adapter_client.remove_pool_for_tokens(admin, &tokens_ab, &info_ab.pool_index);
+    assert_eq!(aqua_adapter_client.get_pool_for_tokens(&tokens_ab), None);
+    assert!(!aqua_adapter_client.list_pools(&0, &100).contains(&pool_ab));
+    assert_eq!(aqua_adapter_client.get_pools_for_tokens(&tokens_ab).len(), 0);
+    let res =
+        aqua_adapter_client.try_remove_pool_for_tokens(admin, &tokens_ab, &info_ab.pool_index);
+    assert_eq!(res, Err(Ok(AdapterError::PoolNotFound)));
+    aqua_adapter_client.set_pool_for_tokens(admin, &tokens_ab, &info_ab);
+    assert_eq!(
+        aqua_adapter_client.get_pool(&tokens_ab, &info_ab.pool_index),
+        Some(info_ab.clone())
+    );
+    assert_eq!(aqua_adapter_client.get_pool_for_tokens(&tokens_ab), Some(info_ab));
+    assert!(aqua_adapter_client.list_pools(&0, &100).contains(&pool_ab));
+}
+
+pub fn run_discovery(test_env: &HoopsTestEnvironment) {
+    let env = &test_env.env;
+    env.mock_all_auths();
+    let aqua_adapter_client = &test_env.adapters.aqua;
+    let pool_ab = test_env.aqua.pool_ids.get(0).unwrap();
+    let pool_client = AquaPoolClient::new(env, &pool_ab);
+
+    let info = aqua_adapter_client.get_pool_info(&pool_ab);
+    assert_eq!(info.tokens, pool_client.get_tokens());
+    assert_eq!(info.lp_token, pool_client.share_id());
+    assert_eq!(info.reserves.len(), 2);
+    assert_eq!(info.fee_bps, pool_client.get_fee_fraction());
+    assert_eq!(info.kind, PoolKind::ConstantProduct);
+    assert_eq!(info.weights.len(), 0);
+    assert_eq!(info.lp_supply, pool_client.get_total_shares() as i128);
+
+    assert!(aqua_adapter_client.supports(&Capability::MultiHop));
+    assert!(aqua_adapter_client.supports(&Capability::Rewards));
+    assert!(!aqua_adapter_client.supports(&Capability::Staking));
+    let res = aqua_adapter_client.try_get_pool_info(&test_env.comet.pool_ids.get(0).unwrap());
+    assert!(res.is_err());
+}
+
+pub fn run_multi_pool_selection(test_env: &HoopsTestEnvironment) {
+    let env = &test_env.env;
+    env.mock_all_auths();
+    let user = &test_env.user;
+    let admin = &test_env.admin;
+    let aqua_adapter_client = &test_env.adapters.aqua;
+    let aqua_router_id = test_env.aqua.router_id.clone().unwrap();
+    let aqua_router = AquaRouterClient::new(env, &aqua_router_id);
+    let token_a_client = token::Client::new(env, &test_env.tokens.client_a);
+    let token_b_client = token::Client::new(env, &test_env.tokens.client_b);
+    let path = vec![
+        env,
+        token_a_client.address.clone(),
+        token_b_client.address.clone(),
+    ];
+
+    // Second A/B pool at a higher fee tier, seeded at the same 7:1 price
+    let (high_fee_index, high_fee_pool) = aqua_router.init_standard_pool(user, &path, &100);
+    let high_fee_client = AquaPoolClient::new(env, &high_fee_pool);
+    let pool_tokens = high_fee_client.get_tokens();
+    let seed = if pool_tokens.get(0).unwrap() == token_a_client.address {
+        vec![env, 7_000_000_000u128, 1_000_000_000u128]
+    } else {
+        vec![env, 1_000_000_000u128, 7_000_000_000u128]
+    };
+    aqua_router.deposit(user, &pool_tokens, &high_fee_index, &seed, &0);
+    let high_fee_info = AquaPoolInfo {
+        pool_address: high_fee_pool.clone(),
+        lp_token_address: high_fee_client.share_id(),
+        pool_index: high_fee_index.clone(),
+    };
+    aqua_adapter_client.set_pool_for_tokens(admin, &path, &high_fee_info);
+    assert_eq!(aqua_adapter_client.get_pools_for_tokens(&path).len(), 2);
+
+    // The deeper 0.3% pool quotes better and is picked by default
+    let amount_in: i128 = 1_000_000;
+    let best = aqua_adapter_client.get_best_pool_in(&path, &amount_in);
+    assert_eq!(best.pool_address, test_env.aqua.pool_ids.get(0).unwrap());
+
+    // The caller can still force the 1% pool
+    let before = token_b_client.balance(user);
+    let deadline = env.ledger().timestamp() + 100;
+    let out = aqua_adapter_client.swap_exact_in_pool(
+        &high_fee_index,
+        &amount_in,
+        &0,
+        &path,
+        user,
+        &deadline,
+    );
+    assert_eq!(token_b_client.balance(user), before + out);
+    let unknown = BytesN::from_array(env, &[9; 32]);
+    let res = aqua_adapter_client.try_swap_exact_in_pool(
+        &unknown,
+        &amount_in,
+        &0,
+        &path,
+        user,
+        &deadline,
+    );
+    assert_eq!(res, Err(Ok(AdapterError::PoolNotFound)));
+
+    // Or name it by address, as the router does
+    let before = token_b_client.balance(user);
+    let out = aqua_adapter_client.swap_exact_in_on(
+        &high_fee_pool,
+        &amount_in,
+        &0,
+        &path,
+        user,
+        &deadline,
+    );
+    assert_eq!(token_b_client.balance(user), before + out);
+    let res = aqua_adapter_client.try_swap_exact_in_on(
+        &token_a_client.address,
+        &amount_in,
+        &0,
+        &path,
+        user,
+        &deadline,
+    );
+    assert_eq!(res, Err(Ok(AdapterError::PoolNotFound)));
+
+    aqua_adapter_client.remove_pool_for_tokens(admin, &path, &high_fee_index);
+    assert_eq!(aqua_adapter_client.get_pools_for_tokens(&path).len(), 1);
+}
+
+pub fn run_stable_liquidity(test_env: &HoopsTestEnvironment) {
+    let env = &test_env.env;
+    env.mock_all_auths();
+    let user = &test_env.user;
+    let aqua_adapter_client = &test_env.adapters.aqua;
+    let aqua_router_id = test_env.aqua.router_id.clone().unwrap();
+    let token_b_client = token::Client::new(env, &test_env.tokens.client_b);
+    let deadline = env.ledger().timestamp() + 100;
+    let stable_pool = test_env.aqua.pool_ids.get(1).unwrap();
+    let pool_index = aqua_pool_index(env, &aqua_router_id, &stable_pool);
+    let lp = AquaPoolClient::new(env, &stable_pool).share_id();
+    let lp_client = token::Client::new(env, &lp);
+    let tokens_bc = vec![
+        env,
+        test_env.tokens.client_b.clone(),
+        test_env.tokens.client_c.clone(),
+    ];
+
+    // Single-sided B deposit, bounded by the pool's own share estimate
+    let amounts = vec![env, 10_000_000i128, 0i128];
+    let estimate = aqua_adapter_client.estimate_deposit(&tokens_bc, &pool_index, &amounts);
+    assert!(estimate > 0);
+    let lp_before = lp_client.balance(user);
+    let (deposited, shares) = aqua_adapter_client.deposit(
+        &tokens_bc,
+        &pool_index,
+        &amounts,
+        &(estimate * 99 / 100),
+        user,
+        &deadline,
+        &false,
+    );
+    std::println!(
+        "[AQUA][stable_liquidity] deposited = {:?}, shares = {}, estimate = {}",
+        deposited,
+        shares,
+        estimate
+    );
+    assert_eq!(deposited, amounts);
+    assert_eq!(lp_client.balance(user), lp_before + shares);
+
+    // And back out as B only
+    let b_before = token_b_client.balance(user);
+    let expected = aqua_adapter_client.estimate_withdraw_one(&lp, &shares, &token_b_client.address);
+    let got = aqua_adapter_client.withdraw_one(
+        &lp,
+        &shares,
+        &token_b_client.address,
+        &expected,
+        user,
+        &deadline,
+    );
+    assert_eq!(got, expected);
+    assert_eq!(token_b_client.balance(user), b_before + got);
+    assert_eq!(lp_client.balance(user), lp_before);
+
+    // Two-sided deposits turn the minimums into a share floor
+    let amt: i128 = 10_000_000;
+    let token_b = &test_env.tokens.client_b;
+    let token_c = &test_env.tokens.client_c;
+    // A lopsided deposit pays the imbalance fee, so it mints fewer shares
+    // than its own amounts are worth and zero-tolerance minimums fail
+    let res = aqua_adapter_client.try_add_liquidity(token_b, token_c, &amt, &(amt / 10), &amt, &(amt / 10), user, &deadline);
+    std::println!("[AQUA][stable_liquidity] zero-tolerance imbalanced deposit: {:?}", res);
+    assert_eq!(res, Err(Ok(AdapterError::MinAmountNotMet)));
+    let (_, _, minted) = aqua_adapter_client.add_liquidity(token_b, token_c, &amt, &amt, &(amt * 99 / 100), &(amt * 99 / 100), user, &deadline);
+    assert!(minted > 0);
+
+    // Constant-product pools reject single-sided deposits
+    let cp_pool = test_env.aqua.pool_ids.get(0).unwrap();
+    let cp_index = aqua_pool_index(env, &aqua_router_id, &cp_pool);
+    let tokens_ab = vec![
+        env,
+        test_env.tokens.client_a.clone(),
+        test_env.tokens.client_b.clone(),
+    ];
+    let res = aqua_adapter_client.try_deposit(
+        &tokens_ab,
+        &cp_index,
+        &vec![env, 10_000_000i128, 0i128],
+        &0,
+        user,
+        &deadline,
+        &false,
+    );
+    assert_eq!(res, Err(Ok(AdapterError::InvalidAmount)));
+}
+
+pub fn run_seed_empty_pool(test_env: &HoopsTestEnvironment) {
+    let env = &test_env.env;
+    env.mock_all_auths();
+    let user = &test_env.user;
+    let admin = &test_env.admin;
+    let aqua_adapter_client = &test_env.adapters.aqua;
+    let aqua_router = AquaRouterClient::new(env, &test_env.aqua.router_id.clone().unwrap());
+    let deadline = env.ledger().timestamp() + 100;
+    let tokens_ac = vec![
+        env,
+        test_env.tokens.client_a.clone(),
+        test_env.tokens.client_c.clone(),
+    ];
+    let (pool_index, pool) = aqua_router.init_standard_pool(user, &tokens_ac, &30);
+    let info = AquaPoolInfo {
+        pool_address: pool.clone(),
+        lp_token_address: AquaPoolClient::new(env, &pool).share_id(),
+        pool_index: pool_index.clone(),
+    };
+    aqua_adapter_client.set_pool_for_tokens(admin, &tokens_ac, &info);
+    let amounts = vec![env, 7_000_000i128, 1_000_000i128];
+
+    // Empty pools are only seeded on request
+    let res = aqua_adapter_client.try_deposit(
+        &tokens_ac,
+        &pool_index,
+        &amounts,
+        &0,
+        user,
+        &deadline,
+        &false,
+    );
+    assert_eq!(res, Err(Ok(AdapterError::InsufficientLiquidity)));
+    let (deposited, shares) = aqua_adapter_client.deposit(
+        &tokens_ac,
+        &pool_index,
+        &amounts,
+        &0,
+        user,
+        &deadline,
+        &true,
+    );
+    assert_eq!(deposited, amounts);
+    assert!(shares > 0);
+    aqua_adapter_client.remove_pool_for_tokens(admin, &tokens_ac, &pool_index);
+}
+
+pub fn run_stable_set_mapping(test_env: &HoopsTestEnvironment) {
+    let env = &test_env.env;
+    env.mock_all_auths();
+    let user = &test_env.user;
+    let admin = &test_env.admin;
+    let aqua_adapter_client = &test_env.adapters.aqua;
+    let aqua_router = AquaRouterClient::new(env, &test_env.aqua.router_id.clone().unwrap());
+    let tokens_abc = vec![
+        env,
+        test_env.tokens.client_a.clone(),
+        test_env.tokens.client_b.clone(),
+        test_env.tokens.client_c.clone(),
+    ];
+    let (pool_index, pool) = aqua_router.init_stableswap_pool(user, &tokens_abc, &10);
+    let info = AquaPoolInfo {
+        pool_address: pool.clone(),
+        lp_token_address: AquaPoolClient::new(env, &pool).share_id(),
+        pool_index: pool_index.clone(),
+    };
+
+    // A 3-token set registers and removes like a pair
+    aqua_adapter_client.set_pool_for_tokens(admin, &tokens_abc, &info);
+    assert_eq!(aqua_adapter_client.get_pool(&tokens_abc, &pool_index), Some(info.clone()));
+    assert!(aqua_adapter_client.list_pools(&0, &100).contains(&pool));
+    aqua_adapter_client.remove_pool_for_tokens(admin, &tokens_abc, &pool_index);
+    assert_eq!(aqua_adapter_client.get_pool(&tokens_abc, &pool_index), None);
+    assert!(!aqua_adapter_client.list_pools(&0, &100).contains(&pool));
+    let res = aqua_adapter_client.try_remove_pool_for_tokens(admin, &tokens_abc, &pool_index);
+    assert_eq!(res, Err(Ok(AdapterError::PoolNotFound)));
+}
+
+pub fn run_stable_set_swap(test_env: &HoopsTestEnvironment) {
+    let env = &test_env.env;
+    env.mock_all_auths();
+    let user = &test_env.user;
+    let admin = &test_env.admin;
+    let aqua_adapter_client = &test_env.adapters.aqua;
+    let aqua_router = AquaRouterClient::new(env, &test_env.aqua.router_id.clone().unwrap());
+    let deadline = env.ledger().timestamp() + 100;
+    let token_c_client = token::Client::new(env, &test_env.tokens.client_c);
+    let tokens_abc = vec![
+        env,
+        test_env.tokens.client_a.clone(),
+        test_env.tokens.client_b.clone(),
+        test_env.tokens.client_c.clone(),
+    ];
+    let (pool_index, pool) = aqua_router.init_stableswap_pool(user, &tokens_abc, &10);
+    let info = AquaPoolInfo {
+        pool_address: pool.clone(),
+        lp_token_address: AquaPoolClient::new(env, &pool).share_id(),
+        pool_index: pool_index.clone(),
+    };
+    aqua_adapter_client.set_pool_for_tokens(admin, &tokens_abc, &info);
+    aqua_adapter_client.deposit(
+        &tokens_abc,
+        &pool_index,
+        &vec![env, 10_000_000i128, 10_000_000i128, 10_000_000i128],
+        &0,
+        user,
+        &deadline,
+        &true,
+    );
+
+    // The A/C pair has no pool of its own but finds the A/B/C pool
+    let path = vec![env, test_env.tokens.client_a.clone(), test_env.tokens.client_c.clone()];
+    assert_eq!(aqua_adapter_client.get_pools_for_tokens(&path), vec![env, info.clone()]);
+    assert_eq!(aqua_adapter_client.get_pool(&path, &pool_index), Some(info.clone()));
+    assert_eq!(aqua_adapter_client.get_best_pool_in(&path, &1_000_000), info);
+    let before = token_c_client.balance(user);
+    let out = aqua_adapter_client.swap_exact_in(&1_000_000, &0, &path, user, &deadline);
+    assert!(out > 0);
+    assert_eq!(token_c_client.balance(user), before + out);
+    let before = token_c_client.balance(user);
+    let out = aqua_adapter_client.swap_exact_in_pool(&pool_index, &1_000_000, &0, &path, user, &deadline);
+    assert_eq!(token_c_client.balance(user), before + out);
+
+    aqua_adapter_client.remove_pool_for_tokens(admin, &tokens_abc, &pool_index);
+    assert_eq!(aqua_adapter_client.get_pools_for_tokens(&path).len(), 0);
+}
+
+pub fn run_rewards(test_env: &HoopsTestEnvironment) {
+    let env = &test_env.env;
+    env.mock_all_auths();
+    let user = &test_env.user;
+    let aqua_adapter_client = &test_env.adapters.aqua;
+    let pool = test_env.aqua.pool_ids.get(0).unwrap();
+    let lp = AquaPoolClient::new(env, &pool).share_id();
+
+    let pending = aqua_adapter_client.get_user_reward(user, &lp);
+    let info = aqua_adapter_client.get_rewards_info(user, &lp);
+    std::println!("[AQUA][rewards] pending = {}, info = {:?}", pending, info);
+    assert!(pending >= 0);
+
+    // Forwarding to someone else needs the reward token configured
+    let recipient = Address::generate(env);
+    assert_eq!(aqua_adapter_client.get_reward_token(), None);
+    let res = aqua_adapter_client.try_claim(user, &lp, &recipient);
+    assert_eq!(res, Err(Ok(AdapterError::NotInitialized)));
+
+    let claimed = aqua_adapter_client.claim(user, &lp, user);
+    assert_eq!(claimed, pending);
+    assert_eq!(aqua_adapter_client.get_user_reward(user, &lp), 0);
+
+    let unknown_lp = Address::generate(env);
+    let res = aqua_adapter_client.try_get_user_reward(user, &unknown_lp);
+    assert_eq!(res, Err(Ok(AdapterError::PoolNotFound)));
+}
+
 pub fn run_add_liquidity(test_env: &HoopsTestEnvironment) -> i128 {
     let lp = 0;
     let env = &test_env.env;
@@ -246,6 +726,70 @@ pub fn run_remove_liquidity(test_env: &HoopsTestEnvironment, lp: i128) {
     );
 }
 
+pub fn run_error_codes(test_env: &HoopsTestEnvironment) {
+    let env = &test_env.env;
+    env.mock_all_auths();
+    let user = &test_env.user;
+    let aqua_adapter_client = &test_env.adapters.aqua;
+    let path = vec![env, test_env.tokens.client_a.clone(), test_env.tokens.client_b.clone()];
+    let deadline = env.ledger().timestamp() + 100;
+    if env.ledger().timestamp() > 0 {
+        let expired = env.ledger().timestamp() - 1;
+        let res = aqua_adapter_client.try_swap_exact_in(&1, &0, &path, user, &expired);
+        assert_eq!(res, Err(Ok(AdapterError::DeadlinePassed)));
+    }
+    // An LP token the adapter does not know
+    let unknown = Address::generate(env);
+    let res = aqua_adapter_client.try_remove_liquidity(&unknown, &1, &0, &0, user, &deadline);
+    assert_eq!(res, Err(Ok(AdapterError::PoolNotFound)));
+    // Exact-out input above the caller's maximum, direct and through the
+    // Aqua router's chained swap
+    let res = aqua_adapter_client.try_swap_exact_out(&1_000_000, &1, &path, user, &deadline);
+    assert_eq!(res, Err(Ok(AdapterError::MaxInExceeded)));
+    let hops = vec![
+        env,
+        test_env.tokens.client_a.clone(),
+        test_env.tokens.client_b.clone(),
+        test_env.tokens.client_c.clone(),
+    ];
+    let res = aqua_adapter_client.try_swap_exact_out(&1_000_000, &1, &hops, user, &deadline);
+    assert_eq!(res, Err(Ok(AdapterError::MaxInExceeded)));
+}
+
+pub fn run_spot_price(test_env: &HoopsTestEnvironment) {
+    let env = &test_env.env;
+    env.mock_all_auths();
+    let adapter = &test_env.adapters.aqua;
+    let pool = test_env.aqua.pool_ids.get(0).unwrap();
+    let token_a = test_env.tokens.client_a.clone();
+    let token_b = test_env.tokens.client_b.clone();
+    let spot = adapter.get_spot_price(&pool, &token_a, &token_b);
+    let fee_bps = adapter.get_pool_info(&pool).fee_bps as i128;
+    // A small trade executes just below spot, by about the fee
+    let amount_in: i128 = 1_000_000;
+    let exec = adapter.quote_in(&pool, &amount_in, &token_a, &token_b) * 10_000_000 / amount_in;
+    std::println!("[AQUA][spot_price] spot = {}, exec = {}, fee_bps = {}", spot, exec, fee_bps);
+    assert!(exec <= spot);
+    assert!(exec >= spot * (10_000 - fee_bps - 10) / 10_000);
+
+    // Only registered pools are priced
+    let res = adapter.try_get_spot_price(&token_a, &token_a, &token_b);
+    assert_eq!(res, Err(Ok(AdapterError::PoolNotFound)));
+}
+
+pub fn run_router_redeem(test_env: &HoopsTestEnvironment) {
+    let env = &test_env.env;
+    env.mock_all_auths();
+    let (token_a, token_b) = (&test_env.tokens.client_a, &test_env.tokens.client_b);
+    // Setup seeded the pool from the user
+    let pool = test_env.aqua.pool_ids.get(0).unwrap();
+    let share = AquaPoolClient::new(env, &pool).share_id();
+    test_env.router.add_adapter(&0, &test_env.adapters.aqua.address);
+    test_env.router.discover_pools(&0, &0, &10);
+    let (out_a, out_b) = redeem_with_lp_auth_only(test_env, &share, 1_000_000, token_a, token_b);
+    assert!(out_a > 0 && out_b > 0);
+}
+
 pub fn test_aqua_adapter(test_env: &HoopsTestEnvironment, failures: i32) -> i32 {
     use std::panic::AssertUnwindSafe;
     let mut failures = failures;
@@ -257,6 +801,50 @@ pub fn test_aqua_adapter(test_env: &HoopsTestEnvironment, failures: i32) -> i32
         std::println!("[FAIL][AQUA][swap_exact_out]: {:?}", e);
         failures += 1;
     }
+    if let Err(e) = std::panic::catch_unwind(AssertUnwindSafe(|| run_discovery(&test_env))) {
+        std::println!("[FAIL][AQUA][discovery]: {:?}", e);
+        failures += 1;
+    }
+    if let Err(e) = std::panic::catch_unwind(AssertUnwindSafe(|| run_error_codes(&test_env))) {
+        std::println!("[FAIL][AQUA][error_codes]: {:?}", e);
+        failures += 1;
+    }
+    if let Err(e) = std::panic::catch_unwind(AssertUnwindSafe(|| run_spot_price(&test_env))) {
+        std::println!("[FAIL][AQUA][spot_price]: {:?}", e);
+        failures += 1;
+    }
+    if let Err(e) = std::panic::catch_unwind(AssertUnwindSafe(|| run_swap_multihop(&test_env))) {
+        std::println!("[FAIL][AQUA][swap_multihop]: {:?}", e);
+        failures += 1;
+    }
+    if let Err(e) = std::panic::catch_unwind(AssertUnwindSafe(|| run_pool_mapping_auth(&test_env))) {
+        std::println!("[FAIL][AQUA][pool_mapping_auth]: {:?}", e);
+        failures += 1;
+    }
+    if let Err(e) = std::panic::catch_unwind(AssertUnwindSafe(|| run_multi_pool_selection(&test_env))) {
+        std::println!("[FAIL][AQUA][multi_pool_selection]: {:?}", e);
+        failures += 1;
+    }
+    if let Err(e) = std::panic::catch_unwind(AssertUnwindSafe(|| run_stable_liquidity(&test_env))) {
+        std::println!("[FAIL][AQUA][stable_liquidity]: {:?}", e);
+        failures += 1;
+    }
+    if let Err(e) = std::panic::catch_unwind(AssertUnwindSafe(|| run_seed_empty_pool(&test_env))) {
+        std::println!("[FAIL][AQUA][seed_empty_pool]: {:?}", e);
+        failures += 1;
+    }
+    if let Err(e) = std::panic::catch_unwind(AssertUnwindSafe(|| run_stable_set_mapping(&test_env))) {
+        std::println!("[FAIL][AQUA][stable_set_mapping]: {:?}", e);
+        failures += 1;
+    }
+    if let Err(e) = std::panic::catch_unwind(AssertUnwindSafe(|| run_stable_set_swap(&test_env))) {
+        std::println!("[FAIL][AQUA][stable_set_swap]: {:?}", e);
+        failures += 1;
+    }
+    if let Err(e) = std::panic::catch_unwind(AssertUnwindSafe(|| run_rewards(&test_env))) {
+        std::println!("[FAIL][AQUA][rewards]: {:?}", e);
+        failures += 1;
+    }
     // Run add_liquidity and capture the LP token amount
     let lp = match std::panic::catch_unwind(AssertUnwindSafe(|| run_add_liquidity(&test_env))) {
         Ok(lp) => lp,
@@ -277,6 +865,9 @@ pub fn test_aqua_adapter(test_env: &HoopsTestEnvironment, failures: i32) -> i32
     } else {
         std::println!("[INFO][AQUA] add liquidity failed, skipping remove");
     }
+    if let Err(e) = std::panic::catch_unwind(AssertUnwindSafe(|| run_router_redeem(&test_env))) {
+        std::println!("[FAIL][AQUA][router_redeem]: {:?}", e); failures += 1;
+    }
     std::println!("[AQUA] Test results: {} failures", failures);
     if failures > 0 {
         panic!(
@@ -4,14 +4,15 @@ use core::panic::AssertUnwindSafe;
 
 use soroban_sdk::token;
 #[allow(unused_imports)]
-use soroban_sdk::{Env, vec, Address, Vec};
+use soroban_sdk::{Env, vec, Address, BytesN, Vec};
+use crate::tests::test_setup::comet_factory::CometFactoryClient;
 use crate::tests::test_setup::comet_pool::CometPoolClient;
-use crate::tests::test_setup::HoopsTestEnvironment;
-use crate::tests::test_setup::comet_adapter::Client as CometAdapterClient;
+use crate::tests::test_setup::{redeem_with_lp_auth_only, HoopsTestEnvironment};
+use crate::tests::test_setup::comet_adapter::{AdapterError, Capability, Client as CometAdapterClient, PoolKind};
 extern crate std;
 
-pub fn register_comet_pool(adapter: &CometAdapterClient, tokens: Vec<Address>, pool: Address) {
-    adapter.set_pool_for_tokens(&tokens, &pool);
+pub fn register_comet_pool(adapter: &CometAdapterClient, admin: &Address, tokens: Vec<Address>, pool: Address) {
+    adapter.set_pool_for_tokens(admin, &tokens, &pool);
 }
 
 pub fn run_swap_exact_in(test_env: &HoopsTestEnvironment) {
@@ -28,7 +29,7 @@ pub fn run_swap_exact_in(test_env: &HoopsTestEnvironment) {
     let path = vec![env, token_a_client.address.clone(), token_b_client.address.clone()];
     // Register pool for this token set
     let pool = test_env.comet.pool_ids.get(0).unwrap();
-    register_comet_pool(comet_adapter_client, path.clone(), pool.clone());
+    register_comet_pool(comet_adapter_client, &test_env.admin, path.clone(), pool.clone());
     token_a_client.approve(&user, &pool, &amount_in, &(env.ledger().timestamp() as u32 + 200));
     let initial_user_balance_a = token_a_client.balance(user);
     let initial_user_balance_b = token_b_client.balance(user);
@@ -170,6 +171,270 @@ pub fn run_remove_liquidity(test_env: &HoopsTestEnvironment, lp_amt: i128) {
     assert!(after_balance_b >= amt_b_out, "User TKB balance should increase by at least amt_b_out");
 }
 
+pub fn run_single_sided(test_env: &HoopsTestEnvironment) {
+    let env = &test_env.env;
+    env.mock_all_auths();
+    let user = &test_env.user;
+    let token_a = test_env.tokens.client_a.clone();
+    let token_b = test_env.tokens.client_b.clone();
+    let token_b_client = token::Client::new(env, &token_b);
+    let comet_adapter_client = &test_env.adapters.comet;
+    let deadline = env.ledger().timestamp() + 100;
+    let pool = test_env.comet.pool_ids.get(0).unwrap();
+    let pool_client = CometPoolClient::new(env, &pool);
+    register_comet_pool(comet_adapter_client, &test_env.admin, vec![env, token_a.clone(), token_b.clone()], pool.clone());
+
+    // Enter the A/B pool with B only, through the generic add_liquidity
+    let amount_in: i128 = 100_000_000;
+    let lp_before = pool_client.balance(user);
+    let b_before = token_b_client.balance(user);
+    let (amt_a, amt_b, lp) = comet_adapter_client.add_liquidity(&token_b, &token_a, &amount_in, &0, &0, &0, user, &deadline);
+    std::println!("[COMET][single_sided] join: amt_a = {}, amt_b = {}, lp = {}", amt_a, amt_b, lp);
+    assert_eq!((amt_a, amt_b), (amount_in, 0));
+    assert!(lp > 0);
+    assert_eq!(pool_client.balance(user), lp_before + lp);
+    assert_eq!(token_b_client.balance(user), b_before - amount_in);
+
+    // Take half of it back out as B
+    let amount_out = amount_in / 2;
+    let lp_in = comet_adapter_client.withdraw_single(&pool, &token_b, &amount_out, &lp, user, &deadline);
+    std::println!("[COMET][single_sided] exit: amount_out = {}, lp_in = {}", amount_out, lp_in);
+    assert!(lp_in > 0 && lp_in <= lp);
+    assert_eq!(token_b_client.balance(user), b_before - amount_in + amount_out);
+    assert_eq!(pool_client.balance(user), lp_before + lp - lp_in);
+}
+
+pub fn run_n_token_pool(test_env: &HoopsTestEnvironment) {
+    let env = &test_env.env;
+    env.mock_all_auths();
+    let user = &test_env.user;
+    let admin = &test_env.admin;
+    let comet_adapter_client = &test_env.adapters.comet;
+    let deadline = env.ledger().timestamp() + 100;
+    let tokens = vec![
+        env,
+        test_env.tokens.client_a.clone(),
+        test_env.tokens.client_b.clone(),
+        test_env.tokens.client_c.clone(),
+    ];
+    let seed: i128 = 1_000_000_000_000;
+    for t in tokens.iter() {
+        token::StellarAssetClient::new(env, &t).mint(admin, &seed);
+    }
+    let factory = CometFactoryClient::new(env, &test_env.comet.factory_id);
+    let pool = factory.new_c_pool(
+        &BytesN::from_array(env, &[3; 32]),
+        admin,
+        &tokens,
+        &vec![env, 3_333_334i128, 3_333_333i128, 3_333_333i128],
+        &vec![env, seed, seed, seed],
+        &3_000i128,
+    );
+    let max_amounts = vec![env, 10_000_000i128, 10_000_000i128, 10_000_000i128];
+
+    // Liquidity entry points refuse pools the adapter does not know
+    let res = comet_adapter_client.try_deposit(&pool, &max_amounts, user, &deadline);
+    assert_eq!(res, Err(Ok(AdapterError::PoolNotFound)));
+    let res = comet_adapter_client.try_deposit_single(&pool, &tokens.get(0).unwrap(), &1_000_000, &0, user, &deadline);
+    assert_eq!(res, Err(Ok(AdapterError::PoolNotFound)));
+    let res = comet_adapter_client.try_withdraw(&pool, &1, &vec![env, 0i128, 0i128, 0i128], user, &deadline);
+    assert_eq!(res, Err(Ok(AdapterError::PoolNotFound)));
+    let res = comet_adapter_client.try_withdraw_single(&pool, &tokens.get(0).unwrap(), &1, &1, user, &deadline);
+    assert_eq!(res, Err(Ok(AdapterError::PoolNotFound)));
+
+    register_comet_pool(comet_adapter_client, admin, tokens.clone(), pool.clone());
+    assert_eq!(comet_adapter_client.get_pool_for_tokens(&tokens), Some(pool.clone()));
+
+    // Proportional join/exit across all three tokens
+    let pool_client = CometPoolClient::new(env, &pool);
+    let pool_tokens = pool_client.get_tokens();
+    let (taken, lp) = comet_adapter_client.deposit(&pool, &max_amounts, user, &deadline);
+    std::println!("[COMET][n_token] join: taken = {:?}, lp = {}", taken, lp);
+    assert_eq!(taken.len(), 3);
+    assert!(lp > 0);
+    for amt in taken.iter() {
+        assert!(amt > 0 && amt <= 10_000_000);
+    }
+    let received = comet_adapter_client.withdraw(&pool, &lp, &vec![env, 0i128, 0i128, 0i128], user, &deadline);
+    assert_eq!(received.len(), pool_tokens.len());
+    assert_eq!(pool_client.balance(user), 0);
+
+    // The two-token entrypoints refuse three-token pools
+    let res = comet_adapter_client.try_remove_liquidity(&pool, &1, &0, &0, user, &deadline);
+    assert!(res.is_err());
+}
+
+pub fn run_slippage_limits(test_env: &HoopsTestEnvironment) {
+    let env = &test_env.env;
+    env.mock_all_auths();
+    let user = &test_env.user;
+    let token_a = test_env.tokens.client_a.clone();
+    let token_b = test_env.tokens.client_b.clone();
+    let comet_adapter_client = &test_env.adapters.comet;
+    let deadline = env.ledger().timestamp() + 100;
+    let pool = test_env.comet.pool_ids.get(0).unwrap();
+    let path = vec![env, token_a.clone(), token_b.clone()];
+    register_comet_pool(comet_adapter_client, &test_env.admin, path.clone(), pool.clone());
+    let amount_in: i128 = 10_000_000;
+
+    // Output below min_out
+    let res = comet_adapter_client.try_swap_exact_in(&amount_in, &i128::MAX, &path, user, &deadline);
+    assert_eq!(res, Err(Ok(AdapterError::MinAmountNotMet)));
+
+    // max_price below the current spot price
+    let spot = CometPoolClient::new(env, &pool).get_spot_price(&token_a, &token_b);
+    let res = comet_adapter_client.try_swap_exact_in_limit(&amount_in, &0, &(spot / 2), &path, user, &deadline);
+    assert_eq!(res, Err(Ok(AdapterError::MinAmountNotMet)));
+
+    // A limit quoted for a smaller trade rejects a larger one (1% of the pool)
+    let large_in = CometPoolClient::new(env, &pool).get_balance(&token_a) / 100;
+    let max_price = comet_adapter_client.max_price_for(&pool, &token_a, &token_b, &(large_in / 2), &10);
+    let res = comet_adapter_client.try_swap_exact_in_limit(&large_in, &0, &max_price, &path, user, &deadline);
+    assert_eq!(res, Err(Ok(AdapterError::MinAmountNotMet)));
+
+    // Quote plus tolerance goes through
+    let max_price = comet_adapter_client.max_price_for(&pool, &token_a, &token_b, &large_in, &10);
+    assert!(max_price > spot);
+    let out = comet_adapter_client.swap_exact_in_limit(&large_in, &1, &max_price, &path, user, &deadline);
+    assert!(out > 0);
+
+    // Join minimums are checked against what the pool actually took
+    let res = comet_adapter_client.try_add_liquidity(&token_a, &token_b, &amount_in, &amount_in, &amount_in, &amount_in, user, &deadline);
+    assert_eq!(res, Err(Ok(AdapterError::MinAmountNotMet)));
+    let res = comet_adapter_client.try_add_liquidity(&token_a, &token_b, &amount_in, &0, &0, &1, user, &deadline);
+    assert_eq!(res, Err(Ok(AdapterError::MinAmountNotMet)));
+    let res = comet_adapter_client.try_add_liquidity(&token_a, &token_b, &amount_in, &0, &(amount_in + 1), &0, user, &deadline);
+    assert_eq!(res, Err(Ok(AdapterError::MinAmountNotMet)));
+
+    // Plain swaps and single-sided joins are bounded by the stored tolerance;
+    // at 1 bp the quotes must match what the pool does
+    assert_eq!(comet_adapter_client.get_price_tolerance(), 100);
+    let res = comet_adapter_client.try_set_price_tolerance(&10_000);
+    assert_eq!(res, Err(Ok(AdapterError::InvalidArgument)));
+    comet_adapter_client.set_price_tolerance(&1);
+    let (_, _, lp) = comet_adapter_client.add_liquidity(&token_a, &token_b, &amount_in, &0, &amount_in, &0, user, &deadline);
+    assert!(lp > 0);
+    let out = comet_adapter_client.swap_exact_in(&large_in, &1, &path, user, &deadline);
+    assert!(out > 0);
+    let spent = comet_adapter_client.swap_exact_out(&(out / 2), &large_in, &path, user, &deadline);
+    assert!(spent > 0 && spent < large_in);
+    comet_adapter_client.set_price_tolerance(&100);
+}
+
+pub fn run_quotes(test_env: &HoopsTestEnvironment) {
+    let env = &test_env.env;
+    env.mock_all_auths();
+    let user = &test_env.user;
+    let token_a = test_env.tokens.client_a.clone();
+    let token_b = test_env.tokens.client_b.clone();
+    let comet_adapter_client = &test_env.adapters.comet;
+    let deadline = env.ledger().timestamp() + 100;
+    let pool = test_env.comet.pool_ids.get(0).unwrap();
+    let path = vec![env, token_a.clone(), token_b.clone()];
+    register_comet_pool(comet_adapter_client, &test_env.admin, path.clone(), pool.clone());
+    let amount_in: i128 = 100_000_000;
+
+    // Quotes follow the pool's weighted math, fee included. This runs the
+    // adapter wasm in bytecodes/; the math itself is unit tested in
+    // adapters/comet/src/math.rs
+    let quoted_out = comet_adapter_client.quote_in(&pool, &amount_in, &token_a, &token_b);
+    let needed_in = comet_adapter_client.quote_out(&pool, &quoted_out, &token_a, &token_b);
+    std::println!("[COMET][quotes] in = {}, quoted_out = {}, needed_in = {}", amount_in, quoted_out, needed_in);
+    assert!((needed_in - amount_in).abs() <= 10);
+    let out = comet_adapter_client.swap_exact_in(&amount_in, &0, &path, user, &deadline);
+    assert!((out - quoted_out).abs() <= 10, "quote {} vs swap {}", quoted_out, out);
+
+    // Comet's trade size limits are reported up front
+    let balance_a = CometPoolClient::new(env, &pool).get_balance(&token_a);
+    let res = comet_adapter_client.try_quote_in(&pool, &balance_a, &token_a, &token_b);
+    assert_eq!(res, Err(Ok(AdapterError::MaxInRatio)));
+}
+
+pub fn run_discovery(test_env: &HoopsTestEnvironment) {
+    let env = &test_env.env;
+    env.mock_all_auths();
+    let comet_adapter_client = &test_env.adapters.comet;
+    let pool = test_env.comet.pool_ids.get(0).unwrap();
+    let tokens = vec![env, test_env.tokens.client_a.clone(), test_env.tokens.client_b.clone()];
+    register_comet_pool(comet_adapter_client, &test_env.admin, tokens.clone(), pool.clone());
+    assert!(comet_adapter_client.list_pools(&0, &100).contains(&pool));
+
+    let info = comet_adapter_client.get_pool_info(&pool);
+    let pool_client = CometPoolClient::new(env, &pool);
+    assert_eq!(info.tokens, pool_client.get_tokens());
+    assert_eq!(info.lp_token, pool);
+    assert_eq!(info.kind, PoolKind::Weighted);
+    assert_eq!(info.weights.len(), 2);
+    assert!(info.weights.get(0).unwrap() > info.weights.get(1).unwrap());
+    assert_eq!(info.reserves.get(0).unwrap(), pool_client.get_balance(&info.tokens.get(0).unwrap()));
+    assert_eq!(info.lp_supply, pool_client.get_total_supply());
+
+    assert!(comet_adapter_client.supports(&Capability::SingleSidedLp));
+    assert!(!comet_adapter_client.supports(&Capability::MultiHop));
+
+    // Dropping the only mapping drops the pool from the list
+    comet_adapter_client.remove_pool_for_tokens(&test_env.admin, &tokens);
+    assert!(!comet_adapter_client.list_pools(&0, &100).contains(&pool));
+    register_comet_pool(comet_adapter_client, &test_env.admin, tokens, pool.clone());
+}
+
+pub fn run_error_codes(test_env: &HoopsTestEnvironment) {
+    let env = &test_env.env;
+    env.mock_all_auths();
+    let user = &test_env.user;
+    let comet_adapter_client = &test_env.adapters.comet;
+    let token_a = test_env.tokens.client_a.clone();
+    let token_b = test_env.tokens.client_b.clone();
+    let deadline = env.ledger().timestamp() + 100;
+    if env.ledger().timestamp() > 0 {
+        let expired = env.ledger().timestamp() - 1;
+        let res = comet_adapter_client.try_swap_exact_in(&1, &0, &vec![env, token_a.clone(), token_b.clone()], user, &expired);
+        assert_eq!(res, Err(Ok(AdapterError::DeadlinePassed)));
+    }
+    // No pool registered for A/C
+    let res = comet_adapter_client.try_swap_exact_in(&1, &0, &vec![env, token_a.clone(), test_env.tokens.client_c.clone()], user, &deadline);
+    assert_eq!(res, Err(Ok(AdapterError::PoolNotFound)));
+    let res = comet_adapter_client.try_swap_exact_in(&1, &0, &vec![env, token_a.clone(), token_b.clone(), token_a.clone()], user, &deadline);
+    assert_eq!(res, Err(Ok(AdapterError::MultipathUnsupported)));
+    // Trading more than half the pool trips Comet's ratio limit
+    let pool = test_env.comet.pool_ids.get(0).unwrap();
+    let path = vec![env, token_a.clone(), token_b.clone()];
+    register_comet_pool(comet_adapter_client, &test_env.admin, path.clone(), pool.clone());
+    let balance_a = CometPoolClient::new(env, &pool).get_balance(&token_a);
+    let res = comet_adapter_client.try_swap_exact_in(&balance_a, &0, &path, user, &deadline);
+    assert_eq!(res, Err(Ok(AdapterError::MaxInRatio)));
+}
+
+pub fn run_spot_price(test_env: &HoopsTestEnvironment) {
+    let env = &test_env.env;
+    env.mock_all_auths();
+    let adapter = &test_env.adapters.comet;
+    let pool = test_env.comet.pool_ids.get(0).unwrap();
+    let token_a = test_env.tokens.client_a.clone();
+    let token_b = test_env.tokens.client_b.clone();
+    register_comet_pool(adapter, &test_env.admin, vec![env, token_a.clone(), token_b.clone()], pool.clone());
+    let spot = adapter.get_spot_price(&pool, &token_a, &token_b);
+    let fee_bps = adapter.get_pool_info(&pool).fee_bps as i128;
+    // A small trade executes just below spot, by about the fee
+    let amount_in: i128 = 1_000_000;
+    let exec = adapter.quote_in(&pool, &amount_in, &token_a, &token_b) * 10_000_000 / amount_in;
+    std::println!("[COMET][spot_price] spot = {}, exec = {}, fee_bps = {}", spot, exec, fee_bps);
+    assert!(exec <= spot);
+    assert!(exec >= spot * (10_000 - fee_bps - 10) / 10_000);
+}
+
+pub fn run_router_redeem(test_env: &HoopsTestEnvironment) {
+    let env = &test_env.env;
+    env.mock_all_auths();
+    let (token_a, token_b) = (&test_env.tokens.client_a, &test_env.tokens.client_b);
+    // The pool is its own LP token; setup seeded it from the user
+    let pool = test_env.comet.pool_ids.get(0).unwrap();
+    test_env.router.add_adapter(&1, &test_env.adapters.comet.address);
+    test_env.router.discover_pools(&1, &0, &10);
+    let (out_a, out_b) = redeem_with_lp_auth_only(test_env, &pool, 1_000_000, token_a, token_b);
+    assert!(out_a > 0 && out_b > 0);
+}
+
 pub fn test_comet_adapter(test_env: &HoopsTestEnvironment, failures: i32) -> i32{
 
     let mut failures = failures;
@@ -180,6 +445,27 @@ pub fn test_comet_adapter(test_env: &HoopsTestEnvironment, failures: i32) -> i32
     if let Err(e) = std::panic::catch_unwind(AssertUnwindSafe(|| run_swap_exact_out(&test_env))) {
         std::println!("[FAIL][COMET][swap_exact_out]: {:?}", e); failures += 1;
     }
+    if let Err(e) = std::panic::catch_unwind(AssertUnwindSafe(|| run_discovery(&test_env))) {
+        std::println!("[FAIL][COMET][discovery]: {:?}", e); failures += 1;
+    }
+    if let Err(e) = std::panic::catch_unwind(AssertUnwindSafe(|| run_error_codes(&test_env))) {
+        std::println!("[FAIL][COMET][error_codes]: {:?}", e); failures += 1;
+    }
+    if let Err(e) = std::panic::catch_unwind(AssertUnwindSafe(|| run_spot_price(&test_env))) {
+        std::println!("[FAIL][COMET][spot_price]: {:?}", e); failures += 1;
+    }
+    if let Err(e) = std::panic::catch_unwind(AssertUnwindSafe(|| run_single_sided(&test_env))) {
+        std::println!("[FAIL][COMET][single_sided]: {:?}", e); failures += 1;
+    }
+    if let Err(e) = std::panic::catch_unwind(AssertUnwindSafe(|| run_n_token_pool(&test_env))) {
+        std::println!("[FAIL][COMET][n_token_pool]: {:?}", e); failures += 1;
+    }
+    if let Err(e) = std::panic::catch_unwind(AssertUnwindSafe(|| run_slippage_limits(&test_env))) {
+        std::println!("[FAIL][COMET][slippage_limits]: {:?}", e); failures += 1;
+    }
+    if let Err(e) = std::panic::catch_unwind(AssertUnwindSafe(|| run_quotes(&test_env))) {
+        std::println!("[FAIL][COMET][quotes]: {:?}", e); failures += 1;
+    }
     
     // Run add_liquidity and capture the LP token amount
     let lp = match std::panic::catch_unwind(AssertUnwindSafe(|| run_add_liquidity(&test_env))) {
@@ -198,6 +484,9 @@ pub fn test_comet_adapter(test_env: &HoopsTestEnvironment, failures: i32) -> i32
     } else {
         std::println!("[INFO][COMET] add liquidity failed, skipping remove");
     }
+    if let Err(e) = std::panic::catch_unwind(AssertUnwindSafe(|| run_router_redeem(&test_env))) {
+        std::println!("[FAIL][COMET][router_redeem]: {:?}", e); failures += 1;
+    }
     std::println!("[COMET] Test results: {} failures", failures);
     failures
 }
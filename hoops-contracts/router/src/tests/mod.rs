@@ -6,4 +6,4 @@ pub mod soroswap_adapter_tests;
 pub mod setuputils;
 pub mod aqua_adapter_tests;
 pub mod phoenix_adapter_tests;
-pub mod comet_adapter_tests;
\ No newline at end of file
+pub mod comet_adapter_tests;pub mod router_tests;
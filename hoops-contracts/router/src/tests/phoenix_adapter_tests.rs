@@ -4,7 +4,8 @@ use soroban_sdk::token;
 #[allow(unused_imports)]
 use soroban_sdk::{Env, vec};
 
-use crate::tests::test_setup::{PhoenixPoolClient, HoopsTestEnvironment, phoenix_pool};
+use crate::tests::test_setup::{PhoenixPoolClient, HoopsTestEnvironment, phoenix_pool, redeem_with_lp_auth_only};
+use crate::tests::test_setup::phoenix_adapter::{AdapterError, Capability, PoolKind};
 extern crate std;
 
 
@@ -43,7 +44,10 @@ pub fn run_swap_exact_in(test_env: &HoopsTestEnvironment) {
     std::println!("[PHOENIX][swap_exact_in] Initial user balances: TKA = {:.7}, TKB = {:.7}", initial_user_balance_a as f64 * 1e-7, initial_user_balance_b as f64 * 1e-7);
     std::println!("[PHOENIX][swap_exact_in] Pool reserves: TKA = {:.7}, TKB = {:.7}", reserve_a as f64 * 1e-7, reserve_b as f64 * 1e-7);
     std::println!("[PHOENIX][swap_exact_in] amount_in = {:.7}, min_out = {:.7}, expected_out = {:.7}, slippage_bps = {}", amount_in as f64 * 1e-7, min_out as f64 * 1e-7, expected_out as f64 * 1e-7, slippage_bps);
+    let quoted = phoenix_adapter_client.quote_in(pool_addr, &amount_in, &token_a_client.address, &token_b_client.address);
     let amount_out = phoenix_adapter_client.swap_exact_in(&amount_in, &min_out, &path, user, &deadline);
+    // The quote is what the trader receives, after spread and commission
+    assert_eq!(amount_out, quoted, "quote_in should match the swap output");
     std::println!("[PHOENIX][swap_exact_in] Swap result: amount_in = {:.7}, amount_out = {:.7}", amount_in as f64 * 1e-7, amount_out as f64 * 1e-7);
     let final_user_balance_a = token_a_client.balance(user);
     let final_user_balance_b = token_b_client.balance(user);
@@ -158,9 +162,101 @@ pub fn run_remove_liquidity(test_env: &HoopsTestEnvironment, lp_amt: i128) {
     assert!(after_lp < user_lp_balance, "User LP balance should decrease");
 }
 
+pub fn run_discovery(test_env: &HoopsTestEnvironment) {
+    let env = &test_env.env;
+    let phoenix_adapter_client = &test_env.adapters.phoenix;
+    let pool_addr = test_env.phoenix.pool_ids.get(0).unwrap();
+    assert_eq!(phoenix_adapter_client.list_pools(&0, &100), vec![env, pool_addr.clone()]);
+
+    let info = phoenix_adapter_client.get_pool_info(&pool_addr);
+    let pool_info = get_pool_info(env, &pool_addr);
+    assert_eq!(info.tokens, vec![env, pool_info.asset_a.address, pool_info.asset_b.address]);
+    assert_eq!(info.reserves, vec![env, pool_info.asset_a.amount, pool_info.asset_b.amount]);
+    assert_eq!(info.lp_token, pool_info.asset_lp_share.address);
+    assert_eq!(info.lp_supply, pool_info.asset_lp_share.amount);
+    assert_eq!(info.kind, PoolKind::ConstantProduct);
+
+    assert!(phoenix_adapter_client.supports(&Capability::ExactOut));
+    assert!(!phoenix_adapter_client.supports(&Capability::MultiHop));
+    // Only the configured pool is known
+    assert!(phoenix_adapter_client.try_get_pool_info(&test_env.comet.pool_ids.get(0).unwrap()).is_err());
+}
+
+pub fn run_error_codes(test_env: &HoopsTestEnvironment) {
+    let env = &test_env.env;
+    env.mock_all_auths();
+    let user = &test_env.user;
+    let phoenix_adapter_client = &test_env.adapters.phoenix;
+    let token_a = test_env.tokens.client_a.clone();
+    let token_b = test_env.tokens.client_b.clone();
+    let path = vec![env, token_a.clone(), token_b.clone()];
+    let amount_in: i128 = 10_000_000;
+    let deadline = env.ledger().timestamp() + 100;
+    if env.ledger().timestamp() > 0 {
+        let expired = env.ledger().timestamp() - 1;
+        let res = phoenix_adapter_client.try_swap_exact_in(&amount_in, &0, &path, user, &expired);
+        assert_eq!(res, Err(Ok(AdapterError::DeadlinePassed)));
+    }
+    // Pool slippage checks surface as MinAmountNotMet
+    let res = phoenix_adapter_client.try_swap_exact_in(&amount_in, &i128::MAX, &path, user, &deadline);
+    assert_eq!(res, Err(Ok(AdapterError::MinAmountNotMet)));
+    let res = phoenix_adapter_client.try_swap_exact_out(&amount_in, &1, &path, user, &deadline);
+    assert_eq!(res, Err(Ok(AdapterError::MaxInExceeded)));
+    // Only single hops through the configured pool
+    let res = phoenix_adapter_client.try_swap_exact_in(&amount_in, &0, &vec![env, token_a.clone()], user, &deadline);
+    assert_eq!(res, Err(Ok(AdapterError::InvalidPath)));
+    let res = phoenix_adapter_client.try_swap_exact_in(&amount_in, &0, &vec![env, token_a.clone(), token_b.clone(), token_a.clone()], user, &deadline);
+    assert_eq!(res, Err(Ok(AdapterError::MultipathUnsupported)));
+    let foreign = test_env.comet.pool_ids.get(0).unwrap();
+    let res = phoenix_adapter_client.try_quote_in(&foreign, &amount_in, &token_a, &token_b);
+    assert_eq!(res, Err(Ok(AdapterError::PoolNotFound)));
+}
+
+pub fn run_spot_price(test_env: &HoopsTestEnvironment) {
+    let env = &test_env.env;
+    env.mock_all_auths();
+    let adapter = &test_env.adapters.phoenix;
+    let pool = test_env.phoenix.pool_ids.get(0).unwrap();
+    let token_a = test_env.tokens.client_a.clone();
+    let token_b = test_env.tokens.client_b.clone();
+    let spot = adapter.get_spot_price(&pool, &token_a, &token_b);
+    let fee_bps = adapter.get_pool_info(&pool).fee_bps as i128;
+    // A 100 unit trade moves the price by under a basis point, so it
+    // executes just below spot, by about the fee
+    let amount_in: i128 = 1_000_000_000;
+    let exec = adapter.quote_in(&pool, &amount_in, &token_a, &token_b) * 10_000_000 / amount_in;
+    std::println!("[PHOENIX][spot_price] spot = {}, exec = {}, fee_bps = {}", spot, exec, fee_bps);
+    assert!(exec <= spot);
+    assert!(exec >= spot * (10_000 - fee_bps - 10) / 10_000);
+}
+
+pub fn run_router_redeem(test_env: &HoopsTestEnvironment) {
+    let env = &test_env.env;
+    env.mock_all_auths();
+    let (token_a, token_b) = (&test_env.tokens.client_a, &test_env.tokens.client_b);
+    let pool_addr = test_env.phoenix.pool_ids.get(0).unwrap();
+    let share = get_pool_info(env, &pool_addr).asset_lp_share.address;
+    test_env.router.add_adapter(&2, &test_env.adapters.phoenix.address);
+    test_env.router.discover_pools(&2, &0, &1);
+    // Setup seeded the pool from the admin
+    let lp_amount: i128 = 1_000_000;
+    token::Client::new(env, &share).transfer(&test_env.admin, &test_env.user, &lp_amount);
+    let (out_a, out_b) = redeem_with_lp_auth_only(test_env, &share, lp_amount, token_a, token_b);
+    assert!(out_a > 0 && out_b > 0);
+}
+
 pub fn test_phoenix_adapter(test_env: &HoopsTestEnvironment, failures: i32) -> i32 {
     use std::panic::AssertUnwindSafe;
     let mut failures = failures;
+    if let Err(e) = std::panic::catch_unwind(AssertUnwindSafe(|| run_discovery(&test_env))) {
+        std::println!("[FAIL][PHOENIX][discovery]: {:?}", e); failures += 1;
+    }
+    if let Err(e) = std::panic::catch_unwind(AssertUnwindSafe(|| run_error_codes(&test_env))) {
+        std::println!("[FAIL][PHOENIX][error_codes]: {:?}", e); failures += 1;
+    }
+    if let Err(e) = std::panic::catch_unwind(AssertUnwindSafe(|| run_spot_price(&test_env))) {
+        std::println!("[FAIL][PHOENIX][spot_price]: {:?}", e); failures += 1;
+    }
     if let Err(e) = std::panic::catch_unwind(AssertUnwindSafe(|| run_swap_exact_in(&test_env))) {
         std::println!("[FAIL][PHOENIX][swap_exact_in]: {:?}", e); failures += 1;
     }
@@ -183,6 +279,9 @@ pub fn test_phoenix_adapter(test_env: &HoopsTestEnvironment, failures: i32) -> i
     } else {
         std::println!("[INFO][PHOENIX] add liquidity failed, skipping remove");
     }
+    if let Err(e) = std::panic::catch_unwind(AssertUnwindSafe(|| run_router_redeem(&test_env))) {
+        std::println!("[FAIL][PHOENIX][router_redeem]: {:?}", e); failures += 1;
+    }
     std::println!("[PHOENIX] Test results: {} failures", failures);
     if failures > 0 {
         panic!("{} Phoenix adapter subtests failed. See log for details.", failures);
@@ -0,0 +1,386 @@
+use soroban_sdk::{
+    contract, contractimpl, contracttype, panic_with_error,
+    testutils::{Address as _, MockAuth, MockAuthInvoke},
+    token, vec, Address, Env, IntoVal, Vec,
+};
+
+use hoops_adapter_interface::AdapterError;
+
+use crate::types::{MarketData, QuoteKind, QuoteRequest};
+use crate::{HoopsRouter, HoopsRouterClient, RouterError, MAX_SIMULATE_REQUESTS};
+
+#[contracttype]
+enum MockKey {
+    Pending(Address),
+    FailClaims,
+}
+
+/// Rewards adapter whose pending amounts are set by the test. With
+/// `FailClaims` set every `claim` panics.
+#[contract]
+pub struct MockRewards;
+
+#[contractimpl]
+impl MockRewards {
+    pub fn set_reward(e: Env, lp: Address, pending: i128) {
+        e.storage().instance().set(&MockKey::Pending(lp), &pending);
+    }
+
+    pub fn set_fail_claims(e: Env, fail: bool) {
+        e.storage().instance().set(&MockKey::FailClaims, &fail);
+    }
+
+    pub fn get_user_reward(e: Env, _user: Address, lp: Address) -> i128 {
+        e.storage().instance().get(&MockKey::Pending(lp)).unwrap_or(0)
+    }
+
+    pub fn claim(e: Env, _user: Address, lp: Address, _to: Address) -> i128 {
+        if e.storage().instance().get(&MockKey::FailClaims).unwrap_or(false) {
+            panic_with_error!(&e, AdapterError::ExternalFailure);
+        }
+        let pending: i128 = e.storage().instance().get(&MockKey::Pending(lp.clone())).unwrap_or(0);
+        e.storage().instance().set(&MockKey::Pending(lp), &0i128);
+        pending
+    }
+}
+
+#[contracttype]
+enum MockSwapKey {
+    Rate(Address),
+    Frozen(Address),
+    Short,
+}
+
+/// Adapter whose pools pay `amount_in * rate` from the adapter's balance. A
+/// frozen pool fails after the input was taken, like a pool whose swaps were
+/// killed. With `Short` set exact-out swaps deliver one unit less than they
+/// report.
+#[contract]
+pub struct MockSwap;
+
+#[contractimpl]
+impl MockSwap {
+    pub fn set_rate(e: Env, pool: Address, rate: i128) {
+        e.storage().instance().set(&MockSwapKey::Rate(pool), &rate);
+    }
+
+    pub fn set_frozen(e: Env, pool: Address, frozen: bool) {
+        e.storage().instance().set(&MockSwapKey::Frozen(pool), &frozen);
+    }
+
+    pub fn set_short(e: Env, short: bool) {
+        e.storage().instance().set(&MockSwapKey::Short, &short);
+    }
+
+    pub fn quote_out(e: Env, pool: Address, amount_out: i128, _token_in: Address, _token_out: Address) -> i128 {
+        let rate: i128 = e.storage().instance().get(&MockSwapKey::Rate(pool)).unwrap();
+        (amount_out + rate - 1) / rate
+    }
+
+    pub fn swap_exact_out_on(e: Env, pool: Address, amount_out: i128, max_in: i128, path: Vec<Address>, to: Address, _deadline: u64) -> i128 {
+        let me = e.current_contract_address();
+        token::Client::new(&e, &path.get_unchecked(0)).transfer(&to, &me, &max_in);
+        if e.storage().instance().get(&MockSwapKey::Frozen(pool.clone())).unwrap_or(false) {
+            panic_with_error!(&e, AdapterError::ExternalFailure);
+        }
+        let amount_in = Self::quote_out(e.clone(), pool, amount_out, me.clone(), me.clone());
+        let short = e.storage().instance().get(&MockSwapKey::Short).unwrap_or(false);
+        let paid = if short { amount_out - 1 } else { amount_out };
+        token::Client::new(&e, &path.get_unchecked(1)).transfer(&me, &to, &paid);
+        token::Client::new(&e, &path.get_unchecked(0)).transfer(&me, &to, &(max_in - amount_in));
+        amount_in
+    }
+
+    pub fn quote_in(e: Env, pool: Address, amount_in: i128, _token_in: Address, _token_out: Address) -> i128 {
+        amount_in * e.storage().instance().get::<_, i128>(&MockSwapKey::Rate(pool)).unwrap()
+    }
+
+    pub fn swap_exact_in_on(e: Env, pool: Address, amount_in: i128, min_out: i128, path: Vec<Address>, to: Address, _deadline: u64) -> i128 {
+        let me = e.current_contract_address();
+        token::Client::new(&e, &path.get_unchecked(0)).transfer(&to, &me, &amount_in);
+        if e.storage().instance().get(&MockSwapKey::Frozen(pool.clone())).unwrap_or(false) {
+            panic_with_error!(&e, AdapterError::ExternalFailure);
+        }
+        let amount_out = Self::quote_in(e.clone(), pool, amount_in, me.clone(), me.clone());
+        if amount_out < min_out {
+            panic_with_error!(&e, AdapterError::MinAmountNotMet);
+        }
+        token::Client::new(&e, &path.get_unchecked(1)).transfer(&me, &to, &amount_out);
+        amount_out
+    }
+}
+
+fn market(e: &Env, adapter_id: i128, lp_token: &Address) -> MarketData {
+    MarketData {
+        adapter_id,
+        pool_address: Address::generate(e),
+        lp_token: lp_token.clone(),
+        token_a: Address::generate(e),
+        token_b: Address::generate(e),
+        reserve_a: 0,
+        reserve_b: 0,
+        pool_type: 0,
+        ledger: e.ledger().sequence(),
+    }
+}
+
+#[test]
+fn test_claim_all_rewards_skips_failures_and_pages() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let admin = Address::generate(&e);
+    let user = Address::generate(&e);
+
+    let router = HoopsRouterClient::new(&e, &e.register(HoopsRouter, ()));
+    router.initialize(&admin);
+
+    let good_id = e.register(MockRewards, ());
+    let bad_id = e.register(MockRewards, ());
+    let good = MockRewardsClient::new(&e, &good_id);
+    let bad = MockRewardsClient::new(&e, &bad_id);
+    bad.set_fail_claims(&true);
+    router.add_adapter(&10, &good_id);
+    router.add_adapter(&11, &bad_id);
+
+    let (lp0, lp1, lp2) = (Address::generate(&e), Address::generate(&e), Address::generate(&e));
+    good.set_reward(&lp0, &5);
+    bad.set_reward(&lp1, &7);
+    good.set_reward(&lp2, &11);
+    router.add_markets(&vec![&e, market(&e, 10, &lp0), market(&e, 11, &lp1), market(&e, 10, &lp2)]);
+
+    assert_eq!(router.get_user_rewards(&user, &0, &10).len(), 3);
+    let page = router.get_user_rewards(&user, &1, &1);
+    assert_eq!(page.len(), 1);
+    assert_eq!(page.get(0).unwrap().lp_token, lp1);
+    assert_eq!(router.get_user_rewards(&user, &10, &5).len(), 0);
+
+    // The failing claim on lp1 is skipped; lp2 is outside the range
+    assert_eq!(router.claim_all_rewards(&user, &0, &2), 5);
+    assert_eq!(good.get_user_reward(&user, &lp0), 0);
+    assert_eq!(bad.get_user_reward(&user, &lp1), 7);
+    assert_eq!(good.get_user_reward(&user, &lp2), 11);
+
+    assert_eq!(router.claim_all_rewards(&user, &2, &5), 11);
+    assert_eq!(good.get_user_reward(&user, &lp2), 0);
+}
+
+struct SwapSetup {
+    e: Env,
+    router: HoopsRouterClient<'static>,
+    user: Address,
+    token_in: token::Client<'static>,
+    token_out: token::Client<'static>,
+    frozen: MockSwapClient<'static>,
+    live: MockSwapClient<'static>,
+    frozen_pool: Address,
+    live_pool: Address,
+}
+
+/// A router with two markets for one pair: adapter 20's pool quotes best but
+/// is frozen, adapter 21's pool quotes less and fills.
+fn swap_setup() -> SwapSetup {
+    let e = Env::default();
+    e.mock_all_auths();
+    let admin = Address::generate(&e);
+    let user = Address::generate(&e);
+    let router = HoopsRouterClient::new(&e, &e.register(HoopsRouter, ()));
+    router.initialize(&admin);
+
+    let token_in = e.register_stellar_asset_contract_v2(admin.clone()).address();
+    let token_out = e.register_stellar_asset_contract_v2(admin.clone()).address();
+    token::StellarAssetClient::new(&e, &token_in).mint(&user, &1_000);
+
+    let frozen = MockSwapClient::new(&e, &e.register(MockSwap, ()));
+    let live = MockSwapClient::new(&e, &e.register(MockSwap, ()));
+    let (frozen_pool, live_pool) = (Address::generate(&e), Address::generate(&e));
+    frozen.set_rate(&frozen_pool, &3);
+    frozen.set_frozen(&frozen_pool, &true);
+    live.set_rate(&live_pool, &2);
+    for adapter in [&frozen, &live] {
+        token::StellarAssetClient::new(&e, &token_out).mint(&adapter.address, &10_000);
+    }
+    router.add_adapter(&20, &frozen.address);
+    router.add_adapter(&21, &live.address);
+    let mut markets = Vec::new(&e);
+    for (id, pool) in [(20, &frozen_pool), (21, &live_pool)] {
+        markets.push_back(pair_market(&e, id, pool, &token_in, &token_out));
+    }
+    router.add_markets(&markets);
+
+    SwapSetup {
+        token_in: token::Client::new(&e, &token_in),
+        token_out: token::Client::new(&e, &token_out),
+        e,
+        router,
+        user,
+        frozen,
+        live,
+        frozen_pool,
+        live_pool,
+    }
+}
+
+fn pair_market(e: &Env, adapter_id: i128, pool: &Address, token_in: &Address, token_out: &Address) -> MarketData {
+    let mut m = market(e, adapter_id, &Address::generate(e));
+    m.pool_address = pool.clone();
+    m.token_a = token_in.clone();
+    m.token_b = token_out.clone();
+    m
+}
+
+/// Adds a live pool at rate 3 to the frozen adapter 20 and raises its frozen
+/// pool to rate 4, so the adapter's second pool ranks between the two.
+fn add_second_pool(s: &SwapSetup) -> Address {
+    let pool = Address::generate(&s.e);
+    s.frozen.set_rate(&s.frozen_pool, &4);
+    s.frozen.set_rate(&pool, &3);
+    s.router.add_markets(&vec![&s.e, pair_market(&s.e, 20, &pool, &s.token_in.address, &s.token_out.address)]);
+    pool
+}
+
+/// Only the user's auth is mocked, for the swap and the transfer of
+/// `pulled` into the router; the router authorizes the adapter's pull itself.
+fn mock_user_swap(s: &SwapSetup, fn_name: &str, amount: i128, limit: i128, pulled: i128) {
+    let args = (
+        amount,
+        limit,
+        s.token_in.address.clone(),
+        s.token_out.address.clone(),
+        s.user.clone(),
+        u64::MAX,
+    );
+    s.e.mock_auths(&[MockAuth {
+        address: &s.user,
+        invoke: &MockAuthInvoke {
+            contract: &s.router.address,
+            fn_name,
+            args: args.into_val(&s.e),
+            sub_invokes: &[MockAuthInvoke {
+                contract: &s.token_in.address,
+                fn_name: "transfer",
+                args: (&s.user, &s.router.address, pulled).into_val(&s.e),
+                sub_invokes: &[],
+            }],
+        },
+    }]);
+}
+
+#[test]
+fn test_swap_exact_in_falls_back_from_frozen_pool() {
+    let s = swap_setup();
+    mock_user_swap(&s, "swap_exact_in", 100, 150, 100);
+    let res = s.router.swap_exact_in(&100, &150, &s.token_in.address, &s.token_out.address, &s.user, &u64::MAX);
+    assert_eq!(res.adapter_id, 21);
+    assert_eq!(res.pool_address, s.live_pool);
+    assert_eq!(res.amount_out, 200);
+    assert_eq!(s.token_in.balance(&s.user), 900);
+    assert_eq!(s.token_out.balance(&s.user), 200);
+    // The frozen attempt was rolled back and the router keeps nothing
+    assert_eq!(s.token_in.balance(&s.frozen.address), 0);
+    assert_eq!(s.token_in.balance(&s.live.address), 100);
+    assert_eq!(s.token_in.balance(&s.router.address), 0);
+    assert_eq!(s.token_out.balance(&s.router.address), 0);
+}
+
+#[test]
+fn test_swap_exact_in_falls_back_to_another_pool_of_the_same_adapter() {
+    let s = swap_setup();
+    let second = add_second_pool(&s);
+    mock_user_swap(&s, "swap_exact_in", 100, 150, 100);
+    let res = s.router.swap_exact_in(&100, &150, &s.token_in.address, &s.token_out.address, &s.user, &u64::MAX);
+    assert_eq!(res.adapter_id, 20);
+    assert_eq!(res.pool_address, second);
+    assert_eq!(res.amount_out, 300);
+    assert_eq!(s.token_out.balance(&s.user), 300);
+    assert_eq!(s.token_in.balance(&s.frozen.address), 100);
+    assert_eq!(s.token_in.balance(&s.live.address), 0);
+}
+
+#[test]
+fn test_swap_exact_in_reverts_when_every_market_fails() {
+    let s = swap_setup();
+    s.live.set_frozen(&s.live_pool, &true);
+    mock_user_swap(&s, "swap_exact_in", 100, 150, 100);
+    let res = s.router.try_swap_exact_in(&100, &150, &s.token_in.address, &s.token_out.address, &s.user, &u64::MAX);
+    assert_eq!(res, Err(Ok(RouterError::ExternalFailure)));
+    assert_eq!(s.token_in.balance(&s.user), 1_000);
+    assert_eq!(s.token_in.balance(&s.router.address), 0);
+
+    // The live market alone does not meet a higher minimum
+    s.live.set_frozen(&s.live_pool, &false);
+    mock_user_swap(&s, "swap_exact_in", 100, 250, 100);
+    let res = s.router.try_swap_exact_in(&100, &250, &s.token_in.address, &s.token_out.address, &s.user, &u64::MAX);
+    assert_eq!(res, Err(Ok(RouterError::ExternalFailure)));
+    assert_eq!(s.token_in.balance(&s.user), 1_000);
+}
+
+#[test]
+fn test_simulate_swaps_caps_batch_size() {
+    let s = swap_setup();
+    let req = QuoteRequest {
+        token_in: s.token_in.address.clone(),
+        token_out: s.token_out.address.clone(),
+        amount: 100,
+        kind: QuoteKind::ExactIn,
+    };
+    let mut requests = Vec::new(&s.e);
+    for _ in 0..MAX_SIMULATE_REQUESTS {
+        requests.push_back(req.clone());
+    }
+    let results = s.router.simulate_swaps(&requests);
+    assert_eq!(results.len(), MAX_SIMULATE_REQUESTS);
+    assert_eq!(results.get(0).unwrap().quotes.len(), 2);
+
+    requests.push_back(req);
+    let res = s.router.try_simulate_swaps(&requests);
+    assert_eq!(res, Err(Ok(soroban_sdk::Error::from_contract_error(RouterError::InvalidArgument as u32))));
+}
+
+#[test]
+fn test_swap_exact_out_refunds_unused_input() {
+    let s = swap_setup();
+    // The frozen market needs 67 and fails; the live one needs 100
+    mock_user_swap(&s, "swap_exact_out", 200, 150, 150);
+    let res = s.router.swap_exact_out(&200, &150, &s.token_in.address, &s.token_out.address, &s.user, &u64::MAX);
+    assert_eq!(res.adapter_id, 21);
+    assert_eq!(res.pool_address, s.live_pool);
+    assert_eq!(res.amount_in, 100);
+    assert_eq!(s.token_in.balance(&s.user), 900);
+    assert_eq!(s.token_out.balance(&s.user), 200);
+    assert_eq!(s.token_in.balance(&s.router.address), 0);
+    assert_eq!(s.token_in.balance(&s.frozen.address), 0);
+
+    // Nothing spent when every market fails
+    s.live.set_frozen(&s.live_pool, &true);
+    mock_user_swap(&s, "swap_exact_out", 200, 150, 150);
+    let res = s.router.try_swap_exact_out(&200, &150, &s.token_in.address, &s.token_out.address, &s.user, &u64::MAX);
+    assert_eq!(res, Err(Ok(RouterError::ExternalFailure)));
+    assert_eq!(s.token_in.balance(&s.user), 900);
+}
+
+#[test]
+fn test_swap_exact_out_falls_back_to_another_pool_of_the_same_adapter() {
+    let s = swap_setup();
+    let second = add_second_pool(&s);
+    // The frozen pool needs 75, the adapter's second pool 100, adapter 21 150
+    mock_user_swap(&s, "swap_exact_out", 300, 150, 150);
+    let res = s.router.swap_exact_out(&300, &150, &s.token_in.address, &s.token_out.address, &s.user, &u64::MAX);
+    assert_eq!(res.adapter_id, 20);
+    assert_eq!(res.pool_address, second);
+    assert_eq!(res.amount_in, 100);
+    assert_eq!(s.token_in.balance(&s.user), 900);
+    assert_eq!(s.token_out.balance(&s.user), 300);
+    assert_eq!(s.token_in.balance(&s.router.address), 0);
+}
+
+#[test]
+fn test_swap_exact_out_rejects_short_delivery() {
+    let s = swap_setup();
+    s.frozen.set_frozen(&s.frozen_pool, &false);
+    s.frozen.set_short(&true);
+    mock_user_swap(&s, "swap_exact_out", 200, 150, 150);
+    let res = s.router.try_swap_exact_out(&200, &150, &s.token_in.address, &s.token_out.address, &s.user, &u64::MAX);
+    assert_eq!(res, Err(Ok(RouterError::MinAmountNotMet)));
+    assert_eq!(s.token_in.balance(&s.user), 1_000);
+    assert_eq!(s.token_out.balance(&s.user), 0);
+}
@@ -2,7 +2,8 @@
 use soroban_sdk::token;
 #[allow(unused_imports)]
 use soroban_sdk::{Env, vec};
-use crate::tests::test_setup::{ HoopsTestEnvironment};
+use crate::tests::test_setup::{redeem_with_lp_auth_only, HoopsTestEnvironment};
+use crate::tests::test_setup::soroswap_adapter::{AdapterError, Capability, PoolKind};
 extern crate std;
 
 pub fn run_swap_exact_in(test_env: &HoopsTestEnvironment) {
@@ -175,15 +176,217 @@ pub fn run_remove_liquidity(test_env: &HoopsTestEnvironment, lp_amt: i128) {
     assert!(after_lp_balance < before_lp_balance, "User LP balance should decrease");
 }
 
+pub fn run_quote_path(test_env: &HoopsTestEnvironment) {
+    let env = &test_env.env;
+    env.mock_all_auths();
+    let user = &test_env.user;
+    let token_c_client = token::Client::new(&env, &test_env.tokens.client_c);
+    let soroswap_adapter_client = &test_env.adapters.soroswap;
+    let deadline = env.ledger().timestamp() + 100;
+    // A -> B -> C across both pairs
+    let path = vec![env, test_env.tokens.client_a.clone(), test_env.tokens.client_b.clone(), test_env.tokens.client_c.clone()];
+    let amount_in: i128 = 10_000_000;
+    let quoted_out = soroswap_adapter_client.quote_path_in(&path, &amount_in);
+    let needed_in = soroswap_adapter_client.quote_path_out(&path, &quoted_out);
+    std::println!("[SOROSWAP][quote_path] in = {}, quoted_out = {}, needed_in = {}", amount_in, quoted_out, needed_in);
+    assert!(quoted_out > 0);
+    assert!(needed_in <= amount_in);
+
+    let before_c = token_c_client.balance(user);
+    let out = soroswap_adapter_client.swap_exact_in(&amount_in, &quoted_out, &path, user, &deadline);
+    assert_eq!(out, quoted_out);
+    assert_eq!(token_c_client.balance(user), before_c + out);
+
+    // Single-hop quotes check the pool against the factory
+    let pair_bc = test_env.soroswap.pool_ids.get(1).unwrap();
+    let res = soroswap_adapter_client.try_quote_in(&pair_bc, &amount_in, &test_env.tokens.client_a, &test_env.tokens.client_b);
+    assert_eq!(res, Err(Ok(AdapterError::PairNotFound)));
+    let res = soroswap_adapter_client.try_quote_path_in(&vec![env, test_env.tokens.client_a.clone()], &amount_in);
+    assert_eq!(res, Err(Ok(AdapterError::InvalidPath)));
+}
+
+pub fn run_new_pair(test_env: &HoopsTestEnvironment) {
+    let env = &test_env.env;
+    env.mock_all_auths();
+    let user = &test_env.user;
+    let token_a = test_env.tokens.client_a.clone();
+    let token_c = test_env.tokens.client_c.clone();
+    let soroswap_adapter_client = &test_env.adapters.soroswap;
+    let deadline = env.ledger().timestamp() + 100;
+    assert_eq!(soroswap_adapter_client.get_pair(&token_a, &token_c), None);
+
+    // Adding liquidity to A/C creates the pair on the way
+    let amount: i128 = 100_000_000;
+    let (amt_a, amt_c, lp) = soroswap_adapter_client.add_liquidity(&token_a, &token_c, &amount, &amount, &0, &0, user, &deadline);
+    std::println!("[SOROSWAP][new_pair] amt_a = {}, amt_c = {}, lp = {}", amt_a, amt_c, lp);
+    assert_eq!((amt_a, amt_c), (amount, amount));
+    assert!(lp > 0);
+    let pair = soroswap_adapter_client.get_pair(&token_a, &token_c).unwrap();
+    assert_eq!(token::Client::new(env, &pair).balance(user), lp);
+
+    // And the new pair is accepted as an LP token
+    let (out_a, out_c) = soroswap_adapter_client.remove_liquidity(&pair, &lp, &0, &0, user, &deadline);
+    assert!(out_a > 0 && out_c > 0);
+}
+
+pub fn run_foreign_lp(test_env: &HoopsTestEnvironment) {
+    let env = &test_env.env;
+    env.mock_all_auths();
+    let user = &test_env.user;
+    let soroswap_adapter_client = &test_env.adapters.soroswap;
+    let deadline = env.ledger().timestamp() + 100;
+    // A Comet pool is not a Soroswap pair
+    let foreign = test_env.comet.pool_ids.get(0).unwrap();
+    let res = soroswap_adapter_client.try_remove_liquidity(&foreign, &1, &0, &0, user, &deadline);
+    assert_eq!(res, Err(Ok(AdapterError::PairNotFound)));
+}
+
+pub fn run_discovery(test_env: &HoopsTestEnvironment) {
+    let env = &test_env.env;
+    let soroswap_adapter_client = &test_env.adapters.soroswap;
+    let pair_ab = test_env.soroswap.pool_ids.get(0).unwrap();
+    let pair_bc = test_env.soroswap.pool_ids.get(1).unwrap();
+    let pools = soroswap_adapter_client.list_pools(&0, &100);
+    assert!(pools.contains(&pair_ab));
+    assert!(pools.contains(&pair_bc));
+    // Factory pairs are listed a page at a time, in creation order
+    assert_eq!(soroswap_adapter_client.list_pools(&0, &1), vec![env, pair_ab.clone()]);
+    assert_eq!(soroswap_adapter_client.list_pools(&1, &1), vec![env, pair_bc.clone()]);
+    assert_eq!(soroswap_adapter_client.list_pools(&pools.len(), &10).len(), 0);
+
+    let info = soroswap_adapter_client.get_pool_info(&pair_ab);
+    let pair = crate::tests::test_setup::soroswap_pair::SoroswapPairClient::new(env, &pair_ab);
+    let (reserve_0, reserve_1) = pair.get_reserves();
+    assert_eq!(info.lp_token, pair_ab);
+    assert_eq!(info.reserves, vec![env, reserve_0, reserve_1]);
+    assert_eq!(info.lp_supply, pair.total_supply());
+    assert_eq!(info.fee_bps, 30);
+    assert_eq!(info.kind, PoolKind::ConstantProduct);
+
+    assert!(soroswap_adapter_client.supports(&Capability::ExactOut));
+    assert!(soroswap_adapter_client.supports(&Capability::MultiHop));
+    assert!(!soroswap_adapter_client.supports(&Capability::Rewards));
+
+    // The router builds markets one page of pools at a time; running a page
+    // again refreshes its markets instead of adding new ones
+    env.mock_all_auths();
+    let router = &test_env.router;
+    let (token_a, token_b, token_c) = (&test_env.tokens.client_a, &test_env.tokens.client_b, &test_env.tokens.client_c);
+    router.add_adapter(&3, &soroswap_adapter_client.address);
+    assert_eq!(router.discover_pools(&3, &0, &1), 1);
+    assert_eq!(router.get_all_quotes(&1_000_000, token_a, token_b).len(), 1);
+    assert_eq!(router.get_all_quotes(&1_000_000, token_b, token_c).len(), 0);
+    assert_eq!(router.discover_pools(&3, &0, &2), 2);
+    assert_eq!(router.get_all_quotes(&1_000_000, token_a, token_b).len(), 1);
+    assert_eq!(router.get_all_quotes(&1_000_000, token_c, token_b).len(), 1);
+    assert_eq!(router.discover_pools(&3, &100, &1), 0);
+
+    // Pool info is forwarded for the account's position valuation
+    let forwarded = router.get_pool_info(&3, &pair_ab).unwrap();
+    assert_eq!((forwarded.reserves, forwarded.lp_supply), (info.reserves, info.lp_supply));
+    assert_eq!(router.get_pool_info(&99, &pair_ab), None);
+}
+
+pub fn run_error_codes(test_env: &HoopsTestEnvironment) {
+    let env = &test_env.env;
+    env.mock_all_auths();
+    let user = &test_env.user;
+    let soroswap_adapter_client = &test_env.adapters.soroswap;
+    let path = vec![env, test_env.tokens.client_a.clone(), test_env.tokens.client_b.clone()];
+    let amount_in: i128 = 10_000_000;
+    let deadline = env.ledger().timestamp() + 100;
+    let expired = env.ledger().timestamp().saturating_sub(1);
+    if env.ledger().timestamp() > 0 {
+        let res = soroswap_adapter_client.try_swap_exact_in(&amount_in, &0, &path, user, &expired);
+        assert_eq!(res, Err(Ok(AdapterError::DeadlinePassed)));
+    }
+    // Router slippage errors surface as MinAmountNotMet
+    let res = soroswap_adapter_client.try_swap_exact_in(&amount_in, &i128::MAX, &path, user, &deadline);
+    assert_eq!(res, Err(Ok(AdapterError::MinAmountNotMet)));
+    let res = soroswap_adapter_client.try_swap_exact_out(&amount_in, &1, &path, user, &deadline);
+    assert_eq!(res, Err(Ok(AdapterError::MinAmountNotMet)));
+    let res = soroswap_adapter_client.try_swap_exact_in(&amount_in, &0, &vec![env, test_env.tokens.client_a.clone()], user, &deadline);
+    assert_eq!(res, Err(Ok(AdapterError::InvalidPath)));
+}
+
+pub fn run_spot_price(test_env: &HoopsTestEnvironment) {
+    let env = &test_env.env;
+    env.mock_all_auths();
+    let adapter = &test_env.adapters.soroswap;
+    let pool = test_env.soroswap.pool_ids.get(0).unwrap();
+    let token_a = test_env.tokens.client_a.clone();
+    let token_b = test_env.tokens.client_b.clone();
+    let spot = adapter.get_spot_price(&pool, &token_a, &token_b);
+    let fee_bps = adapter.get_pool_info(&pool).fee_bps as i128;
+    // A small trade executes just below spot, by about the fee
+    let amount_in: i128 = 1_000_000;
+    let exec = adapter.quote_in(&pool, &amount_in, &token_a, &token_b) * 10_000_000 / amount_in;
+    std::println!("[SOROSWAP][spot_price] spot = {}, exec = {}, fee_bps = {}", spot, exec, fee_bps);
+    assert!(exec <= spot);
+    assert!(exec >= spot * (10_000 - fee_bps - 10) / 10_000);
+}
+
+pub fn run_admin(test_env: &HoopsTestEnvironment) {
+    let env = &test_env.env;
+    env.mock_all_auths();
+    let adapter = &test_env.adapters.soroswap;
+    let config = adapter.get_config();
+    assert_eq!(config.admin, test_env.admin);
+    assert_eq!(adapter.get_admin(), test_env.admin);
+    // No router registered until the admin sets one
+    assert_eq!(adapter.try_get_router(), Err(Ok(AdapterError::NotInitialized)));
+    adapter.set_router(&test_env.router.address);
+    assert_eq!(adapter.get_router(), test_env.router.address);
+    let res = adapter.try_set_ttl(&0, &config.ttl_bump);
+    assert_eq!(res, Err(Ok(AdapterError::InvalidArgument)));
+    adapter.set_ttl(&(config.ttl_thresh / 2), &config.ttl_bump);
+    assert_eq!(adapter.get_config().ttl_thresh, config.ttl_thresh / 2);
+}
+
+pub fn run_router_redeem(test_env: &HoopsTestEnvironment) {
+    let env = &test_env.env;
+    env.mock_all_auths();
+    let (token_a, token_b) = (&test_env.tokens.client_a, &test_env.tokens.client_b);
+    let pair_ab = test_env.soroswap.pool_ids.get(0).unwrap();
+    test_env.router.add_adapter(&3, &test_env.adapters.soroswap.address);
+    test_env.router.discover_pools(&3, &0, &1);
+    // Setup seeded the pair from the admin
+    let lp_amount: i128 = 1_000_000;
+    token::Client::new(env, &pair_ab).transfer(&test_env.admin, &test_env.user, &lp_amount);
+    let (out_a, out_b) = redeem_with_lp_auth_only(test_env, &pair_ab, lp_amount, token_a, token_b);
+    assert!(out_a > 0 && out_b > 0);
+}
+
 pub fn test_soroswap_adapter(test_env: &HoopsTestEnvironment, failures: i32) -> i32 {
     use std::panic::AssertUnwindSafe;
     let mut failures = failures;
+    if let Err(e) = std::panic::catch_unwind(AssertUnwindSafe(|| run_admin(&test_env))) {
+        std::println!("[FAIL][SOROSWAP][admin]: {:?}", e); failures += 1;
+    }
     if let Err(e) = std::panic::catch_unwind(AssertUnwindSafe(|| run_swap_exact_in(&test_env))) {
         std::println!("[FAIL][SOROSWAP][swap_exact_in]: {:?}", e); failures += 1;
     }
     if let Err(e) = std::panic::catch_unwind(AssertUnwindSafe(|| run_swap_exact_out(&test_env))) {
         std::println!("[FAIL][SOROSWAP][swap_exact_out]: {:?}", e); failures += 1;
     }
+    if let Err(e) = std::panic::catch_unwind(AssertUnwindSafe(|| run_discovery(&test_env))) {
+        std::println!("[FAIL][SOROSWAP][discovery]: {:?}", e); failures += 1;
+    }
+    if let Err(e) = std::panic::catch_unwind(AssertUnwindSafe(|| run_error_codes(&test_env))) {
+        std::println!("[FAIL][SOROSWAP][error_codes]: {:?}", e); failures += 1;
+    }
+    if let Err(e) = std::panic::catch_unwind(AssertUnwindSafe(|| run_spot_price(&test_env))) {
+        std::println!("[FAIL][SOROSWAP][spot_price]: {:?}", e); failures += 1;
+    }
+    if let Err(e) = std::panic::catch_unwind(AssertUnwindSafe(|| run_quote_path(&test_env))) {
+        std::println!("[FAIL][SOROSWAP][quote_path]: {:?}", e); failures += 1;
+    }
+    if let Err(e) = std::panic::catch_unwind(AssertUnwindSafe(|| run_new_pair(&test_env))) {
+        std::println!("[FAIL][SOROSWAP][new_pair]: {:?}", e); failures += 1;
+    }
+    if let Err(e) = std::panic::catch_unwind(AssertUnwindSafe(|| run_foreign_lp(&test_env))) {
+        std::println!("[FAIL][SOROSWAP][foreign_lp]: {:?}", e); failures += 1;
+    }
     // Run add_liquidity and capture the LP token amount
     let lp = match std::panic::catch_unwind(AssertUnwindSafe(|| run_add_liquidity(&test_env))) {
         Ok(lp) => lp,
@@ -200,6 +403,9 @@ pub fn test_soroswap_adapter(test_env: &HoopsTestEnvironment, failures: i32) ->
     } else {
         std::println!("[INFO][SOROSWAP] add liquidity failed, skipping remove");
     }
+    if let Err(e) = std::panic::catch_unwind(AssertUnwindSafe(|| run_router_redeem(&test_env))) {
+        std::println!("[FAIL][SOROSWAP][router_redeem]: {:?}", e); failures += 1;
+    }
     std::println!("[SOROSWAP] Test results: {} failures", failures);
     if failures > 0 {
         panic!("{} Soroswap adapter subtests failed. See log for details.", failures);
@@ -653,6 +653,18 @@ pub fn setup_aqua_environment(
     )
 }
 
+/// Looks up the Aqua router's pool index hash for `pool`.
+pub fn aqua_pool_index(env: &Env, aqua_router_id: &Address, pool: &Address) -> BytesN<32> {
+    let aqua_router = AquaRouterClient::new(env, aqua_router_id);
+    let tokens = AquaPoolClient::new(env, pool).get_tokens();
+    for (pool_index, pool_address) in aqua_router.get_pools(&tokens).iter() {
+        if pool_address == *pool {
+            return pool_index;
+        }
+    }
+    panic!("pool not registered in the Aqua router");
+}
+
 pub fn setup_comet_environment(
     env: &Env,
     admin: &Address,
@@ -1213,10 +1225,12 @@ impl<'a> HoopsTestEnvironment<'a> {
         std::println!("[SETUP] Comet environment ready");
 
         // --- Deploy Adapters ---
+        // Token A stands in for USDC in the adapters' core config.
+        let usdc = token_a_client.address.clone();
         std::println!("[SETUP] Deploying Soroswap adapter");
         let soroswap_adapter_id = env.register(SOROSWAP_ADAPTER_WASM, ());
         let soroswap_adapter = SoroswapAdapterClient::new(&env, &soroswap_adapter_id);
-        soroswap_adapter.initialize(&3, &soroswap_router_id);
+        soroswap_adapter.initialize(&3, &soroswap_router_id, &admin, &usdc);
         std::println!(
             "[LOG] Soroswap adapter deployed at: {:?}",
             soroswap_adapter_id
@@ -1226,7 +1240,7 @@ impl<'a> HoopsTestEnvironment<'a> {
         std::println!("[SETUP] Deploying Aqua adapter");
         let aqua_adapter_id = env.register(AQUA_ADAPTER_WASM, ());
         let aqua_adapter = AquaAdapterClient::new(&env, &aqua_adapter_id);
-        aqua_adapter.initialize(&0, &aqua_router_id);
+        aqua_adapter.initialize(&0, &aqua_router_id, &admin, &usdc);
         std::println!("[LOG] Aqua adapter deployed at: {:?}", aqua_adapter_id);
         std::println!("[SETUP] Aqua adapter initialized");
   // Register Aqua pools with the adapter and log
@@ -1242,16 +1256,18 @@ impl<'a> HoopsTestEnvironment<'a> {
         let pool_info_ab = AquaPoolInfo {
             pool_address: aqua_pool_ab_address.clone(),
             lp_token_address: aqua_pool_ab_lp_token_address.clone(),
+            pool_index: aqua_pool_index(&env, &aqua_router_id, &aqua_pool_ab_address),
         };
-        aqua_adapter.set_pool_for_tokens(&tokens_ab, &pool_info_ab);
+        aqua_adapter.set_pool_for_tokens(&admin, &tokens_ab, &pool_info_ab);
         std::println!("[SETUP] Registered Aqua pool {:?} for tokens {:?}, with lp: {:?}", aqua_pool_ab_address, tokens_ab, aqua_pool_ab_lp_token_address);
 
         let tokens_bc = Vec::from_array(&env, [token_b_client.address.clone(), token_c_client.address.clone()]);
         let pool_info_bc = AquaPoolInfo {
             pool_address: aqua_pool_bc_address.clone(),
-                        lp_token_address: aqua_pool_bc_lp_token_address.clone(),
+            lp_token_address: aqua_pool_bc_lp_token_address.clone(),
+            pool_index: aqua_pool_index(&env, &aqua_router_id, &aqua_pool_bc_address),
         };
-        aqua_adapter.set_pool_for_tokens(&tokens_bc, &pool_info_bc);
+        aqua_adapter.set_pool_for_tokens(&admin, &tokens_bc, &pool_info_bc);
         std::println!("[SETUP] Registered Aqua pool {:?} for tokens {:?}, with lp {:?}", aqua_pool_bc_address, tokens_bc, aqua_pool_bc_lp_token_address);
         std::println!("[SETUP] Aqua environment ready");
 
@@ -1260,9 +1276,9 @@ impl<'a> HoopsTestEnvironment<'a> {
         let phoenix_adapter_id = env.register(PHOENIX_ADAPTER_WASM, ());
         let phoenix_adapter = PhoenixAdapterClient::new(&env, &phoenix_adapter_id);
         if let Some(first_phoenix_pool) = phoenix_amm.pool_ids.get(0) {
-            phoenix_adapter.initialize(&2, &first_phoenix_pool);
+            phoenix_adapter.initialize(&2, &first_phoenix_pool, &admin, &usdc);
         } else {
-            phoenix_adapter.initialize(&2, &phoenix_factory_id);
+            phoenix_adapter.initialize(&2, &phoenix_factory_id, &admin, &usdc);
         }
         std::println!(
             "[LOG] Phoenix adapter deployed at: {:?}",
@@ -1274,9 +1290,9 @@ impl<'a> HoopsTestEnvironment<'a> {
         let comet_adapter_id = env.register(COMET_ADAPTER_WASM, ());
         let comet_adapter = CometAdapterClient::new(&env, &comet_adapter_id);
         if let Some(first_comet_pool) = comet_amm.pool_ids.get(0) {
-            comet_adapter.initialize(&1, &first_comet_pool);
+            comet_adapter.initialize(&1, &first_comet_pool, &admin, &usdc);
         } else {
-            comet_adapter.initialize(&1, &comet_amm.factory_id);
+            comet_adapter.initialize(&1, &comet_amm.factory_id, &admin, &usdc);
         }
         std::println!("[LOG] Comet adapter deployed at: {:?}", comet_adapter_id);
         std::println!("[SETUP] Comet adapter initialized");
@@ -1285,7 +1301,7 @@ impl<'a> HoopsTestEnvironment<'a> {
         for pool_addr in comet_amm.pool_ids.iter() {
             let pool_client = CometPoolClient::new(&env, &pool_addr);
             let tokens = pool_client.get_tokens();
-            comet_adapter.set_pool_for_tokens(&tokens, &pool_addr);
+            comet_adapter.set_pool_for_tokens(&admin, &tokens, &pool_addr);
             std::println!(
                 "[SETUP] Registered Comet pool {:?} for tokens {:?}",
                 pool_addr,
@@ -1333,6 +1349,52 @@ impl<'a> HoopsTestEnvironment<'a> {
     }
 }
 
+/// Redeems `lp_amount` of the user's `lp_token` through the router with no
+/// auth but what a contract account can pre-authorize for itself: the
+/// `redeem_liquidity` call and the LP transfer into the router. The token's
+/// market must already be known to the router. Returns what the user got of
+/// `token_a` and `token_b`.
+pub fn redeem_with_lp_auth_only(
+    test_env: &HoopsTestEnvironment,
+    lp_token: &Address,
+    lp_amount: i128,
+    token_a: &Address,
+    token_b: &Address,
+) -> (i128, i128) {
+    use soroban_sdk::testutils::{MockAuth, MockAuthInvoke};
+    let env = &test_env.env;
+    let user = &test_env.user;
+    let router = &test_env.router.address;
+    let deadline = env.ledger().timestamp() + 100;
+    let (lp, a, b) = (
+        token::Client::new(env, lp_token),
+        token::Client::new(env, token_a),
+        token::Client::new(env, token_b),
+    );
+    let (before_lp, before_a, before_b) = (lp.balance(user), a.balance(user), b.balance(user));
+    let held = |r: &Address| (a.balance(r), b.balance(r), lp.balance(r));
+    let router_before = held(router);
+    env.mock_auths(&[MockAuth {
+        address: user,
+        invoke: &MockAuthInvoke {
+            contract: router,
+            fn_name: "redeem_liquidity",
+            args: (lp_token.clone(), lp_amount, user.clone(), deadline).into_val(env),
+            sub_invokes: &[MockAuthInvoke {
+                contract: lp_token,
+                fn_name: "transfer",
+                args: (user.clone(), router.clone(), lp_amount).into_val(env),
+                sub_invokes: &[],
+            }],
+        },
+    }]);
+    test_env.router.redeem_liquidity(lp_token, &lp_amount, user, &deadline);
+    assert_eq!(lp.balance(user), before_lp - lp_amount);
+    // Nothing is left behind in the router
+    assert_eq!(held(router), router_before);
+    (a.balance(user) - before_a, b.balance(user) - before_b)
+}
+
 /*
 #[test]
 fn test_environment_setup_placeholders() {
@@ -1346,6 +1408,8 @@ fn test_setup_verification() {
     // Soroswap
     let pair_ab = &test_env.soroswap.pool_ids.get(0).unwrap();
     let pair_bc = &test_env.soroswap.pool_ids.get(1).unwrap();
+    // Setup seeds Soroswap and Phoenix from the admin, Aqua and Comet from the user
+    let admin = &test_env.admin;
     let user = &test_env.user;
     let token_a = &test_env.tokens.client_a;
     let token_b = &test_env.tokens.client_b;
@@ -1353,10 +1417,10 @@ fn test_setup_verification() {
     std::println!("[TEST] Verifying Soroswap Pool AB reserves and LP balance");
     assert!(get_reserve_soroswap(pair_ab, token_a) > 0);
     assert!(get_reserve_soroswap(pair_ab, token_b) > 0);
-    assert!(get_lp_balance_soroswap(pair_ab, user) > 0);
+    assert!(get_lp_balance_soroswap(pair_ab, admin) > 0);
     assert!(get_reserve_soroswap(pair_bc, token_b) > 0);
     assert!(get_reserve_soroswap(pair_bc, token_c) > 0);
-    assert!(get_lp_balance_soroswap(pair_bc, user) > 0);
+    assert!(get_lp_balance_soroswap(pair_bc, admin) > 0);
 
     // Aqua
     let pool_ab = &test_env.aqua.pool_ids.get(0).unwrap();
@@ -1374,10 +1438,10 @@ fn test_setup_verification() {
     std::println!("[TEST] Verifying Phoenix Pool AB reserves and LP balance");
     assert!(get_reserve_phoenix(pho_ab, token_a) > 0);
     assert!(get_reserve_phoenix(pho_ab, token_b) > 0);
-    assert!(get_lp_balance_phoenix(pho_ab, user) > 0);
+    assert!(get_lp_balance_phoenix(pho_ab, admin) > 0);
     assert!(get_reserve_phoenix(pho_bc, token_b) > 0);
     assert!(get_reserve_phoenix(pho_bc, token_c) > 0);
-    assert!(get_lp_balance_phoenix(pho_bc, user) > 0);
+    assert!(get_lp_balance_phoenix(pho_bc, admin) > 0);
     // Comet
     let comet_ab = &test_env.comet.pool_ids.get(0).unwrap();
     let comet_bc = &test_env.comet.pool_ids.get(1).unwrap();
@@ -1,7 +1,7 @@
-use soroban_sdk::{contracttype, Address};
+use soroban_sdk::{contracttype, Address, Vec};
 
 #[contracttype]
-#[derive(Clone, Debug, Eq, PartialEq)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
 pub enum PoolType {
     Soroswap = 0,
     Aqua = 1,
@@ -9,7 +9,9 @@ pub enum PoolType {
     Comet = 3,
 }
 
-#[contracttype]
+/// Router storage only; not exported so it does not clash with the
+/// adapters' `CoreConfig` in the router's contract spec.
+#[contracttype(export = false)]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct CoreConfig {
     pub admin: Address,
@@ -52,3 +54,67 @@ pub struct SwapQuote {
     pub pool_type: u32,
     pub lp_token: Address,
 }
+
+/// Direction of a quote: `amount` is the input (`ExactIn`) or the desired
+/// output (`ExactOut`).
+#[contracttype]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[repr(u32)]
+pub enum QuoteKind {
+    ExactIn = 0,
+    ExactOut = 1,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct QuoteRequest {
+    pub token_in: Address,
+    pub token_out: Address,
+    pub amount: i128,
+    pub kind: QuoteKind,
+}
+
+/// One market's answer to a `QuoteRequest`. Prices are `token_out` per
+/// `token_in`, scaled by 1e7. `error` is 0 on success, otherwise the
+/// adapter's error code with zero amounts.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MarketQuote {
+    pub adapter_id: i128,
+    pub pool_address: Address,
+    pub amount_in: i128,
+    pub amount_out: i128,
+    pub error: u32,
+    pub spot_price: i128,
+    pub exec_price: i128,
+    pub price_impact_bps: i128,
+    pub fee_bps: u32,
+    /// Fee charged on `amount_in`, in `token_in`.
+    pub fee_amount: i128,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct QuoteResult {
+    pub request: QuoteRequest,
+    pub quotes: Vec<MarketQuote>,
+}
+
+/// Outcome of a routed swap: the market that filled it and the amounts.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SwapResult {
+    pub adapter_id: i128,
+    pub pool_address: Address,
+    pub amount_in: i128,
+    pub amount_out: i128,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RewardPosition {
+    pub adapter_id: i128,
+    pub pool_address: Address,
+    pub lp_token: Address,
+    pub pending: i128,
+}